@@ -0,0 +1,1052 @@
+use std::{cmp::Ordering, collections::HashMap, fmt, mem};
+
+use anyhow::Result;
+
+use crate::{i18n::trf, memory::row_size, resolve_index, FieldSelector};
+
+// A mode that has to see every selected record before it can print
+// anything.  Records arrive in input order across all inputs; once
+// they're all in, finish() hands back the rows to print.
+pub trait Aggregate: fmt::Debug {
+    fn add(&mut self, fields: &[&str]) -> Result<()>;
+    fn finish(&mut self) -> Result<Vec<Vec<String>>>;
+    // Roughly how many bytes it is holding on to, for --max-memory.
+    fn footprint(&self) -> usize;
+    // Whether the first row finish() will hand back is a header, which
+    // has to stay first whatever is done with the rest.
+    fn headed(&self) -> bool {
+        false
+    }
+}
+
+// Swap rows and columns, so each output line holds one field position
+// from every record.  Short records are padded with empty fields.
+#[derive(Debug, Default)]
+pub struct Transpose {
+    rows: Vec<Vec<String>>,
+    bytes: usize,
+}
+
+impl Aggregate for Transpose {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        self.bytes += row_size(fields);
+        self.rows
+            .push(fields.iter().map(|val| String::from(*val)).collect());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        self.bytes = 0;
+        let rows = std::mem::take(&mut self.rows);
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        Ok((0..width)
+            .map(|col| {
+                rows.iter()
+                    .map(|row| row.get(col).cloned().unwrap_or_default())
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn footprint(&self) -> usize {
+        self.bytes
+    }
+}
+
+// One of the summaries --agg can compute per group.  Field indexes
+// address the selected record, like the --group-by key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggFn {
+    Count,
+    Sum(isize),
+    Min(isize),
+    Max(isize),
+    Mean(isize),
+}
+
+// Parse one summary, like `count` or `sum(3)`.
+pub fn parse_agg(agg: &str) -> Result<AggFn> {
+    let agg = agg.trim();
+    if agg == "count" {
+        return Ok(AggFn::Count);
+    }
+    let invalid = || format_err!(trf("invalid aggregate '{}'", &[agg]));
+    let (name, arg) = agg
+        .strip_suffix(')')
+        .and_then(|agg| agg.split_once('('))
+        .ok_or_else(invalid)?;
+    let idx = arg.trim().parse::<isize>().map_err(|_| invalid())?;
+    match name.trim() {
+        "sum" => Ok(AggFn::Sum(idx)),
+        "min" => Ok(AggFn::Min(idx)),
+        "max" => Ok(AggFn::Max(idx)),
+        "mean" | "avg" => Ok(AggFn::Mean(idx)),
+        _ => Err(invalid()),
+    }
+}
+
+// Numeric cells are parsed leniently: anything that isn't a number is
+// left out of sums, minimums and the like rather than being an error.
+pub fn parse_number(s: &str) -> Option<f64> {
+    s.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+// How numbers are written in the input, for --numeric-locale: plainly,
+// like `1234.56`, or grouped in thousands the way a locale writes them,
+// like `1,234.56` (en), `1.234,56` (de) or `1 234,56` (fr).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum NumericLocale {
+    #[default]
+    C,
+    En,
+    De,
+    Fr,
+}
+
+impl NumericLocale {
+    // Parse a number as the locale writes it.  Grouping has to be in
+    // threes, so that under `de` a `1.5` is text rather than fifteen.
+    pub fn parse(self, s: &str) -> Option<f64> {
+        let (groups, decimal): (&[char], char) = match self {
+            NumericLocale::C => return parse_number(s),
+            NumericLocale::En => (&[','], '.'),
+            NumericLocale::De => (&['.'], ','),
+            // Spaces, and the no-break spaces French typography uses.
+            NumericLocale::Fr => (&[' ', '\u{a0}', '\u{202f}'], ','),
+        };
+        let s = s.trim();
+        let (sign, unsigned) = match s.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (whole, fraction) = match unsigned.split_once(decimal) {
+            Some((whole, fraction)) if !fraction.is_empty() => (whole, Some(fraction)),
+            Some(_) => return None,
+            None => (unsigned, None),
+        };
+        let digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        let parts: Vec<&str> = whole.split(groups).collect();
+        let grouped = parts.len() == 1
+            || (matches!(parts[0].len(), 1..=3) && parts[1..].iter().all(|part| part.len() == 3));
+        if !grouped || !parts.iter().all(|part| digits(part)) || !fraction.is_none_or(digits) {
+            return None;
+        }
+        if whole.is_empty() && fraction.is_none() {
+            return None;
+        }
+        let plain = format!("{}{}.{}", sign, parts.concat(), fraction.unwrap_or("0"));
+        parse_number(&plain)
+    }
+}
+
+// Running state for one AggFn within one group.
+#[derive(Debug, Clone, Default)]
+struct Accumulator {
+    rows: u64,
+    numbers: u64,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Accumulator {
+    fn add(&mut self, agg: AggFn, fields: &[&str], locale: NumericLocale) {
+        self.rows += 1;
+        let idx = match agg {
+            AggFn::Count => return,
+            AggFn::Sum(idx) | AggFn::Min(idx) | AggFn::Max(idx) | AggFn::Mean(idx) => idx,
+        };
+        let number =
+            resolve_index(idx, fields.len()).and_then(|offset| locale.parse(fields[offset]));
+        if let Some(n) = number {
+            self.numbers += 1;
+            self.sum += n;
+            self.min = Some(self.min.map_or(n, |min| min.min(n)));
+            self.max = Some(self.max.map_or(n, |max| max.max(n)));
+        }
+    }
+
+    fn result(&self, agg: AggFn) -> String {
+        let value = match agg {
+            AggFn::Count => return self.rows.to_string(),
+            AggFn::Sum(_) => Some(self.sum),
+            AggFn::Min(_) => self.min,
+            AggFn::Max(_) => self.max,
+            AggFn::Mean(_) if self.numbers > 0 => Some(self.sum / self.numbers as f64),
+            AggFn::Mean(_) => None,
+        };
+        value.map(|n| n.to_string()).unwrap_or_default()
+    }
+}
+
+impl AggFn {
+    // The summary as --agg spells it, naming its field from the header
+    // where there is one.
+    fn label(self, names: &[String]) -> String {
+        let (name, idx) = match self {
+            AggFn::Count => return String::from("count"),
+            AggFn::Sum(idx) => ("sum", idx),
+            AggFn::Min(idx) => ("min", idx),
+            AggFn::Max(idx) => ("max", idx),
+            AggFn::Mean(idx) => ("mean", idx),
+        };
+        match resolve_index(idx, names.len()) {
+            Some(offset) => format!("{}({})", name, names[offset]),
+            None => format!("{}({})", name, idx),
+        }
+    }
+}
+
+// Hash aggregation keyed on some fields of each record, printing the key
+// followed by each summary, one row per group in order of first
+// appearance.  Where the first record is the header, it isn't a group
+// but heads the output, naming the key's fields and the summaries.
+#[derive(Debug)]
+pub struct GroupBy {
+    key: FieldSelector,
+    aggs: Vec<AggFn>,
+    index: HashMap<Vec<String>, usize>,
+    groups: Vec<(Vec<String>, Vec<Accumulator>)>,
+    locale: NumericLocale,
+    named: bool,
+    header: Option<Vec<String>>,
+    bytes: usize,
+}
+
+impl GroupBy {
+    pub fn new(key: FieldSelector, aggs: Vec<AggFn>) -> Self {
+        GroupBy {
+            key,
+            aggs,
+            index: HashMap::new(),
+            groups: vec![],
+            locale: NumericLocale::C,
+            named: false,
+            header: None,
+            bytes: 0,
+        }
+    }
+
+    pub fn with_locale(mut self, locale: NumericLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub fn with_header(mut self, named: bool) -> Self {
+        self.named = named;
+        self
+    }
+}
+
+impl Aggregate for GroupBy {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        if self.named && self.header.is_none() {
+            let names: Vec<String> = fields.iter().map(|name| String::from(*name)).collect();
+            let mut header: Vec<String> = self
+                .key
+                .select(fields)
+                .into_iter()
+                .map(String::from)
+                .collect();
+            header.extend(self.aggs.iter().map(|agg| agg.label(&names)));
+            self.header = Some(header);
+            return Ok(());
+        }
+        let key: Vec<String> = self
+            .key
+            .select(fields)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let groups = &mut self.groups;
+        let aggs = &self.aggs;
+        let bytes = &mut self.bytes;
+        let group = *self.index.entry(key).or_insert_with_key(|key| {
+            // The key is held twice, in the index and with the group.
+            *bytes += 2 * row_size(key) + aggs.len() * mem::size_of::<Accumulator>();
+            groups.push((key.clone(), vec![Accumulator::default(); aggs.len()]));
+            groups.len() - 1
+        });
+        for (acc, agg) in self.groups[group].1.iter_mut().zip(self.aggs.iter()) {
+            acc.add(*agg, fields, self.locale);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        self.index.clear();
+        self.bytes = 0;
+        let aggs = &self.aggs;
+        Ok(self
+            .header
+            .take()
+            .into_iter()
+            .chain(
+                std::mem::take(&mut self.groups)
+                    .into_iter()
+                    .map(|(mut key, accs)| {
+                        key.extend(
+                            accs.iter()
+                                .zip(aggs.iter())
+                                .map(|(acc, agg)| acc.result(*agg)),
+                        );
+                        key
+                    }),
+            )
+            .collect())
+    }
+
+    fn footprint(&self) -> usize {
+        self.bytes
+    }
+
+    fn headed(&self) -> bool {
+        self.header.is_some()
+    }
+}
+
+// Tally each distinct record, printing its count followed by it, most
+// frequent first, as `sort | uniq -c | sort -rn` would.  Records seen
+// as often as each other keep the order they first appeared in.  Where
+// the first record is the header, it isn't counted but heads the
+// output, under `count`.
+#[derive(Debug, Default)]
+pub struct Count {
+    index: HashMap<Vec<String>, usize>,
+    tallies: Vec<(Vec<String>, u64)>,
+    named: bool,
+    header: Option<Vec<String>>,
+    bytes: usize,
+}
+
+impl Count {
+    pub fn with_header(mut self, named: bool) -> Self {
+        self.named = named;
+        self
+    }
+}
+
+impl Aggregate for Count {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        if self.named && self.header.is_none() {
+            let names = fields.iter().map(|name| String::from(*name));
+            self.header = Some(
+                std::iter::once(String::from("count"))
+                    .chain(names)
+                    .collect(),
+            );
+            return Ok(());
+        }
+        let record: Vec<String> = fields.iter().map(|val| String::from(*val)).collect();
+        let tallies = &mut self.tallies;
+        let bytes = &mut self.bytes;
+        let tally = *self.index.entry(record).or_insert_with_key(|record| {
+            // The record is held twice, in the index and with its tally.
+            *bytes += 2 * row_size(record) + mem::size_of::<u64>();
+            tallies.push((record.clone(), 0));
+            tallies.len() - 1
+        });
+        self.tallies[tally].1 += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        self.index.clear();
+        self.bytes = 0;
+        let mut tallies = std::mem::take(&mut self.tallies);
+        tallies.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        let rows = tallies.into_iter().map(|(record, count)| {
+            let mut row = vec![count.to_string()];
+            row.extend(record);
+            row
+        });
+        Ok(self.header.take().into_iter().chain(rows).collect())
+    }
+
+    fn footprint(&self) -> usize {
+        self.bytes
+    }
+
+    fn headed(&self) -> bool {
+        self.header.is_some()
+    }
+}
+
+// The summaries --stats gives each column, in the order it prints them.
+const STATS: [&str; 5] = ["count", "min", "max", "mean", "sum"];
+
+// Summarize each column of the selected records as numbers: how many
+// of its values are numbers, and their minimum, maximum, mean and sum,
+// one row per column under a row naming the summaries.  A column is
+// labelled by its position, or by its name where the first record is
+// the header.
+#[derive(Debug)]
+pub struct Stats {
+    names: Option<Vec<String>>,
+    named: bool,
+    columns: Vec<Accumulator>,
+    locale: NumericLocale,
+}
+
+impl Stats {
+    pub fn new(named: bool) -> Self {
+        Stats {
+            names: None,
+            named,
+            columns: vec![],
+            locale: NumericLocale::C,
+        }
+    }
+
+    pub fn with_locale(mut self, locale: NumericLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+}
+
+impl Aggregate for Stats {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        if self.named && self.names.is_none() {
+            self.names = Some(fields.iter().map(|name| String::from(*name)).collect());
+            return Ok(());
+        }
+        if fields.len() > self.columns.len() {
+            self.columns.resize(fields.len(), Accumulator::default());
+        }
+        for (col, acc) in self.columns.iter_mut().enumerate().take(fields.len()) {
+            acc.add(AggFn::Sum(col as isize + 1), fields, self.locale);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        let names = self.names.take().unwrap_or_default();
+        let mut rows = vec![std::iter::once("field")
+            .chain(STATS)
+            .map(String::from)
+            .collect()];
+        for (col, acc) in std::mem::take(&mut self.columns).iter().enumerate() {
+            let label = names.get(col).cloned().unwrap_or((col + 1).to_string());
+            rows.push(vec![
+                label,
+                acc.numbers.to_string(),
+                acc.result(AggFn::Min(0)),
+                acc.result(AggFn::Max(0)),
+                acc.result(AggFn::Mean(0)),
+                acc.result(AggFn::Sum(0)),
+            ]);
+        }
+        Ok(rows)
+    }
+
+    fn footprint(&self) -> usize {
+        self.columns.len() * mem::size_of::<Accumulator>()
+    }
+
+    fn headed(&self) -> bool {
+        true
+    }
+}
+
+// What --pivot asked for: which fields label the rows and columns of
+// the table, and what goes in each cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PivotSpec {
+    pub rows: isize,
+    pub cols: isize,
+    pub values: AggFn,
+}
+
+// Parse `rows=N,cols=M[,values=AGG]`, where AGG is any --agg summary.
+pub fn parse_pivot(s: &str) -> Result<PivotSpec> {
+    let invalid = || format_err!(trf("invalid pivot '{}'", &[s]));
+    let (mut rows, mut cols, mut values) = (None, None, AggFn::Count);
+    for part in s.split(',') {
+        let (key, val) = part.split_once('=').ok_or_else(invalid)?;
+        match key.trim() {
+            "rows" => rows = Some(val.trim().parse::<isize>().map_err(|_| invalid())?),
+            "cols" => cols = Some(val.trim().parse::<isize>().map_err(|_| invalid())?),
+            "values" => values = parse_agg(val)?,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(PivotSpec {
+        rows: rows.ok_or_else(invalid)?,
+        cols: cols.ok_or_else(invalid)?,
+        values,
+    })
+}
+
+// A cross-tab of two fields, with distinct values of one down the side
+// and of the other across the top, in order of first appearance.
+#[derive(Debug)]
+pub struct Pivot {
+    spec: PivotSpec,
+    row_keys: Vec<String>,
+    col_keys: Vec<String>,
+    row_index: HashMap<String, usize>,
+    col_index: HashMap<String, usize>,
+    cells: HashMap<(usize, usize), Accumulator>,
+    locale: NumericLocale,
+    // What the row and column keys take up.
+    key_bytes: usize,
+}
+
+impl Pivot {
+    pub fn new(spec: PivotSpec) -> Self {
+        Pivot {
+            spec,
+            row_keys: vec![],
+            col_keys: vec![],
+            row_index: HashMap::new(),
+            col_index: HashMap::new(),
+            cells: HashMap::new(),
+            locale: NumericLocale::C,
+            key_bytes: 0,
+        }
+    }
+
+    pub fn with_locale(mut self, locale: NumericLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+}
+
+// Find a key's position, adding it to the end if it's new, and to the
+// bytes the keys take up.
+fn intern(
+    key: &str,
+    keys: &mut Vec<String>,
+    index: &mut HashMap<String, usize>,
+    bytes: &mut usize,
+) -> usize {
+    if let Some(pos) = index.get(key) {
+        return *pos;
+    }
+    *bytes += 2 * row_size(&[key]);
+    keys.push(String::from(key));
+    index.insert(String::from(key), keys.len() - 1);
+    keys.len() - 1
+}
+
+impl Aggregate for Pivot {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        let row = resolve_index(self.spec.rows, fields.len());
+        let col = resolve_index(self.spec.cols, fields.len());
+        if let (Some(row), Some(col)) = (row, col) {
+            let bytes = &mut self.key_bytes;
+            let row = intern(fields[row], &mut self.row_keys, &mut self.row_index, bytes);
+            let col = intern(fields[col], &mut self.col_keys, &mut self.col_index, bytes);
+            self.cells
+                .entry((row, col))
+                .or_default()
+                .add(self.spec.values, fields, self.locale);
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        let mut table = vec![];
+        let mut header = vec![String::new()];
+        header.extend(self.col_keys.iter().cloned());
+        table.push(header);
+        let empty = Accumulator::default();
+        for (row, row_key) in self.row_keys.iter().enumerate() {
+            let mut line = vec![row_key.clone()];
+            line.extend((0..self.col_keys.len()).map(|col| {
+                self.cells
+                    .get(&(row, col))
+                    .unwrap_or(&empty)
+                    .result(self.spec.values)
+            }));
+            table.push(line);
+        }
+        Ok(table)
+    }
+
+    fn footprint(&self) -> usize {
+        self.key_bytes + self.cells.len() * mem::size_of::<((usize, usize), Accumulator)>()
+    }
+
+    fn headed(&self) -> bool {
+        true
+    }
+}
+
+// What --sort-by asked for: the output column to order by, and how.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortSpec {
+    pub field: isize,
+    pub numeric: bool,
+    pub descending: bool,
+}
+
+// Parse `N[:numeric][:desc]`.
+pub fn parse_sort(s: &str) -> Result<SortSpec> {
+    let invalid = || format_err!(trf("invalid sort key '{}'", &[s]));
+    let mut parts = s.split(':');
+    let field = parts
+        .next()
+        .and_then(|field| field.trim().parse::<isize>().ok())
+        .filter(|field| *field != 0)
+        .ok_or_else(invalid)?;
+    let mut spec = SortSpec {
+        field,
+        numeric: false,
+        descending: false,
+    };
+    for part in parts {
+        match part.trim() {
+            "numeric" | "n" => spec.numeric = true,
+            "desc" | "r" => spec.descending = true,
+            "asc" => spec.descending = false,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(spec)
+}
+
+// Hold every output row back and print them in order of one column,
+// after whatever aggregate is in use has had its say.  The sort is
+// stable, so rows with equal keys keep their input order.  Numerically,
+// values that aren't numbers sort after those that are.  A header,
+// whether the first record or the aggregate's, stays on top.
+#[derive(Debug)]
+pub struct Sort {
+    spec: SortSpec,
+    inner: Option<Box<dyn Aggregate>>,
+    rows: Vec<Vec<String>>,
+    locale: NumericLocale,
+    named: bool,
+    header: Option<Vec<String>>,
+    bytes: usize,
+}
+
+impl Sort {
+    pub fn new(spec: SortSpec, inner: Option<Box<dyn Aggregate>>) -> Self {
+        Sort {
+            spec,
+            inner,
+            rows: vec![],
+            locale: NumericLocale::C,
+            named: false,
+            header: None,
+            bytes: 0,
+        }
+    }
+
+    pub fn with_locale(mut self, locale: NumericLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    // Without an aggregate of its own, which would see to the header.
+    pub fn with_header(mut self, named: bool) -> Self {
+        self.named = named;
+        self
+    }
+}
+
+impl Aggregate for Sort {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        match self.inner {
+            Some(ref mut inner) => inner.add(fields),
+            None if self.named && self.header.is_none() => {
+                self.header = Some(fields.iter().map(|name| String::from(*name)).collect());
+                Ok(())
+            }
+            None => {
+                self.bytes += row_size(fields);
+                self.rows
+                    .push(fields.iter().map(|val| String::from(*val)).collect());
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        let (header, rows) = match self.inner {
+            Some(ref mut inner) => {
+                let headed = inner.headed();
+                let mut rows = inner.finish()?;
+                let header = (headed && !rows.is_empty()).then(|| rows.remove(0));
+                (header, rows)
+            }
+            None => (self.header.take(), std::mem::take(&mut self.rows)),
+        };
+        self.bytes = 0;
+        let (spec, locale) = (self.spec, self.locale);
+        // Each row's key, and its value as a number if that's how
+        // they're compared, is worked out just the once.
+        let mut keyed: Vec<(String, Option<f64>, Vec<String>)> = rows
+            .into_iter()
+            .map(|row| {
+                let key = resolve_index(spec.field, row.len())
+                    .map(|offset| row[offset].clone())
+                    .unwrap_or_default();
+                let number = if spec.numeric {
+                    locale.parse(&key)
+                } else {
+                    None
+                };
+                (key, number, row)
+            })
+            .collect();
+        keyed.sort_by(|(a, x, _), (b, y, _)| {
+            let order = match (spec.numeric, x, y) {
+                (false, _, _) => a.cmp(b),
+                (true, Some(x), Some(y)) => x.total_cmp(y),
+                (true, Some(_), None) => Ordering::Less,
+                (true, None, Some(_)) => Ordering::Greater,
+                (true, None, None) => a.cmp(b),
+            };
+            if spec.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+        Ok(header
+            .into_iter()
+            .chain(keyed.into_iter().map(|(_, _, row)| row))
+            .collect())
+    }
+
+    fn footprint(&self) -> usize {
+        match self.inner {
+            Some(ref inner) => inner.footprint(),
+            None => self.bytes,
+        }
+    }
+
+    fn headed(&self) -> bool {
+        match self.inner {
+            Some(ref inner) => inner.headed(),
+            None => self.header.is_some(),
+        }
+    }
+}
+
+// Hold every output row back and print them in aligned columns, as
+// `column -t` does, after whatever aggregate is in use has had its say.
+// Each field but the last in a row is padded out to the widest in its
+// column, counting characters.
+#[derive(Debug)]
+pub struct Table {
+    inner: Option<Box<dyn Aggregate>>,
+    rows: Vec<Vec<String>>,
+    bytes: usize,
+}
+
+impl Table {
+    pub fn new(inner: Option<Box<dyn Aggregate>>) -> Self {
+        Table {
+            inner,
+            rows: vec![],
+            bytes: 0,
+        }
+    }
+}
+
+impl Aggregate for Table {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        match self.inner {
+            Some(ref mut inner) => inner.add(fields),
+            None => {
+                self.bytes += row_size(fields);
+                self.rows
+                    .push(fields.iter().map(|val| String::from(*val)).collect());
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        let mut rows = match self.inner {
+            Some(ref mut inner) => inner.finish()?,
+            None => std::mem::take(&mut self.rows),
+        };
+        self.bytes = 0;
+        let mut widths: Vec<usize> = vec![];
+        for row in &rows {
+            for (col, field) in row.iter().enumerate().rev().skip(1) {
+                if col >= widths.len() {
+                    widths.resize(col + 1, 0);
+                }
+                widths[col] = widths[col].max(field.chars().count());
+            }
+        }
+        for row in &mut rows {
+            let last = row.len().saturating_sub(1);
+            for (field, width) in row[..last].iter_mut().zip(&widths) {
+                let padding = width - field.chars().count();
+                field.extend(std::iter::repeat_n(' ', padding));
+            }
+        }
+        Ok(rows)
+    }
+
+    fn footprint(&self) -> usize {
+        match self.inner {
+            Some(ref inner) => inner.footprint(),
+            None => self.bytes,
+        }
+    }
+
+    fn headed(&self) -> bool {
+        self.inner.as_ref().is_some_and(|inner| inner.headed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field_parser;
+
+    #[test]
+    fn test_parse_agg() {
+        assert_eq!(parse_agg("count").unwrap(), AggFn::Count);
+        assert_eq!(parse_agg("sum(3)").unwrap(), AggFn::Sum(3));
+        assert_eq!(parse_agg(" max(-1)").unwrap(), AggFn::Max(-1));
+        assert_eq!(parse_agg("avg( 2 )").unwrap(), AggFn::Mean(2));
+        assert!(parse_agg("sum").is_err());
+        assert!(parse_agg("median(2)").is_err());
+        assert!(parse_agg("sum(x)").is_err());
+    }
+
+    #[test]
+    fn test_numeric_locale() {
+        assert_eq!(NumericLocale::De.parse("1.234,56"), Some(1234.56));
+        assert_eq!(NumericLocale::De.parse("-2,5"), Some(-2.5));
+        assert_eq!(NumericLocale::De.parse("1234"), Some(1234.0));
+        assert_eq!(NumericLocale::De.parse("1.5"), None);
+        assert_eq!(NumericLocale::De.parse("12.34.567"), None);
+        assert_eq!(NumericLocale::En.parse("1,234,567.5"), Some(1234567.5));
+        assert_eq!(NumericLocale::Fr.parse("1\u{202f}234,5"), Some(1234.5));
+        assert_eq!(NumericLocale::C.parse("1,5"), None);
+        for bad in ["", ",", "1,", "x", "1.234,5x"] {
+            assert_eq!(NumericLocale::De.parse(bad), None, "{}", bad);
+        }
+
+        let mut group_by = GroupBy::new(field_parser("1").unwrap(), vec![AggFn::Sum(2)])
+            .with_locale(NumericLocale::De);
+        group_by.add(&["a", "1.000,5"]).unwrap();
+        group_by.add(&["a", "0,5"]).unwrap();
+        assert_eq!(group_by.finish().unwrap(), vec![vec!["a", "1001"]]);
+    }
+
+    #[test]
+    fn test_group_by() {
+        let mut group_by = GroupBy::new(
+            field_parser("1").unwrap(),
+            vec![AggFn::Count, AggFn::Sum(2), AggFn::Min(2), AggFn::Mean(2)],
+        );
+        group_by.add(&["web", "3"]).unwrap();
+        group_by.add(&["db", "10"]).unwrap();
+        group_by.add(&["web", "1.5"]).unwrap();
+        group_by.add(&["web", "n/a"]).unwrap();
+        assert_eq!(
+            group_by.finish().unwrap(),
+            vec![
+                vec!["web", "3", "4.5", "1.5", "2.25"],
+                vec!["db", "1", "10", "10", "10"],
+            ]
+        );
+        let aggs = vec![AggFn::Count, AggFn::Max(-1), AggFn::Sum(5)];
+        let mut group_by = GroupBy::new(field_parser("2,1").unwrap(), aggs).with_header(true);
+        group_by.add(&["host", "dc", "ms"]).unwrap();
+        group_by.add(&["web", "eu", "3"]).unwrap();
+        assert_eq!(
+            group_by.finish().unwrap(),
+            vec![
+                vec!["dc", "host", "count", "max(ms)", "sum(5)"],
+                vec!["eu", "web", "1", "3", "0"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count() {
+        let mut count = Count::default();
+        for record in [
+            &["b", "x"][..],
+            &["a"],
+            &["b", "x"],
+            &["a"],
+            &["b"],
+            &["b", "x"],
+        ] {
+            count.add(record).unwrap();
+        }
+        assert_eq!(
+            count.finish().unwrap(),
+            vec![vec!["3", "b", "x"], vec!["2", "a"], vec!["1", "b"]]
+        );
+        assert!(count.finish().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut stats = Stats::new(true);
+        stats.add(&["host", "ms"]).unwrap();
+        stats.add(&["web", "3"]).unwrap();
+        stats.add(&["db", "-1.5"]).unwrap();
+        stats.add(&["web", "n/a", "7"]).unwrap();
+        assert_eq!(
+            stats.finish().unwrap(),
+            vec![
+                vec!["field", "count", "min", "max", "mean", "sum"],
+                vec!["host", "0", "", "", "", "0"],
+                vec!["ms", "2", "-1.5", "3", "0.75", "1.5"],
+                vec!["3", "1", "7", "7", "7", "7"],
+            ]
+        );
+        let mut stats = Stats::new(false).with_locale(NumericLocale::De);
+        stats.add(&["1.234,5"]).unwrap();
+        assert_eq!(
+            stats.finish().unwrap()[1],
+            ["1", "1", "1234.5", "1234.5", "1234.5", "1234.5"]
+        );
+    }
+
+    #[test]
+    fn test_parse_pivot() {
+        assert_eq!(
+            parse_pivot("rows=1,cols=2").unwrap(),
+            PivotSpec {
+                rows: 1,
+                cols: 2,
+                values: AggFn::Count
+            }
+        );
+        assert_eq!(
+            parse_pivot("cols=2,rows=1,values=sum(3)").unwrap().values,
+            AggFn::Sum(3)
+        );
+        assert!(parse_pivot("rows=1").is_err());
+        assert!(parse_pivot("rows=1,cols=2,bogus=3").is_err());
+    }
+
+    #[test]
+    fn test_pivot() {
+        let mut pivot = Pivot::new(parse_pivot("rows=1,cols=2").unwrap());
+        pivot.add(&["web1", "up"]).unwrap();
+        pivot.add(&["web2", "down"]).unwrap();
+        pivot.add(&["web1", "up"]).unwrap();
+        pivot.add(&["web1", "down"]).unwrap();
+        assert_eq!(
+            pivot.finish().unwrap(),
+            vec![
+                vec!["", "up", "down"],
+                vec!["web1", "2", "1"],
+                vec!["web2", "0", "1"],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort() {
+        assert_eq!(
+            parse_sort("2:numeric:desc").unwrap(),
+            SortSpec {
+                field: 2,
+                numeric: true,
+                descending: true
+            }
+        );
+        assert!(!parse_sort("-1").unwrap().numeric);
+        assert!(parse_sort("0").is_err());
+        assert!(parse_sort("1:sideways").is_err());
+
+        let mut sort = Sort::new(parse_sort("2:n").unwrap(), None);
+        for row in [["a", "10"], ["b", "9"], ["c", "n/a"], ["d", "9"]] {
+            sort.add(&row).unwrap();
+        }
+        let firsts: Vec<String> = sort
+            .finish()
+            .unwrap()
+            .into_iter()
+            .map(|row| row[0].clone())
+            .collect();
+        assert_eq!(firsts, vec!["b", "d", "a", "c"]);
+
+        let mut sort = Sort::new(
+            parse_sort("2:desc").unwrap(),
+            Some(Box::new(GroupBy::new(
+                field_parser("1").unwrap(),
+                vec![AggFn::Count],
+            ))),
+        );
+        for row in [["x"], ["y"], ["y"]] {
+            sort.add(&row).unwrap();
+        }
+        assert_eq!(sort.finish().unwrap(), vec![vec!["y", "2"], vec!["x", "1"]]);
+
+        // The header stays on top, whether it's the first record or the
+        // aggregate's.
+        let mut sort = Sort::new(parse_sort("2:n").unwrap(), None).with_header(true);
+        for row in [["name", "n"], ["a", "10"], ["b", "9"]] {
+            sort.add(&row).unwrap();
+        }
+        assert!(sort.headed());
+        assert_eq!(
+            sort.finish().unwrap(),
+            vec![vec!["name", "n"], vec!["b", "9"], vec!["a", "10"]]
+        );
+        let group_by =
+            GroupBy::new(field_parser("1").unwrap(), vec![AggFn::Count]).with_header(true);
+        let mut sort = Sort::new(parse_sort("2:n:desc").unwrap(), Some(Box::new(group_by)));
+        for row in [["k"], ["x"], ["y"], ["y"]] {
+            sort.add(&row).unwrap();
+        }
+        assert_eq!(
+            sort.finish().unwrap(),
+            vec![vec!["k", "count"], vec!["y", "2"], vec!["x", "1"]]
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        let mut transpose = Transpose::default();
+        transpose.add(&["a", "b", "c"]).unwrap();
+        transpose.add(&["d", "e"]).unwrap();
+        assert_eq!(
+            transpose.finish().unwrap(),
+            vec![vec!["a", "d"], vec!["b", "e"], vec!["c", ""]]
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let mut table = Table::new(None);
+        table.add(&["id", "name", "x"]).unwrap();
+        table.add(&["1234", "é", "long last"]).unwrap();
+        table.add(&["5"]).unwrap();
+        table.add(&[]).unwrap();
+        assert_eq!(
+            table.finish().unwrap(),
+            vec![
+                vec!["id  ", "name", "x"],
+                vec!["1234", "é   ", "long last"],
+                vec!["5"],
+                vec![],
+            ]
+        );
+        let mut sorted = Table::new(Some(Box::new(Sort::new(parse_sort("1").unwrap(), None))));
+        sorted.add(&["bb", "1"]).unwrap();
+        sorted.add(&["a", "2"]).unwrap();
+        assert_eq!(
+            sorted.finish().unwrap(),
+            vec![vec!["a ", "2"], vec!["bb", "1"]]
+        );
+        let sort = Sort::new(parse_sort("1").unwrap(), None).with_header(true);
+        let mut sorted = Table::new(Some(Box::new(sort)));
+        for row in [["name", "n"], ["bb", "1"], ["a", "2"]] {
+            sorted.add(&row).unwrap();
+        }
+        assert_eq!(
+            sorted.finish().unwrap(),
+            vec![vec!["name", "n"], vec!["a   ", "2"], vec!["bb  ", "1"]]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+
+use crate::{diagnostics::InputFile, rng::Seed, CutJob};
+
+// The record --audit keeps of a run: what was read, what was done to
+// it and how much came out, written as JSON once the run is over,
+// whether or not it succeeded.
+#[derive(Debug)]
+pub struct Audit {
+    path: OsString,
+    seed: Seed,
+    started: SystemTime,
+    inputs: Vec<Value>,
+}
+
+fn seconds(time: SystemTime) -> f64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs_f64())
+        .unwrap_or_default()
+}
+
+impl Audit {
+    pub fn new(path: OsString, seed: Seed) -> Self {
+        Audit {
+            path,
+            seed,
+            started: SystemTime::now(),
+            inputs: vec![],
+        }
+    }
+
+    // Note an input once it has been read; inputs read in lockstep
+    // have no count of their own.
+    pub fn input(&mut self, name: &str, lines: Option<u64>) {
+        let bytes = fs::metadata(name).ok().map(|meta| meta.len());
+        self.inputs
+            .push(json!({ "name": name, "bytes": bytes, "lines": lines }));
+    }
+
+    pub fn write(&self, job: &CutJob, outcome: &Result<()>) -> Result<()> {
+        let record = json!({
+            "rcut_version": env!("CARGO_PKG_VERSION"),
+            "command": env::args_os()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect::<Vec<String>>(),
+            "started": seconds(self.started),
+            "finished": seconds(SystemTime::now()),
+            "inputs": self.inputs,
+            "seed": self.seed.0,
+            "job": describe(job)?,
+            "records": {
+                "read": job.records.get(),
+                "written": job.written(),
+            },
+            "error": outcome.as_ref().err().map(|err| format!("{:#}", err)),
+        });
+        let name = self.path.to_string_lossy().into_owned();
+        let text = serde_json::to_string_pretty(&record)? + "\n";
+        fs::write(&self.path, text).with_context(|| InputFile(name))
+    }
+}
+
+// The job's settings, one to a line as --dry-run shows them.
+fn describe(job: &CutJob) -> Result<Vec<String>> {
+    let mut text = vec![];
+    job.describe_settings(&mut text)?;
+    Ok(String::from_utf8_lossy(&text)
+        .lines()
+        .map(String::from)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::format_err;
+
+    #[test]
+    fn test_audit() {
+        let path = env::temp_dir().join(format!("rcut-audit-{}.json", std::process::id()));
+        let mut audit = Audit::new(path.clone().into_os_string(), Seed(7));
+        audit.input("<stdin>", Some(3));
+        let job = CutJob::default();
+        job.records.set(3);
+        audit.write(&job, &Err(format_err!("broken"))).unwrap();
+        let record: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(record["inputs"][0]["name"], "<stdin>");
+        assert_eq!(record["inputs"][0]["lines"], 3);
+        assert_eq!(record["records"]["read"], 3);
+        assert_eq!(record["seed"], 7);
+        assert_eq!(record["job"][1], "selector: all");
+        assert_eq!(record["error"], "broken");
+    }
+
+    #[test]
+    fn test_audit_job() {
+        let (job, args, run_options) = crate::parse_command_line(Some(vec![
+            "rcut_test",
+            "-d",
+            ",",
+            "-s",
+            "-f",
+            "2",
+            "--where",
+            "1==a",
+            "--map",
+            "3:upper",
+            "--subfield",
+            "1:@:2",
+            "--pad",
+            "NA",
+            "--seed",
+            "5",
+            "--audit",
+            "unused.json",
+            "in.csv",
+        ]))
+        .unwrap();
+        let mut dry_run = vec![];
+        job.describe(&args, &run_options, &mut dry_run).unwrap();
+        let dry_run = String::from_utf8(dry_run).unwrap();
+        let settings = describe(&job).unwrap();
+        // Everything --dry-run says about the job, short of its inputs.
+        assert_eq!(
+            dry_run.lines().collect::<Vec<_>>(),
+            settings
+                .iter()
+                .map(String::as_str)
+                .chain(["input: in.csv"])
+                .collect::<Vec<_>>()
+        );
+        for setting in ["undelimited", "where", "map", "subfield", "missing fields"] {
+            assert!(
+                settings.iter().any(|line| line.starts_with(setting)),
+                "{}",
+                setting
+            );
+        }
+        assert_eq!(run_options.audit.unwrap().borrow().seed, Seed(5));
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    ffi::OsString,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use serde_json::json;
+
+use crate::{diagnostics::InputFile, i18n::trf};
+
+// How often progress is saved, at most.
+const INTERVAL: Duration = Duration::from_secs(5);
+
+// Where a run had got to: which input, and how many of its bytes had
+// been cut and written out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Position {
+    pub index: usize,
+    pub input: String,
+    pub offset: u64,
+}
+
+// Periodically records the position in the inputs for --checkpoint,
+// so that --resume can pick up from there.  The output is flushed
+// before each save, so everything before the saved offset has been
+// written by the time it says so.
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    position: Position,
+    // Where in the current input this run started reading it.
+    start: u64,
+    saved: Instant,
+}
+
+impl Checkpoint {
+    pub fn new(path: &OsString) -> Self {
+        Checkpoint {
+            path: PathBuf::from(path),
+            position: Position {
+                index: 0,
+                input: String::new(),
+                offset: 0,
+            },
+            start: 0,
+            saved: Instant::now(),
+        }
+    }
+
+    // Read back the position saved by an earlier run, if it got as far
+    // as saving one.  A run that finished leaves nothing to resume.
+    pub fn load(&self) -> Result<Option<Position>> {
+        let name = self.path.to_string_lossy().into_owned();
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| InputFile(name)),
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&text).with_context(|| InputFile(name.clone()))?;
+        let position = (|| {
+            Some(Position {
+                index: value.get("index")?.as_u64()? as usize,
+                input: String::from(value.get("input")?.as_str()?),
+                offset: value.get("offset")?.as_u64()?,
+            })
+        })();
+        position
+            .ok_or_else(|| format_err!(trf("'{}' is not an rcut checkpoint", &[&name])))
+            .map(Some)
+    }
+
+    // Start on input number `index`, from `start` bytes in.
+    pub fn begin(&mut self, index: usize, input: &str, start: u64) {
+        self.position = Position {
+            index,
+            input: String::from(input),
+            offset: start,
+        };
+        self.start = start;
+    }
+
+    // Note that `read` bytes of the current input have been cut, saving
+    // that if it's been a while.
+    pub fn update(&mut self, read: u64, output: &mut impl Write) -> Result<()> {
+        self.position.offset = self.start + read;
+        if self.saved.elapsed() >= INTERVAL {
+            self.save(output)?;
+        }
+        Ok(())
+    }
+
+    pub fn save(&mut self, output: &mut impl Write) -> Result<()> {
+        output.flush()?;
+        let record = json!({
+            "index": self.position.index,
+            "input": self.position.input,
+            "offset": self.position.offset,
+        });
+        // Write it alongside and rename it into place, so an interrupted
+        // save can't leave a half-written checkpoint behind.
+        let partial = partial_path(&self.path);
+        let name = self.path.to_string_lossy().into_owned();
+        fs::write(&partial, format!("{}\n", record))
+            .and_then(|_| fs::rename(&partial, &self.path))
+            .with_context(|| InputFile(name))?;
+        self.saved = Instant::now();
+        Ok(())
+    }
+
+    // Everything is done, so there's nothing left to resume.
+    pub fn clear(&self) -> Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                Err(err).with_context(|| InputFile(self.path.to_string_lossy().into_owned()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn partial_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".partial");
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let path = std::env::temp_dir().join(format!("rcut-checkpoint-{}", std::process::id()));
+        let mut checkpoint = Checkpoint::new(&path.clone().into_os_string());
+        assert_eq!(checkpoint.load().unwrap(), None);
+        checkpoint.begin(1, "b.txt", 100);
+        checkpoint.update(23, &mut io::sink()).unwrap();
+        checkpoint.save(&mut io::sink()).unwrap();
+        assert_eq!(
+            checkpoint.load().unwrap(),
+            Some(Position {
+                index: 1,
+                input: String::from("b.txt"),
+                offset: 123
+            })
+        );
+        checkpoint.clear().unwrap();
+        assert_eq!(checkpoint.load().unwrap(), None);
+    }
+}
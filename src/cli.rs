@@ -0,0 +1,1283 @@
+use std::{ffi::OsString, time::Duration};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use regex::Regex;
+
+use crate::{
+    aggregate::{AggFn, NumericLocale, PivotSpec, SortSpec},
+    decompress::Codec,
+    dedup::Keep,
+    diagnostics::{ErrorFormat, InvalidSelector},
+    explode::Explode,
+    expr::Program,
+    field_parser,
+    i18n::{tr, trf},
+    metrics::Sink,
+    named_field_parser,
+    predicate::Predicate,
+    sample::SampleSpec,
+    subfield::Subfield,
+    template::Template,
+    throttle::Rate,
+    transform::FieldMap,
+    walk::Glob,
+    Delimiter, FieldSelector, OutputFormat, Passthrough,
+};
+
+pub const EXAMPLES: &str = "EXAMPLES:
+    ps aux | rcut 2 11               print the PID and command columns
+    rcut -d : -f 1,7 /etc/passwd     print each user and their shell
+    rcut -d , -o ' | ' -f 1-3 a.csv  print the first three columns, re-joined
+    rcut -f -1 log.txt               print the last field of every line
+    rcut 2 -- 1-5                    read the file named 1-5, not a selector";
+
+#[derive(Parser, Debug)]
+#[clap(
+    name = "rcut",
+    version = "1.0",
+    author = "Chip Turner <cturner@pattern.net>",
+    about = "cut-like tool with smoother aesthetics",
+    after_help = EXAMPLES,
+    allow_negative_numbers = true,
+    args_conflicts_with_subcommands = true,
+    propagate_version = true
+)]
+pub struct Cli {
+    #[clap(subcommand)]
+    pub mode: Option<Mode>,
+
+    #[clap(flatten)]
+    pub args: Args,
+}
+
+// Each mode of operation is a subcommand, so it can grow options of its
+// own without crowding the others.  The bare legacy syntax, with no
+// subcommand, means `rcut fields`.
+#[derive(Subcommand, Debug)]
+pub enum Mode {
+    /// cut delimited fields, the default when no subcommand is given
+    #[clap(allow_negative_numbers = true, after_help = EXAMPLES)]
+    Fields(Box<Args>),
+    /// cut characters at the positions in LIST, like `cut -c`
+    #[clap(allow_negative_numbers = true)]
+    Chars(Box<PositionArgs>),
+    /// cut bytes at the positions in LIST, like `cut -b`
+    #[clap(allow_negative_numbers = true)]
+    Bytes(Box<PositionArgs>),
+    /// cut fields of RFC 4180 CSV, whose first line names its columns
+    #[clap(allow_negative_numbers = true)]
+    Csv(Box<CsvArgs>),
+    /// cut the values of KEYS out of each line's JSON object
+    Json(Box<JsonArgs>),
+    /// summarize numeric fields once all the input is read: count, min, max, mean and sum
+    #[clap(allow_negative_numbers = true)]
+    Stats(Box<StatsArgs>),
+    /// choose columns interactively from the start of FILE and print the rcut command for them
+    Pick(PickArgs),
+    /// cut the body of each HTTP POST as given by --spec and send back the result
+    Serve(ServeArgs),
+    /// show a live dashboard of the most common values of some fields, and their rates
+    #[clap(allow_negative_numbers = true)]
+    Top(TopArgs),
+    /// print a random sample of the selected records
+    #[clap(allow_negative_numbers = true)]
+    Sample(Box<SampleArgs>),
+    /// join the lines of each file but the last with the lines of the last that share a key, given by --on
+    #[clap(allow_negative_numbers = true)]
+    Join(Box<Args>),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PositionArgs {
+    /// positions to select, like 1-5,8
+    #[clap(value_name = "LIST", value_parser = parse_selector)]
+    pub positions: FieldSelector,
+
+    #[clap(flatten)]
+    pub cut: Args,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CsvArgs {
+    /// fields to select, by column name or number
+    #[clap(value_name = "FIELDS", value_parser = parse_fields)]
+    pub columns: FieldSelector,
+
+    /// read the first line as a record like the rest, not as column names
+    #[clap(long, conflicts_with = "header", help_heading = "INPUT")]
+    pub no_header: bool,
+
+    #[clap(flatten)]
+    pub cut: Args,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct JsonArgs {
+    /// keys to select, like user.id,name
+    #[clap(value_name = "KEYS", value_parser = parse_fields)]
+    pub keys: FieldSelector,
+
+    #[clap(flatten)]
+    pub cut: Args,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct StatsArgs {
+    /// fields to summarize, by number or, with --header, by column name
+    #[clap(value_name = "FIELDS", value_parser = parse_fields)]
+    pub columns: FieldSelector,
+
+    #[clap(flatten)]
+    pub cut: Args,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SampleArgs {
+    /// how many records to keep, or how many of each --stratify value
+    #[clap(
+        short = 'n',
+        value_name = "COUNT",
+        conflicts_with_all = &["verify-against-cut", "checkpoint"],
+        help_heading = "SAMPLING"
+    )]
+    pub count: usize,
+
+    /// sample each value of output field N separately
+    #[clap(
+        long,
+        value_name = "N",
+        allow_hyphen_values = true,
+        help_heading = "SAMPLING"
+    )]
+    pub stratify: Option<isize>,
+
+    #[clap(flatten)]
+    pub cut: Args,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct TopArgs {
+    /// fields to count
+    #[clap(
+        short = 'f',
+        value_name = "FIELDS",
+        value_parser = parse_selector,
+        allow_hyphen_values = true,
+        default_value = "1"
+    )]
+    pub fields: FieldSelector,
+
+    /// field delimiter
+    #[clap(short = 'd', value_name = "DELIM", value_parser = parse_delimiter)]
+    pub delimiter: Option<Delimiter>,
+
+    /// keep reading as FILE grows, like `tail -f`
+    #[clap(long)]
+    pub follow: bool,
+
+    /// export metrics every 10s to a Prometheus text file (prometheus:PATH) or
+    /// to statsd (statsd:HOST:PORT)
+    #[clap(long, value_name = "SINK", value_parser = crate::metrics::parse_sink)]
+    pub metrics: Option<Sink>,
+
+    /// file to read [default: stdin]
+    #[clap(value_parser)]
+    pub file: Option<OsString>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct PickArgs {
+    /// field delimiter to start with
+    #[clap(short = 'd', value_name = "DELIM", value_parser = parse_delimiter)]
+    pub delimiter: Option<Delimiter>,
+
+    /// run the chosen command instead of printing it
+    #[clap(long)]
+    pub run: bool,
+
+    /// file to preview
+    #[clap(value_parser)]
+    pub file: OsString,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// address to listen on
+    #[clap(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    pub bind: String,
+
+    /// rcut options to cut each request with, quoted as for a shell
+    #[clap(long, value_name = "OPTIONS", allow_hyphen_values = true)]
+    pub spec: String,
+
+    /// export metrics every 10s to a Prometheus text file (prometheus:PATH) or
+    /// to statsd (statsd:HOST:PORT)
+    #[clap(long, value_name = "SINK", value_parser = crate::metrics::parse_sink)]
+    pub metrics: Option<Sink>,
+
+    /// log requests to stderr; repeat for more detail (RUST_LOG overrides)
+    #[clap(short = 'v', long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// fields to select, by number or, with --header, by column name
+    #[clap(
+        short = 'f',
+        value_name = "FIELDS",
+        value_parser = parse_fields,
+        allow_hyphen_values = true,
+        help_heading = "SELECTION"
+    )]
+    pub fields: Option<FieldSelector>,
+
+    /// bytes to select, like `cut -b`, rather than fields
+    #[clap(
+        short = 'b',
+        long,
+        value_name = "BYTES",
+        value_parser = parse_selector,
+        allow_hyphen_values = true,
+        conflicts_with_all = &[
+            "fields", "order-from", "expr", "delimiter", "explode", "join", "align-headers",
+            "infer-columns", "paste",
+        ],
+        help_heading = "SELECTION"
+    )]
+    pub bytes: Option<FieldSelector>,
+
+    /// characters to select, like `cut -c`, rather than fields
+    #[clap(
+        short = 'c',
+        long,
+        value_name = "CHARS",
+        value_parser = parse_selector,
+        allow_hyphen_values = true,
+        conflicts_with_all = &[
+            "fields", "bytes", "order-from", "expr", "delimiter", "explode", "join",
+            "align-headers", "infer-columns", "paste",
+        ],
+        help_heading = "SELECTION"
+    )]
+    pub characters: Option<FieldSelector>,
+
+    /// print the fields (or bytes or characters) not selected, in their original order
+    #[clap(long, conflicts_with = "expr", help_heading = "SELECTION")]
+    pub complement: bool,
+
+    /// drop lines without the delimiter in them, like `cut -s`
+    #[clap(
+        short = 's',
+        long,
+        conflicts_with_all = &["bytes", "characters"],
+        help_heading = "SELECTION"
+    )]
+    pub only_delimited: bool,
+
+    /// print lines without the delimiter in them whole, as cut does, instead of as a single field
+    #[clap(
+        long,
+        conflicts_with_all = &["only-delimited", "bytes", "characters"],
+        help_heading = "SELECTION"
+    )]
+    pub print_undelimited: bool,
+
+    /// fail on a line with fewer fields than the selector names, rather than skip those missing
+    #[clap(
+        long,
+        conflicts_with_all = &["bytes", "characters"],
+        help_heading = "SELECTION"
+    )]
+    pub strict: bool,
+
+    /// print STRING for each selected field a line doesn't have, rather than leaving it out
+    #[clap(
+        long,
+        value_name = "STRING",
+        conflicts_with_all = &["complement", "bytes", "characters", "strict"],
+        help_heading = "SELECTION"
+    )]
+    pub pad: Option<String>,
+
+    /// select the fields listed in FILE, one index, range or, with --header, column name per line, in that order
+    #[clap(
+        long,
+        value_name = "FILE",
+        value_parser,
+        conflicts_with_all = &["fields", "expr"],
+        help_heading = "SELECTION"
+    )]
+    pub order_from: Option<OsString>,
+
+    /// print per line instead of selecting: `[print] EXPR, ... [if COND]` over $N, NF and NR
+    #[clap(
+        long,
+        value_name = "PROGRAM",
+        value_parser = crate::expr::parse_program,
+        conflicts_with_all = &["fields", "paste"],
+        help_heading = "SELECTION"
+    )]
+    pub expr: Option<Program>,
+
+    /// print each line's fields into TEMPLATE, where {N} is field N, as -f numbers them
+    #[clap(
+        long,
+        value_name = "TEMPLATE",
+        value_parser = crate::template::parse_template,
+        conflicts_with_all = &[
+            "fields", "bytes", "characters", "order-from", "expr", "complement", "json",
+            "output-format",
+        ],
+        help_heading = "SELECTION"
+    )]
+    pub format: Option<Template>,
+
+    /// only cut lines where COND holds, as FIELD OP VALUE with ==, !=, <, <=, >, >=, ~ or !~; repeatable
+    #[clap(
+        long,
+        value_name = "COND",
+        value_parser = crate::predicate::parse_predicate,
+        allow_hyphen_values = true,
+        help_heading = "SELECTION"
+    )]
+    pub r#where: Vec<Predicate>,
+
+    /// field delimiter
+    #[clap(
+        short = 'd',
+        value_name = "DELIM",
+        value_parser = parse_delimiter,
+        help_heading = "INPUT"
+    )]
+    pub delimiter: Option<Delimiter>,
+
+    /// split fields on anything matching the regular expression PATTERN
+    #[clap(
+        short = 'E',
+        long,
+        value_name = "PATTERN",
+        value_parser = parse_regex_delimiter,
+        conflicts_with_all = &["delimiter", "bytes", "characters"],
+        help_heading = "INPUT"
+    )]
+    pub regex_delimiter: Option<Delimiter>,
+
+    /// make the capture groups of PATTERN the fields, by number or name, leaving out lines it doesn't match
+    #[clap(
+        long,
+        value_name = "PATTERN",
+        value_parser = parse_extract,
+        conflicts_with_all = &[
+            "delimiter", "regex-delimiter", "tsv", "csv", "json", "any-delim", "widths",
+            "widths-from", "infer-columns", "max-fields", "collapse-delims", "no-collapse",
+            "only-delimited", "print-undelimited", "bytes", "characters",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub extract: Option<Delimiter>,
+
+    /// split fields on any one of the characters in CHARS, joining them with the first
+    #[clap(
+        long,
+        value_name = "CHARS",
+        value_parser = parse_any_delim,
+        conflicts_with_all = &[
+            "delimiter", "regex-delimiter", "csv", "json", "tsv", "infer-columns", "widths",
+            "widths-from", "bytes", "characters",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub any_delim: Option<String>,
+
+    /// split each line into at most N fields, the last of them holding the rest of the line
+    #[clap(
+        long,
+        value_name = "N",
+        value_parser = parse_max_fields,
+        conflicts_with_all = &["csv", "json", "infer-columns", "bytes", "characters"],
+        help_heading = "INPUT"
+    )]
+    pub max_fields: Option<usize>,
+
+    /// with --max-fields, split from the end of the line, so the first field holds the rest
+    #[clap(long, requires = "max-fields", help_heading = "INPUT")]
+    pub rsplit: bool,
+
+    /// count a run of the delimiter as one, so `a,,b` has two fields, as whitespace does
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "regex-delimiter", "csv", "json", "infer-columns", "widths", "widths-from", "bytes",
+            "characters",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub collapse_delims: bool,
+
+    /// split on every whitespace character, so two spaces in a row have an empty field between them
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "collapse-delims", "regex-delimiter", "csv", "json", "infer-columns", "widths",
+            "widths-from", "bytes", "characters",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub no_collapse: bool,
+
+    /// read RFC 4180 CSV, where a quoted field can hold the delimiter, and quote output fields that need it
+    #[clap(
+        long,
+        conflicts_with_all = &["regex-delimiter", "infer-columns", "bytes", "characters"],
+        help_heading = "INPUT"
+    )]
+    pub csv: bool,
+
+    /// end records with NUL rather than a newline, on input and output, like `cut -z`
+    #[clap(short = 'z', long, help_heading = "INPUT")]
+    pub zero_terminated: bool,
+
+    /// end records with SEP rather than a newline, reading escapes in it as --escapes does
+    #[clap(
+        long,
+        value_name = "SEP",
+        value_parser = parse_record_sep,
+        conflicts_with = "zero-terminated",
+        help_heading = "INPUT"
+    )]
+    pub record_sep: Option<String>,
+
+    /// read and write tab-separated fields, like `-d '\t'` with a literal tab
+    #[clap(
+        short = 't',
+        long,
+        conflicts_with_all = &[
+            "delimiter", "regex-delimiter", "csv", "json", "infer-columns", "bytes", "characters",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub tsv: bool,
+
+    /// read each line as a JSON object, with -f naming keys like user.id
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "delimiter", "regex-delimiter", "csv", "bytes", "characters", "only-delimited",
+            "print-undelimited", "header", "align-headers",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub json: bool,
+
+    /// read each line as logfmt `key=value` pairs, with -f naming keys like level,msg
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "delimiter", "regex-delimiter", "csv", "json", "tsv", "any-delim", "widths",
+            "widths-from", "infer-columns", "max-fields", "collapse-delims", "no-collapse",
+            "extract", "format", "bytes", "characters", "only-delimited", "print-undelimited",
+            "header", "align-headers",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub logfmt: bool,
+
+    /// find fixed-width columns from how the first lines of input line up
+    #[clap(
+        long,
+        conflicts_with_all = &["delimiter", "regex-delimiter", "paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub infer_columns: bool,
+
+    /// cut each line into fixed-width columns of these widths, in characters, like 10,8,20
+    #[clap(
+        long,
+        value_name = "WIDTHS",
+        value_parser = parse_width,
+        value_delimiter = ',',
+        conflicts_with_all = &[
+            "delimiter", "regex-delimiter", "csv", "json", "tsv", "infer-columns", "bytes",
+            "characters",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub widths: Vec<usize>,
+
+    /// take the --widths from FILE, one or more to a line
+    #[clap(
+        long,
+        value_name = "FILE",
+        value_parser,
+        conflicts_with_all = &[
+            "widths", "delimiter", "regex-delimiter", "csv", "json", "tsv", "infer-columns",
+            "bytes", "characters",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub widths_from: Option<OsString>,
+
+    /// take each input's first line as column names, which -f can select by; only the first input's is printed
+    #[clap(long, conflicts_with_all = &["paste", "interleave"], help_heading = "INPUT")]
+    pub header: bool,
+
+    /// cut the first line as a header, printing it whatever --where, --expr or the like would do
+    #[clap(
+        long,
+        conflicts_with_all = &["paste", "interleave", "align-headers"],
+        help_heading = "INPUT"
+    )]
+    pub pass_header: bool,
+
+    /// ignore the first N lines of each input, as for a banner before the data
+    #[clap(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        conflicts_with_all = &["paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub skip_lines: u64,
+
+    /// drop comment lines, those starting with PREFIX, like '#'
+    #[clap(
+        long,
+        value_name = "PREFIX",
+        value_parser = parse_comment_prefix,
+        conflicts_with_all = &["paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub skip_comments: Option<String>,
+
+    /// print comment lines unchanged instead of dropping them
+    #[clap(long, requires = "skip-comments", help_heading = "INPUT")]
+    pub keep_comments: bool,
+
+    /// take each input's first line as a header and line its columns up with the first input's
+    #[clap(
+        long,
+        conflicts_with_all = &["paste", "interleave", "infer-columns"],
+        help_heading = "INPUT"
+    )]
+    pub align_headers: bool,
+
+    /// decompress any input that starts like gzip, zstd, bzip2 or xz data; .gz, .zst, .bz2 and .xz files always are
+    #[clap(long, help_heading = "INPUT")]
+    pub decompress: bool,
+
+    /// read files through a memory mapping, as files over 64 MiB are anyway
+    #[clap(long, conflicts_with = "tee", help_heading = "INPUT")]
+    pub mmap: bool,
+
+    /// read all inputs in lockstep and print their selected fields side by side, like paste; without -f, every positional is a file
+    #[clap(
+        long,
+        conflicts_with_all = &["complement", "where", "only-delimited", "print-undelimited"],
+        help_heading = "INPUT"
+    )]
+    pub paste: bool,
+
+    /// read one line from each input in turn, padding inputs that run out; without -f, every positional is a file
+    #[clap(long, conflicts_with = "paste", help_heading = "INPUT")]
+    pub interleave: bool,
+
+    /// cut every file under any directory given as an input
+    #[clap(
+        short = 'r',
+        long,
+        conflicts_with_all = &["paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub recursive: bool,
+
+    /// with -r, cut only files whose names match GLOB, such as '*.csv'; repeatable
+    #[clap(
+        long,
+        value_name = "GLOB",
+        value_parser = crate::walk::parse_glob,
+        requires = "recursive",
+        help_heading = "INPUT"
+    )]
+    pub include: Vec<Glob>,
+
+    /// with -r, skip files whose names match GLOB; repeatable
+    #[clap(
+        long,
+        value_name = "GLOB",
+        value_parser = crate::walk::parse_glob,
+        requires = "recursive",
+        help_heading = "INPUT"
+    )]
+    pub exclude: Vec<Glob>,
+
+    /// read the names of more input files from LIST, one per line, or from stdin if LIST is -
+    #[clap(
+        long,
+        value_name = "LIST",
+        value_parser,
+        conflicts_with_all = &["files0-from", "paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub files_from: Option<OsString>,
+
+    /// read the names of more input files from LIST, each ended by a NUL, as find -print0 writes them
+    #[clap(
+        long,
+        value_name = "LIST",
+        value_parser,
+        conflicts_with_all = &["paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub files0_from: Option<OsString>,
+
+    /// also copy the input, unchanged, to FILE
+    #[clap(
+        long,
+        value_name = "FILE",
+        value_parser,
+        conflicts_with_all = &["paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub tee: Option<OsString>,
+
+    /// write lines that can't be cut, as they were, to FILE instead of failing
+    #[clap(
+        long,
+        value_name = "FILE",
+        value_parser,
+        conflicts_with_all = &["paste", "interleave"],
+        help_heading = "INPUT"
+    )]
+    pub rejects: Option<OsString>,
+
+    /// every few seconds, save how far through the input files the job has got to FILE
+    #[clap(
+        long,
+        value_name = "FILE",
+        value_parser,
+        conflicts_with_all = &[
+            "paste", "interleave", "infer-columns", "align-headers", "group-by", "pivot",
+            "transpose", "sort-by", "dedup-key", "skip-lines", "header", "pass-header",
+        ],
+        help_heading = "INPUT"
+    )]
+    pub checkpoint: Option<OsString>,
+
+    /// fail rather than let buffered records (for sorting, grouping, dedup and
+    /// the like) or a single line take more than SIZE bytes, like 512M
+    #[clap(
+        long,
+        value_name = "SIZE",
+        value_parser = crate::memory::parse_size,
+        help_heading = "INPUT"
+    )]
+    pub max_memory: Option<u64>,
+
+    /// stop after DURATION (60s, 5m), flushing what has been cut so far and
+    /// exiting with status 124
+    #[clap(
+        long,
+        value_name = "DURATION",
+        value_parser = crate::signals::parse_duration,
+        help_heading = "INPUT"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// carry on from where the --checkpoint file says an earlier run got to
+    #[clap(long, requires = "checkpoint", help_heading = "INPUT")]
+    pub resume: bool,
+
+    /// append the fields of matching lines in FILE, keyed as given by --on
+    #[clap(long, value_name = "FILE", value_parser, help_heading = "INPUT")]
+    pub join: Option<OsString>,
+
+    /// key fields for --join, as INPUT=FILE or a single field for both [default: 1]
+    #[clap(
+        long,
+        value_name = "KEYS",
+        value_parser = crate::join::parse_on,
+        help_heading = "INPUT"
+    )]
+    pub on: Option<(isize, isize)>,
+
+    /// fields of FILE to append for --join [default: all but the key]
+    #[clap(
+        long,
+        value_name = "FIELDS",
+        value_parser = parse_selector,
+        allow_hyphen_values = true,
+        help_heading = "INPUT"
+    )]
+    pub join_fields: Option<FieldSelector>,
+
+    /// split field N of the selection on DELIM into named columns, as N:DELIM:NAME,...
+    #[clap(
+        long,
+        value_name = "SPEC",
+        value_parser = crate::explode::parse_explode,
+        allow_hyphen_values = true,
+        conflicts_with = "paste",
+        help_heading = "OUTPUT"
+    )]
+    pub explode: Option<Explode>,
+
+    /// replace output field N with the PIECES of it between each DELIM, as N:DELIM:PIECES, like 3:@:2; repeatable
+    #[clap(
+        long,
+        value_name = "SPEC",
+        value_parser = crate::subfield::parse_subfield,
+        allow_hyphen_values = true,
+        help_heading = "OUTPUT"
+    )]
+    pub subfield: Vec<Subfield>,
+
+    /// change output field N with TRANSFORM, one of upper, lower, trim or squeeze-space; repeatable
+    #[clap(
+        long,
+        value_name = "N:TRANSFORM",
+        value_parser = crate::transform::parse_map,
+        allow_hyphen_values = true,
+        help_heading = "OUTPUT"
+    )]
+    pub map: Vec<FieldMap>,
+
+    /// cut the output again, as given by STAGE: `[-d DELIM] [-o SEP] [-f] FIELDS`; repeatable
+    #[clap(
+        long,
+        value_name = "STAGE",
+        value_parser = parse_stage,
+        allow_hyphen_values = true,
+        conflicts_with_all = &["group-by", "pivot", "transpose"],
+        help_heading = "OUTPUT"
+    )]
+    pub then: Vec<Stage>,
+
+    /// start each output line with the name of the file it came from, as grep -H does
+    #[clap(
+        short = 'H',
+        long,
+        overrides_with = "no-filename",
+        conflicts_with_all = &[
+            "output-format", "group-by", "pivot", "transpose", "sort-by", "paste", "interleave",
+        ],
+        help_heading = "OUTPUT"
+    )]
+    pub with_filename: bool,
+
+    /// don't start output lines with the name of their file, which is the default
+    #[clap(long, overrides_with = "with-filename", help_heading = "OUTPUT")]
+    pub no_filename: bool,
+
+    /// start each output line with the number of the line it was cut from, as a field of its own
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "output-format", "group-by", "pivot", "transpose", "sort-by", "paste", "interleave",
+        ],
+        help_heading = "OUTPUT"
+    )]
+    pub line_numbers: bool,
+
+    /// compress the output with CODEC, one of gz, zst, bz2 or xz, as -O FILE's extension would
+    #[clap(
+        long,
+        value_name = "CODEC",
+        value_parser = Codec::parse,
+        conflicts_with_all = &["in-place", "verify-against-cut"],
+        help_heading = "OUTPUT"
+    )]
+    pub compress: Option<Codec>,
+
+    /// rewrite each input file with just what is cut from it, keeping the old one with SUFFIX added if given
+    #[clap(
+        short = 'i',
+        long,
+        value_name = "SUFFIX",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "",
+        conflicts_with_all = &[
+            "output", "paste", "interleave", "checkpoint", "align-headers", "verify-against-cut",
+        ],
+        help_heading = "OUTPUT"
+    )]
+    pub in_place: Option<String>,
+
+    /// write the output to FILE, which is only replaced once the whole run has succeeded
+    #[clap(
+        short = 'O',
+        long,
+        value_name = "FILE",
+        value_parser,
+        conflicts_with = "verify-against-cut",
+        help_heading = "OUTPUT"
+    )]
+    pub output: Option<OsString>,
+
+    /// separator used when printing fields
+    #[clap(short = 'o', value_name = "SEP", help_heading = "OUTPUT")]
+    pub output_separator: Option<String>,
+
+    /// read \t, \n, \0, \\ and \xNN in -d and -o as the characters they stand for
+    #[clap(long, help_heading = "OUTPUT")]
+    pub escapes: bool,
+
+    /// print every line unchanged, with the selected fields appended or prepended
+    #[clap(
+        long,
+        value_enum,
+        value_name = "WHERE",
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "append",
+        conflicts_with_all = &["group-by", "pivot", "transpose", "paste"],
+        help_heading = "OUTPUT"
+    )]
+    pub passthrough: Option<Passthrough>,
+
+    /// print one row per distinct value of these fields, summarized by --agg
+    #[clap(
+        long,
+        value_name = "FIELDS",
+        value_parser = parse_selector,
+        allow_hyphen_values = true,
+        help_heading = "OUTPUT"
+    )]
+    pub group_by: Option<FieldSelector>,
+
+    /// print how often each distinct record of the selected fields occurs, as `count fields`, most frequent first
+    #[clap(
+        long = "count",
+        conflicts_with_all = &["group-by", "pivot", "transpose", "dedup-key", "paste", "interleave"],
+        help_heading = "OUTPUT"
+    )]
+    pub tally: bool,
+
+    /// summarize each selected field as numbers once all the input is read: count, min, max, mean and sum
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "group-by", "pivot", "transpose", "tally", "dedup-key", "paste", "interleave",
+        ],
+        help_heading = "OUTPUT"
+    )]
+    pub stats: bool,
+
+    /// summaries for --group-by: count, sum(N), min(N), max(N), mean(N) [default: count]
+    #[clap(
+        long,
+        value_name = "AGGS",
+        value_parser = crate::aggregate::parse_agg,
+        value_delimiter = ',',
+        requires = "group-by",
+        help_heading = "OUTPUT"
+    )]
+    pub agg: Vec<AggFn>,
+
+    /// cross-tabulate two fields, as rows=N,cols=M[,values=AGG] [default values: count]
+    #[clap(
+        long,
+        value_name = "SPEC",
+        value_parser = crate::aggregate::parse_pivot,
+        conflicts_with = "group-by",
+        help_heading = "OUTPUT"
+    )]
+    pub pivot: Option<PivotSpec>,
+
+    /// print only one record for each distinct value of these output fields
+    #[clap(
+        long,
+        value_name = "FIELDS",
+        value_parser = parse_selector,
+        allow_hyphen_values = true,
+        conflicts_with_all = &["group-by", "pivot", "transpose"],
+        help_heading = "OUTPUT"
+    )]
+    pub dedup_key: Option<FieldSelector>,
+
+    /// which record --dedup-key keeps
+    #[clap(
+        long,
+        value_enum,
+        value_name = "WHICH",
+        default_value_t = Keep::First,
+        requires = "dedup-key",
+        help_heading = "OUTPUT"
+    )]
+    pub keep: Keep,
+
+    /// print only the first record for each distinct combination of the selected fields
+    #[clap(long, conflicts_with = "dedup-key", help_heading = "OUTPUT")]
+    pub unique: bool,
+
+    /// leave out a record with the same selected fields as the one before it, as uniq does
+    #[clap(
+        long,
+        conflicts_with_all = &["dedup-key", "unique"],
+        help_heading = "OUTPUT"
+    )]
+    pub unique_consecutive: bool,
+
+    /// print the output ordered by column N, as N[:numeric][:desc]
+    #[clap(
+        long,
+        value_name = "KEY",
+        value_parser = crate::aggregate::parse_sort,
+        allow_hyphen_values = true,
+        help_heading = "OUTPUT"
+    )]
+    pub sort_by: Option<SortSpec>,
+
+    /// read numbers for --expr, --where, --agg, --pivot and --sort-by as LOCALE
+    /// writes them, like 1.234,56 for de
+    #[clap(
+        long,
+        value_enum,
+        value_name = "LOCALE",
+        default_value_t = NumericLocale::C,
+        help_heading = "INPUT"
+    )]
+    pub numeric_locale: NumericLocale,
+
+    /// print the selected fields in aligned columns, as column -t does, once all the input is read
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "then", "output-format", "bytes", "characters", "with-filename", "line-numbers",
+        ],
+        help_heading = "OUTPUT"
+    )]
+    pub table: bool,
+
+    /// swap rows and columns of the selected fields
+    #[clap(long, conflicts_with_all = &["group-by", "pivot"], help_heading = "OUTPUT")]
+    pub transpose: bool,
+
+    /// limit output to RATE per second, in bytes (10MB/s) or records (1000/s)
+    #[clap(
+        long,
+        value_name = "RATE",
+        value_parser = crate::throttle::parse_rate,
+        help_heading = "OUTPUT"
+    )]
+    pub throttle: Option<Rate>,
+
+    /// seed every random choice, as made by `rcut sample`, for a reproducible run
+    #[clap(long, value_name = "SEED", help_heading = "OUTPUT")]
+    pub seed: Option<u64>,
+
+    /// print the resolved job and its inputs without processing data
+    #[clap(long, help_heading = "DIAGNOSTICS")]
+    pub dry_run: bool,
+
+    /// also run the system cut on the same input and fail if its output differs
+    // It conflicts with everything but cut's own options, as set up by
+    // verify::cut_options_only().
+    #[clap(long, help_heading = "DIAGNOSTICS")]
+    pub verify_against_cut: bool,
+
+    /// write a JSON record of the run (inputs, job, record counts) to FILE
+    #[clap(
+        long,
+        value_name = "FILE",
+        value_parser,
+        conflicts_with_all = &["dry-run", "verify-against-cut"],
+        help_heading = "DIAGNOSTICS"
+    )]
+    pub audit: Option<OsString>,
+
+    /// log progress to stderr; repeat for more detail (RUST_LOG overrides)
+    #[clap(short = 'v', long, action = clap::ArgAction::Count, help_heading = "DIAGNOSTICS")]
+    pub verbose: u8,
+
+    /// log debugging detail to stderr, same as -vv
+    #[clap(long, help_heading = "DIAGNOSTICS")]
+    pub debug: bool,
+
+    /// write each record as text, as CSV quoted where a field needs it, as JSON: an object keyed by the header or --json keys, or else an array, or as kv: name=value pairs, named f1, f2... without a header
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = OutputFormat::Text,
+        help_heading = "OUTPUT"
+    )]
+    pub output_format: OutputFormat,
+
+    /// how to report warnings and errors on stderr
+    #[clap(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = ErrorFormat::Text,
+        help_heading = "DIAGNOSTICS"
+    )]
+    pub error_format: ErrorFormat,
+
+    /// file(s) to process, - meaning stdin, or field selectors
+    #[clap(value_parser)]
+    pub args: Vec<OsString>,
+
+    /// file(s) to process, even if they look like field selectors
+    #[clap(last = true, value_parser)]
+    pub files: Vec<OsString>,
+}
+
+impl Cli {
+    // The localized equivalent of Cli::command().
+    pub fn localized_command() -> clap::Command<'static> {
+        use clap::CommandFactory;
+        crate::verify::cut_options_only(crate::i18n::localize(Cli::command()))
+    }
+
+    // The options for cutting fields.  The other modes have to be
+    // handled before getting this far.
+    pub fn into_args(self) -> Result<Args> {
+        let args = match self.mode {
+            Some(Mode::Fields(args)) => *args,
+            Some(Mode::Chars(chars)) => {
+                let chars = *chars;
+                selecting(chars.cut, "chars", |cut| {
+                    cut.characters = Some(chars.positions);
+                })?
+            }
+            Some(Mode::Bytes(bytes)) => {
+                let bytes = *bytes;
+                selecting(bytes.cut, "bytes", |cut| {
+                    cut.bytes = Some(bytes.positions);
+                })?
+            }
+            Some(Mode::Csv(csv)) => {
+                let csv = *csv;
+                selecting(csv.cut, "csv", |cut| {
+                    cut.csv = true;
+                    cut.header = !csv.no_header;
+                    cut.fields = Some(csv.columns);
+                })?
+            }
+            Some(Mode::Json(json)) => {
+                let json = *json;
+                selecting(json.cut, "json", |cut| {
+                    cut.json = true;
+                    cut.fields = Some(json.keys);
+                })?
+            }
+            Some(Mode::Stats(stats)) => {
+                let stats = *stats;
+                selecting(stats.cut, "stats", |cut| {
+                    cut.stats = true;
+                    cut.fields = Some(stats.columns);
+                })?
+            }
+            // With no -f, the positionals are what to sample from.
+            Some(Mode::Sample(sample)) => {
+                let mut cut = sample.cut;
+                if cut.fields.is_none() {
+                    let files = std::mem::take(&mut cut.args);
+                    cut.files.splice(0..0, files);
+                }
+                cut
+            }
+            // The positionals are all files, since with no -f every field
+            // is kept, and the last of them is the one joined.
+            Some(Mode::Join(mut cut)) => {
+                let files = std::mem::take(&mut cut.args);
+                cut.files.splice(0..0, files);
+                if cut.join.is_some() {
+                    bail!(tr(
+                        "rcut join joins the last file it's given, so it doesn't take --join"
+                    ));
+                }
+                if cut.files.len() < 2 {
+                    bail!(tr("rcut join needs a file to read and one to join with it"));
+                }
+                cut.join = cut.files.pop();
+                *cut
+            }
+            Some(Mode::Pick(_)) | Some(Mode::Serve(_)) | Some(Mode::Top(_)) | None => self.args,
+        };
+        // --join's options are checked here, for rcut join to set --join.
+        if args.join.is_none() && (args.on.is_some() || args.join_fields.is_some()) {
+            let message = format!("{}\n", tr("--on and --join-fields need --join"));
+            return Err(clap::Error::raw(clap::ErrorKind::MissingRequiredArgument, message).into());
+        }
+        Ok(args)
+    }
+
+    // What `rcut sample` should keep, if that's the mode.
+    pub fn sample_spec(&self) -> Option<SampleSpec> {
+        match self.mode {
+            Some(Mode::Sample(ref sample)) => Some(SampleSpec {
+                count: sample.count,
+                stratify: sample.stratify,
+            }),
+            _ => None,
+        }
+    }
+}
+
+// The options of a subcommand that takes what it selects as its first
+// positional, which has to be the only selection, so the rest of its
+// positionals are all files.
+fn selecting(mut cut: Args, mode: &str, select: impl FnOnce(&mut Args)) -> Result<Args> {
+    if cut.fields.is_some()
+        || cut.bytes.is_some()
+        || cut.characters.is_some()
+        || cut.order_from.is_some()
+    {
+        bail!(trf(
+            "rcut {} takes what to select as its first argument, not -f, -b, -c or --order-from",
+            &[mode]
+        ));
+    }
+    select(&mut cut);
+    Ok(cut)
+}
+
+// A later cut stage from --then, which re-splits each output line of
+// the one before it.
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub delimiter: Option<Delimiter>,
+    pub selector: FieldSelector,
+    pub output_separator: Option<String>,
+}
+
+// Parse a stage like `-d = -f 2`, or just `2` to re-split on
+// whitespace.  Words are separated by whitespace, so a whitespace
+// delimiter is spelled by leaving -d out.
+pub fn parse_stage(s: &str) -> Result<Stage> {
+    let invalid = || format_err!(trf("invalid stage '{}'", &[s]));
+    let mut stage = Stage {
+        delimiter: None,
+        selector: FieldSelector::all(),
+        output_separator: None,
+    };
+    let mut selected = false;
+    let mut words = s.split_whitespace();
+    while let Some(word) = words.next() {
+        match word {
+            "-d" => stage.delimiter = Some(parse_delimiter(words.next().ok_or_else(invalid)?)?),
+            "-E" => {
+                stage.delimiter = Some(parse_regex_delimiter(words.next().ok_or_else(invalid)?)?)
+            }
+            "-o" => stage.output_separator = Some(String::from(words.next().ok_or_else(invalid)?)),
+            "-f" if !selected => {
+                stage.selector = parse_selector(words.next().ok_or_else(invalid)?)?;
+                selected = true;
+            }
+            _ if !selected => {
+                stage.selector = parse_selector(word)?;
+                selected = true;
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    if !selected {
+        return Err(invalid());
+    }
+    Ok(stage)
+}
+
+pub fn parse_selector(s: &str) -> Result<FieldSelector> {
+    field_parser(s).with_context(|| InvalidSelector(String::from(s)))
+}
+
+pub fn parse_fields(s: &str) -> Result<FieldSelector> {
+    named_field_parser(s).with_context(|| InvalidSelector(String::from(s)))
+}
+
+// Interpret the backslash escapes --escapes allows in -d and -o: \t,
+// \n, \0, \\ and \xNN for an ASCII character by its hex code.
+pub fn unescape(s: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('0') => unescaped.push('\0'),
+            Some('\\') => unescaped.push('\\'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(code) if hex.len() == 2 && code.is_ascii() => unescaped.push(code as char),
+                    _ => bail!(trf(
+                        "\\x{} isn't the hex code of an ASCII character",
+                        &[&hex]
+                    )),
+                }
+            }
+            Some(c) => bail!(trf("unknown escape \\{}", &[&c.to_string()])),
+            None => bail!(tr("a trailing \\ escapes nothing")),
+        }
+    }
+    Ok(unescaped)
+}
+
+pub fn parse_record_sep(s: &str) -> Result<String> {
+    let sep = unescape(s)?;
+    if sep.is_empty() {
+        bail!(tr("the record separator cannot be empty"));
+    }
+    Ok(sep)
+}
+
+pub fn parse_width(s: &str) -> Result<usize> {
+    match s.trim().parse()? {
+        0 => bail!(tr("a column can't be 0 characters wide")),
+        width => Ok(width),
+    }
+}
+
+pub fn parse_any_delim(s: &str) -> Result<String> {
+    if s.is_empty() {
+        bail!(tr("--any-delim needs at least one character"));
+    }
+    Ok(String::from(s))
+}
+
+pub fn parse_max_fields(s: &str) -> Result<usize> {
+    match s.parse()? {
+        0 => bail!(tr("a line can't be split into 0 fields")),
+        most => Ok(most),
+    }
+}
+
+pub fn parse_delimiter(s: &str) -> Result<Delimiter> {
+    if s.is_empty() {
+        bail!(tr("the delimiter cannot be empty"));
+    }
+    Ok(Delimiter::String(String::from(s)))
+}
+
+// A pattern that can match an empty string would split between every
+// character, which is -c's job, so it is refused.
+pub fn parse_regex_delimiter(s: &str) -> Result<Delimiter> {
+    let re = Regex::new(s)?;
+    if re.is_match("") {
+        bail!(tr("the delimiter pattern cannot match an empty string"));
+    }
+    Ok(Delimiter::Regex(re))
+}
+
+pub fn parse_comment_prefix(s: &str) -> Result<String> {
+    if s.is_empty() {
+        bail!(tr("an empty prefix would make every line a comment"));
+    }
+    Ok(String::from(s))
+}
+
+pub fn parse_extract(s: &str) -> Result<Delimiter> {
+    let re = Regex::new(s)?;
+    if re.captures_len() < 2 {
+        bail!(tr("the pattern has no capture groups to make fields of"));
+    }
+    Ok(Delimiter::Extract(re))
+}
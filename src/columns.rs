@@ -0,0 +1,108 @@
+// Fixed-width columns, found by lining up a sample of text the way a
+// reader would: the gutters between columns are character positions
+// with a space in every sampled line.  This copes with `ps aux` or
+// `docker ps` output, where values themselves can contain spaces but
+// the gutters line up.
+
+// How many lines of input are looked at to find the columns.
+pub const SAMPLE_LINES: usize = 100;
+
+// The character offset at which each column starts; the first is
+// always 0.  Past its end, a line counts as spaces.  A stretch between
+// gutters with text from only one line is a word of that line, like
+// the ID of a `CONTAINER ID` header, so it stays with the column before.
+pub fn infer_columns(sample: &[String]) -> Vec<usize> {
+    let width = sample
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut gap = vec![true; width];
+    for line in sample {
+        for (pos, c) in line.chars().enumerate() {
+            if !c.is_whitespace() {
+                gap[pos] = false;
+            }
+        }
+    }
+    // The stretches between gutters, as [start, end) positions.
+    let mut stretches = vec![];
+    let mut pos = 0;
+    while pos < width {
+        if gap[pos] {
+            pos += 1;
+            continue;
+        }
+        let start = pos;
+        while pos < width && !gap[pos] {
+            pos += 1;
+        }
+        stretches.push((start, pos));
+    }
+    let needed = sample.len().min(2);
+    let mut starts = vec![0];
+    for (start, end) in stretches.into_iter().filter(|(start, _)| *start > 0) {
+        let lines = sample
+            .iter()
+            .filter(|line| {
+                line.chars()
+                    .skip(start)
+                    .take(end - start)
+                    .any(|c| !c.is_whitespace())
+            })
+            .count();
+        if lines >= needed {
+            starts.push(start);
+        }
+    }
+    starts
+}
+
+// Cut a line at the given character offsets, trimming the padding from
+// each piece.  The last column runs to the end of the line, and columns
+// the line is too short for come out empty.
+pub fn split_columns<'a>(line: &'a str, starts: &[usize]) -> Vec<&'a str> {
+    let mut offsets = line.char_indices().map(|(offset, _)| offset);
+    let mut bytes = vec![];
+    let mut pos = 0;
+    for start in starts {
+        let offset = offsets.nth(start - pos).unwrap_or(line.len());
+        bytes.push(offset);
+        pos = start + 1;
+    }
+    bytes.push(line.len());
+    bytes
+        .windows(2)
+        .map(|span| line[span[0]..span[1]].trim())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_columns() {
+        let sample: Vec<String> = [
+            "CONTAINER ID   IMAGE     STATUS",
+            "4f2a9c1b       nginx     Up 2 hours",
+            "9e8d7c6b       redis     Exited (0)",
+        ]
+        .iter()
+        .map(|line| String::from(*line))
+        .collect();
+        let starts = infer_columns(&sample);
+        assert_eq!(starts, vec![0, 15, 25]);
+        assert_eq!(
+            split_columns(&sample[1], &starts),
+            vec!["4f2a9c1b", "nginx", "Up 2 hours"]
+        );
+        assert_eq!(split_columns("short", &starts), vec!["short", "", ""]);
+        assert!(infer_columns(&[]) == vec![0]);
+    }
+
+    #[test]
+    fn test_split_columns_unicode() {
+        assert_eq!(split_columns("née  ok", &[0, 5]), vec!["née", "ok"]);
+    }
+}
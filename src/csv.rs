@@ -0,0 +1,139 @@
+use std::borrow::Cow;
+
+// RFC 4180 fields, for --csv: a field may be wrapped in double quotes,
+// inside which the separator is just text and "" stands for one quote.
+
+// Split a record into its unquoted fields.  Only a field with an
+// escaped quote in it needs copying.  A quote that is never closed
+// runs to the end of the record, and anything between a closing quote
+// and the next separator is kept, as most readers do.
+pub fn split(record: &str, sep: char) -> Vec<Cow<'_, str>> {
+    let mut fields = vec![];
+    let mut start = 0;
+    loop {
+        let (field, end) = field(record, start, sep);
+        fields.push(field);
+        if end >= record.len() {
+            return fields;
+        }
+        start = end + sep.len_utf8();
+    }
+}
+
+// The field that starts at `start`, and where it ends.
+fn field(record: &str, start: usize, sep: char) -> (Cow<'_, str>, usize) {
+    let rest = &record[start..];
+    if !rest.starts_with('"') {
+        let end = rest.find(sep).map_or(record.len(), |i| start + i);
+        return (Cow::Borrowed(&record[start..end]), end);
+    }
+    let mut text = Cow::Borrowed("");
+    let mut pos = start + 1;
+    loop {
+        let Some(quote) = record[pos..].find('"').map(|i| pos + i) else {
+            append(&mut text, &record[pos..]);
+            return (text, record.len());
+        };
+        if record[quote + 1..].starts_with('"') {
+            append(&mut text, &record[pos..=quote]);
+            pos = quote + 2;
+            continue;
+        }
+        append(&mut text, &record[pos..quote]);
+        let end = record[quote + 1..]
+            .find(sep)
+            .map_or(record.len(), |i| quote + 1 + i);
+        append(&mut text, &record[quote + 1..end]);
+        return (text, end);
+    }
+}
+
+// Whether a record stops partway through a quoted field, so that the
+// line break after it belongs to the field and the record goes on.
+pub fn unterminated(record: &str, sep: char) -> bool {
+    let mut start = 0;
+    loop {
+        let mut end = start;
+        if record[start..].starts_with('"') {
+            end += 1;
+            loop {
+                match record[end..].find('"') {
+                    None => return true,
+                    Some(i) if record[end + i + 1..].starts_with('"') => end += i + 2,
+                    Some(i) => {
+                        end += i + 1;
+                        break;
+                    }
+                }
+            }
+        }
+        match record[end..].find(sep) {
+            Some(i) => start = end + i + sep.len_utf8(),
+            None => return false,
+        }
+    }
+}
+
+// Add to a field's text, borrowing it for as long as it is one piece.
+fn append<'a>(text: &mut Cow<'a, str>, piece: &'a str) {
+    if piece.is_empty() {
+        return;
+    }
+    if text.is_empty() {
+        *text = Cow::Borrowed(piece);
+    } else {
+        text.to_mut().push_str(piece);
+    }
+}
+
+// A field as it should be written, quoted if it holds the separator,
+// a quote or a line break, so that it reads back as itself.
+pub fn quote<'a>(field: &'a str, sep: &str) -> Cow<'a, str> {
+    let needs_quotes =
+        (!sep.is_empty() && field.contains(sep)) || field.contains(['"', '\n', '\r']);
+    if needs_quotes {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        assert_eq!(split(r#"a,"b,c",d"#, ','), vec!["a", "b,c", "d"]);
+        assert_eq!(split(r#""say ""hi""",x"#, ','), vec![r#"say "hi""#, "x"]);
+        assert_eq!(split(r#""",,"#, ','), vec!["", "", ""]);
+        assert_eq!(split("", ','), vec![""]);
+        assert_eq!(split(r#"a"b,c"#, ','), vec![r#"a"b"#, "c"]);
+        assert_eq!(split(r#""open,x"#, ','), vec!["open,x"]);
+        assert_eq!(split(r#""a"b;c"#, ';'), vec!["ab", "c"]);
+        assert!(matches!(split(r#""b,c""#, ',')[0], Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_unterminated() {
+        assert!(!unterminated("a,b\n", ','));
+        assert!(!unterminated("a,\"b,c\"\n", ','));
+        assert!(unterminated("a,\"b\n", ','));
+        assert!(unterminated("a,\"b\"\"\n", ','));
+        assert!(!unterminated("a,\"b\"\"\nc\"\n", ','));
+        assert!(!unterminated("a\"b,c\n", ','));
+        assert!(unterminated("\"", ','));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain", ","), "plain");
+        assert_eq!(quote("b,c", ","), r#""b,c""#);
+        assert_eq!(quote(r#"say "hi""#, ","), r#""say ""hi""""#);
+        assert_eq!(quote("two\nlines", ","), "\"two\nlines\"");
+        for field in ["a,b", r#""x""#, "", "é,\""] {
+            let quoted = quote(field, ",");
+            assert_eq!(split(&quoted, ','), vec![field]);
+        }
+    }
+}
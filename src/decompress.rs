@@ -0,0 +1,376 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Stdin, Write},
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    thread::{self, JoinHandle},
+};
+
+use anyhow::{Context, Result};
+
+use crate::i18n::trf;
+
+// The compressed formats rcut reads and writes, each through the
+// system's own compressor so that no compression library has to be
+// built in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Gzip,
+    Zstd,
+    Bzip2,
+    Xz,
+}
+
+impl Codec {
+    // Going by the file's extension.
+    pub fn from_name(path: &Path) -> Option<Codec> {
+        match path.extension()?.to_str()? {
+            "gz" => Some(Codec::Gzip),
+            "zst" => Some(Codec::Zstd),
+            "bz2" => Some(Codec::Bzip2),
+            "xz" => Some(Codec::Xz),
+            _ => None,
+        }
+    }
+
+    // Going by the magic number the data starts with.
+    pub fn sniff(start: &[u8]) -> Option<Codec> {
+        let magic: [(Codec, &[u8]); 4] = [
+            (Codec::Gzip, &[0x1f, 0x8b]),
+            (Codec::Zstd, &[0x28, 0xb5, 0x2f, 0xfd]),
+            (Codec::Bzip2, b"BZh"),
+            (Codec::Xz, b"\xfd7zXZ\0"),
+        ];
+        magic
+            .into_iter()
+            .find(|(_, magic)| start.starts_with(magic))
+            .map(|(codec, _)| codec)
+    }
+
+    // As --compress names it, by its extension or its program.
+    pub fn parse(s: &str) -> Result<Codec> {
+        match s {
+            "gz" | "gzip" => Ok(Codec::Gzip),
+            "zst" | "zstd" => Ok(Codec::Zstd),
+            "bz2" | "bzip2" => Ok(Codec::Bzip2),
+            "xz" => Ok(Codec::Xz),
+            _ => bail!(trf(
+                "unknown compression '{}': expected gz, zst, bz2 or xz",
+                &[s]
+            )),
+        }
+    }
+
+    pub fn program(&self) -> &'static str {
+        match self {
+            Codec::Gzip => "gzip",
+            Codec::Zstd => "zstd",
+            Codec::Bzip2 => "bzip2",
+            Codec::Xz => "xz",
+        }
+    }
+}
+
+// The output of a decompressor, which a thread feeds the compressed
+// data, so that it can come from anywhere, stdin included.  Once the
+// output runs out the decompressor has to have succeeded, or reading
+// fails.
+pub struct Decoder {
+    program: &'static str,
+    child: Child,
+    stdout: ChildStdout,
+    feeder: Option<JoinHandle<io::Result<u64>>>,
+}
+
+impl Decoder {
+    pub fn spawn(codec: Codec, mut compressed: impl Read + Send + 'static) -> Result<Decoder> {
+        let program = codec.program();
+        let mut child = Command::new(program)
+            .arg("-dc")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| trf("decompressing needs {}, which could not be run", &[program]))?;
+        let mut stdin: ChildStdin = child.stdin.take().expect("piped stdin");
+        let stdout = child.stdout.take().expect("piped stdout");
+        let feeder = thread::spawn(move || io::copy(&mut compressed, &mut stdin));
+        Ok(Decoder {
+            program,
+            child,
+            stdout,
+            feeder: Some(feeder),
+        })
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let fed = match self.feeder.take() {
+            Some(feeder) => feeder.join().unwrap_or(Ok(0)),
+            None => return Ok(()),
+        };
+        let status = self.child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(trf(
+                "{} could not decompress the input ({})",
+                &[self.program, &status.to_string()],
+            )));
+        }
+        // A decompressor that stops reading early has said why already.
+        match fed {
+            Err(err) if err.kind() != io::ErrorKind::BrokenPipe => Err(err),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Read for Decoder {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.stdout.read(buf)?;
+        if len == 0 && !buf.is_empty() {
+            self.finish()?;
+        }
+        Ok(len)
+    }
+}
+
+impl Drop for Decoder {
+    fn drop(&mut self) {
+        if self.feeder.is_some() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+// A compressor that what's written to it is piped through, on its way
+// to wherever the compressor's output was sent.  Once everything has
+// been written, finishing waits for the compressor, which has to have
+// succeeded; dropped without that, it's still left to finish what it
+// was given.
+pub struct Encoder {
+    program: &'static str,
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl Encoder {
+    pub fn spawn(codec: Codec, output: Stdio) -> Result<Encoder> {
+        let program = codec.program();
+        let mut child = Command::new(program)
+            .arg("-c")
+            .stdin(Stdio::piped())
+            .stdout(output)
+            .spawn()
+            .with_context(|| trf("compressing needs {}, which could not be run", &[program]))?;
+        let stdin = child.stdin.take();
+        Ok(Encoder {
+            program,
+            child,
+            stdin,
+        })
+    }
+
+    pub fn finish(&mut self) -> Result<()> {
+        drop(self.stdin.take());
+        let status = self.child.wait()?;
+        if !status.success() {
+            bail!(trf(
+                "{} could not compress the output ({})",
+                &[self.program, &status.to_string()]
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Write for Encoder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.stdin {
+            Some(ref mut stdin) => stdin.write(buf),
+            None => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.stdin {
+            Some(ref mut stdin) => stdin.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Encoder {
+    fn drop(&mut self) {
+        if self.stdin.is_some() {
+            drop(self.stdin.take());
+            let _ = self.child.wait();
+        }
+    }
+}
+
+// An input, read as it is or decompressed.
+pub enum Input {
+    Plain(BufReader<File>),
+    Stdin(BufReader<Stdin>),
+    Decoded(BufReader<Decoder>),
+}
+
+impl Input {
+    // Open a file, or stdin for `-`, decompressing it if its name says
+    // it's compressed, or with `sniff` if it starts the way compressed
+    // data does.
+    pub fn open(path: &Path, sniff: bool) -> io::Result<Input> {
+        if path == Path::new("-") {
+            return Input::stdin(sniff);
+        }
+        let mut reader = BufReader::new(File::open(path)?);
+        let codec = match Codec::from_name(path) {
+            None if sniff => Codec::sniff(reader.fill_buf()?),
+            codec => codec,
+        };
+        match codec {
+            Some(codec) => Ok(Input::Decoded(BufReader::new(
+                Decoder::spawn(codec, reader).map_err(io::Error::other)?,
+            ))),
+            None => Ok(Input::Plain(reader)),
+        }
+    }
+
+    pub fn stdin(sniff: bool) -> io::Result<Input> {
+        let mut reader = BufReader::new(io::stdin());
+        let codec = match sniff {
+            true => Codec::sniff(reader.fill_buf()?),
+            false => None,
+        };
+        match codec {
+            Some(codec) => Ok(Input::Decoded(BufReader::new(
+                Decoder::spawn(codec, reader).map_err(io::Error::other)?,
+            ))),
+            None => Ok(Input::Stdin(reader)),
+        }
+    }
+
+    // Move to `offset` bytes into the data, for resuming, which for
+    // stdin or a decompressed input means reading that far.
+    pub fn skip_to(&mut self, offset: u64) -> io::Result<()> {
+        match self {
+            Input::Plain(reader) => reader.seek(SeekFrom::Start(offset)).map(|_| ()),
+            Input::Stdin(_) | Input::Decoded(_) => {
+                io::copy(&mut self.take(offset), &mut io::sink()).map(|_| ())
+            }
+        }
+    }
+}
+
+impl Read for Input {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Plain(reader) => reader.read(buf),
+            Input::Stdin(reader) => reader.read(buf),
+            Input::Decoded(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl BufRead for Input {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Input::Plain(reader) => reader.fill_buf(),
+            Input::Stdin(reader) => reader.fill_buf(),
+            Input::Decoded(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Input::Plain(reader) => reader.consume(amt),
+            Input::Stdin(reader) => reader.consume(amt),
+            Input::Decoded(reader) => reader.consume(amt),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Compress with the system's gzip, if there is one.
+    fn gzip(text: &str) -> Option<Vec<u8>> {
+        let mut child = Command::new("gzip")
+            .arg("-c")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+        let mut stdin = child.stdin.take()?;
+        let text = text.to_string();
+        let feeder = thread::spawn(move || io::Write::write_all(&mut stdin, text.as_bytes()));
+        let output = child.wait_with_output().ok()?;
+        feeder.join().ok()?.ok()?;
+        Some(output.stdout)
+    }
+
+    #[test]
+    fn test_codecs() {
+        assert_eq!(Codec::from_name(Path::new("a/log.gz")), Some(Codec::Gzip));
+        assert_eq!(Codec::from_name(Path::new("x.tar.zst")), Some(Codec::Zstd));
+        assert_eq!(Codec::from_name(Path::new("gz")), None);
+        assert_eq!(Codec::sniff(b"BZh91AY"), Some(Codec::Bzip2));
+        assert_eq!(Codec::sniff(b"\xfd7zXZ\0\0"), Some(Codec::Xz));
+        assert_eq!(Codec::sniff(b"\x1f"), None);
+        assert_eq!(Codec::sniff(b"a,b\n"), None);
+    }
+
+    #[test]
+    fn test_decoder() {
+        let Some(compressed) = gzip("a b\nc d\n") else {
+            eprintln!("skipping: no gzip");
+            return;
+        };
+        assert_eq!(Codec::sniff(&compressed), Some(Codec::Gzip));
+        let mut text = String::new();
+        Decoder::spawn(Codec::Gzip, io::Cursor::new(compressed.clone()))
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "a b\nc d\n");
+        let mut decoder = Decoder::spawn(Codec::Gzip, &b"\x1f\x8bnot really"[..]).unwrap();
+        assert!(decoder.read_to_end(&mut vec![]).is_err());
+
+        // Named for what it isn't and found out by its magic instead.
+        let path = std::env::temp_dir().join(format!("rcut-{}.txt", std::process::id()));
+        std::fs::write(&path, &compressed).unwrap();
+        let mut input = Input::open(&path, true).unwrap();
+        input.skip_to(4).unwrap();
+        let mut text = String::new();
+        input.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "c d\n");
+        assert!(matches!(
+            Input::open(&path, false).unwrap(),
+            Input::Plain(_)
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_encoder() {
+        if gzip("").is_none() {
+            eprintln!("skipping: no gzip");
+            return;
+        }
+        let path = std::env::temp_dir().join(format!("rcut-{}.gz", std::process::id()));
+        let file = File::create(&path).unwrap();
+        let mut encoder = Encoder::spawn(Codec::Gzip, Stdio::from(file)).unwrap();
+        encoder.write_all(b"a b\nc d\n").unwrap();
+        encoder.finish().unwrap();
+        let mut text = String::new();
+        Input::open(&path, false)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "a b\nc d\n");
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(Codec::parse("zst").unwrap(), Codec::Zstd);
+        assert_eq!(Codec::parse("bzip2").unwrap(), Codec::Bzip2);
+        assert!(Codec::parse("zip").is_err());
+    }
+}
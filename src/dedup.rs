@@ -0,0 +1,216 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use anyhow::Result;
+
+use crate::{aggregate::Aggregate, memory::row_size, FieldSelector};
+
+// Which record --dedup-key keeps out of those sharing a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Keep {
+    First,
+    Last,
+}
+
+fn key_of(key: &FieldSelector, fields: &[&str]) -> Vec<String> {
+    key.select(fields).into_iter().map(String::from).collect()
+}
+
+// Keeping the first record with each key needs no buffering: a record
+// can go out as soon as its key turns out to be new.  Only looking for
+// a key among the records just before it, as `uniq` does, needs no
+// more than the last key held.
+#[derive(Debug)]
+pub struct Distinct {
+    key: FieldSelector,
+    seen: HashSet<Vec<String>>,
+    // For --unique-consecutive, the key of the record before.
+    last: Option<Option<Vec<String>>>,
+    // Whether the next record is the header, which is always kept and
+    // isn't a key to compare the rest with.
+    header: bool,
+    bytes: usize,
+}
+
+impl Distinct {
+    pub fn new(key: FieldSelector) -> Self {
+        Distinct {
+            key,
+            seen: HashSet::new(),
+            last: None,
+            header: false,
+            bytes: 0,
+        }
+    }
+
+    pub fn with_header(mut self, named: bool) -> Self {
+        self.header = named;
+        self
+    }
+
+    // Drop only records whose key is the same as the one before's.
+    pub fn consecutive(key: FieldSelector) -> Self {
+        Distinct {
+            last: Some(None),
+            ..Distinct::new(key)
+        }
+    }
+
+    pub fn first_time(&mut self, fields: &[&str]) -> bool {
+        if std::mem::take(&mut self.header) {
+            return true;
+        }
+        let key = key_of(&self.key, fields);
+        let size = row_size(&key);
+        if let Some(ref mut last) = self.last {
+            if last.as_ref() == Some(&key) {
+                return false;
+            }
+            *last = Some(key);
+            self.bytes = size;
+            return true;
+        }
+        let first = self.seen.insert(key);
+        if first {
+            self.bytes += size;
+        }
+        first
+    }
+
+    // Roughly how many bytes the keys seen so far take up.
+    pub fn footprint(&self) -> usize {
+        self.bytes
+    }
+}
+
+// Keeping the last record with each key means waiting for the end of
+// the input.  Records come out in the order of those kept, after the
+// header where the first record is one.
+#[derive(Debug)]
+pub struct KeepLast {
+    key: FieldSelector,
+    // Where each key's latest record is in `rows`.
+    latest: HashMap<Vec<String>, u64>,
+    rows: BTreeMap<u64, Vec<String>>,
+    records: u64,
+    named: bool,
+    header: Option<Vec<String>>,
+    bytes: usize,
+}
+
+impl KeepLast {
+    pub fn new(key: FieldSelector) -> Self {
+        KeepLast {
+            key,
+            latest: HashMap::new(),
+            rows: BTreeMap::new(),
+            records: 0,
+            named: false,
+            header: None,
+            bytes: 0,
+        }
+    }
+
+    pub fn with_header(mut self, named: bool) -> Self {
+        self.named = named;
+        self
+    }
+}
+
+impl Aggregate for KeepLast {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        if self.named && self.header.is_none() {
+            self.header = Some(fields.iter().map(|name| String::from(*name)).collect());
+            return Ok(());
+        }
+        self.records += 1;
+        let key = key_of(&self.key, fields);
+        let key_size = row_size(&key);
+        match self.latest.insert(key, self.records) {
+            Some(previous) => {
+                if let Some(row) = self.rows.remove(&previous) {
+                    self.bytes -= row_size(&row);
+                }
+            }
+            None => self.bytes += key_size,
+        }
+        self.bytes += row_size(fields);
+        self.rows.insert(
+            self.records,
+            fields.iter().map(|val| String::from(*val)).collect(),
+        );
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        self.latest.clear();
+        self.bytes = 0;
+        let rows = std::mem::take(&mut self.rows).into_values();
+        Ok(self.header.take().into_iter().chain(rows).collect())
+    }
+
+    fn footprint(&self) -> usize {
+        self.bytes
+    }
+
+    fn headed(&self) -> bool {
+        self.header.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field_parser;
+
+    const EVENTS: [[&str; 2]; 4] = [
+        ["alice", "login"],
+        ["bob", "login"],
+        ["alice", "logout"],
+        ["carol", "login"],
+    ];
+
+    #[test]
+    fn test_distinct() {
+        let mut distinct = Distinct::new(field_parser("1").unwrap());
+        let kept: Vec<bool> = EVENTS.iter().map(|row| distinct.first_time(row)).collect();
+        assert_eq!(kept, vec![true, true, false, true]);
+    }
+
+    #[test]
+    fn test_consecutive() {
+        let mut distinct = Distinct::consecutive(field_parser("1").unwrap());
+        let rows = [["a", "1"], ["a", "2"], ["b", "1"], ["a", "3"], ["a", "1"]];
+        let kept: Vec<bool> = rows.iter().map(|row| distinct.first_time(row)).collect();
+        assert_eq!(kept, vec![true, false, true, true, false]);
+        assert_eq!(distinct.footprint(), row_size(&["a"]));
+    }
+
+    #[test]
+    fn test_keep_last() {
+        let mut keep_last = KeepLast::new(field_parser("1").unwrap());
+        for row in EVENTS.iter() {
+            keep_last.add(row).unwrap();
+        }
+        assert_eq!(
+            keep_last.finish().unwrap(),
+            vec![
+                vec!["bob", "login"],
+                vec!["alice", "logout"],
+                vec!["carol", "login"]
+            ]
+        );
+        // A data record the same as the header doesn't replace it.
+        let mut keep_last = KeepLast::new(field_parser("1").unwrap()).with_header(true);
+        for row in [["user", "event"], ["bob", "login"], ["user", "event"]] {
+            keep_last.add(&row).unwrap();
+        }
+        assert_eq!(
+            keep_last.finish().unwrap(),
+            vec![
+                vec!["user", "event"],
+                vec!["bob", "login"],
+                vec!["user", "event"]
+            ]
+        );
+    }
+}
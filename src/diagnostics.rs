@@ -0,0 +1,240 @@
+use std::{ffi::OsString, fmt, io, io::Write, num::ParseIntError};
+
+use clap::ValueEnum;
+use serde_json::json;
+
+use crate::i18n::{tr, trf};
+
+// How warnings and errors are written to stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+impl ErrorFormat {
+    // Errors can happen before the command line is fully parsed (a
+    // bad selector, say), so find --error-format in the raw arguments
+    // rather than waiting on clap.
+    pub fn sniff(args: impl IntoIterator<Item = OsString>) -> Self {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = match arg.to_str() {
+                Some("--") => break,
+                Some("--error-format") => args.next(),
+                Some(s) => s.strip_prefix("--error-format=").map(OsString::from),
+                None => None,
+            };
+            if let Some(format) =
+                value.and_then(|v| v.to_str().and_then(|v| Self::from_str(v, false).ok()))
+            {
+                return format;
+            }
+        }
+        ErrorFormat::Text
+    }
+}
+
+// Error context naming the input being read when an error happened.
+#[derive(Debug)]
+pub struct InputFile(pub String);
+
+impl fmt::Display for InputFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Error context naming the (1-based) line being processed.
+#[derive(Debug)]
+pub struct InputLine(pub u64);
+
+impl fmt::Display for InputLine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}", self.0)
+    }
+}
+
+// Error context for a selector that failed to parse.
+#[derive(Debug)]
+pub struct InvalidSelector(pub String);
+
+impl fmt::Display for InvalidSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", trf("invalid field selector '{}'", &[&self.0]))
+    }
+}
+
+// Error for a line that isn't UTF-8, saying why it can't be cut.
+#[derive(Debug)]
+pub struct NotUtf8;
+
+impl fmt::Display for NotUtf8 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message =
+            "the line is not UTF-8, which only -b, or -f with a plain -d or whitespace, can cut";
+        write!(f, "{}", tr(message))
+    }
+}
+
+impl std::error::Error for NotUtf8 {}
+
+// Error for a line with fewer fields than the selector names, under
+// --strict.
+#[derive(Debug)]
+pub struct MissingFields {
+    pub found: usize,
+    pub needed: usize,
+}
+
+impl fmt::Display for MissingFields {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = trf(
+            "the line has only {} of the {} fields the selector needs",
+            &[&self.found.to_string(), &self.needed.to_string()],
+        );
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for MissingFields {}
+
+// A warning or error, with enough structure for a machine to act on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: &'static str,
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn new<S: Into<String>>(code: &'static str, message: S) -> Self {
+        Diagnostic {
+            code,
+            file: None,
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_file<S: Into<String>>(mut self, file: S) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        Diagnostic {
+            code: error_code(err),
+            file: err.downcast_ref::<InputFile>().map(|f| f.0.clone()),
+            line: err.downcast_ref::<InputLine>().map(|l| l.0),
+            message: format!("{:#}", err),
+        }
+    }
+
+    fn to_json(&self, severity: &str) -> String {
+        json!({
+            "severity": severity,
+            "code": self.code,
+            "file": self.file,
+            "line": self.line,
+            "message": self.message,
+        })
+        .to_string()
+    }
+}
+
+// Classify an error into a stable code, looking through its causes for
+// the first one we recognize.
+fn error_code(err: &anyhow::Error) -> &'static str {
+    if err.downcast_ref::<InvalidSelector>().is_some() {
+        return "invalid-selector";
+    }
+    if err.is::<MissingFields>() {
+        return "missing-fields";
+    }
+    if err.is::<crate::signals::Interrupted>() {
+        return "interrupted";
+    }
+    if err.is::<crate::signals::TimedOut>() {
+        return "timed-out";
+    }
+    for cause in err.chain() {
+        if let Some(io_err) = cause.downcast_ref::<io::Error>() {
+            return match io_err.kind() {
+                io::ErrorKind::NotFound => "not-found",
+                io::ErrorKind::PermissionDenied => "permission-denied",
+                io::ErrorKind::InvalidData => "invalid-data",
+                _ => "io",
+            };
+        }
+        if cause.is::<ParseIntError>() {
+            return "invalid-selector";
+        }
+        if cause.is::<clap::Error>() {
+            return "usage";
+        }
+    }
+    "error"
+}
+
+pub fn warn(diagnostic: &Diagnostic, format: ErrorFormat) {
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+    // Nothing sensible to do if stderr itself is gone.
+    let _ = match format {
+        ErrorFormat::Text => writeln!(stderr, "rcut: {}: {}", tr("warning"), diagnostic.message),
+        ErrorFormat::Json => writeln!(stderr, "{}", diagnostic.to_json("warning")),
+    };
+}
+
+pub fn error(err: &anyhow::Error, format: ErrorFormat) {
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+    let _ = match format {
+        ErrorFormat::Text => writeln!(stderr, "{}: {:?}", tr("Error"), err),
+        ErrorFormat::Json => writeln!(stderr, "{}", Diagnostic::from_error(err).to_json("error")),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        let args = |v: &[&str]| v.iter().map(OsString::from).collect::<Vec<_>>();
+        assert_eq!(ErrorFormat::sniff(args(&["rcut", "1"])), ErrorFormat::Text);
+        assert_eq!(
+            ErrorFormat::sniff(args(&["rcut", "--error-format", "json", "1"])),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            ErrorFormat::sniff(args(&["rcut", "--error-format=json"])),
+            ErrorFormat::Json
+        );
+        assert_eq!(
+            ErrorFormat::sniff(args(&["rcut", "1", "--", "--error-format=json"])),
+            ErrorFormat::Text
+        );
+    }
+
+    #[test]
+    fn test_from_error() {
+        let err = Err::<(), _>(io::Error::new(io::ErrorKind::InvalidData, "bad utf-8"))
+            .context(InputLine(3))
+            .context(InputFile(String::from("data.txt")))
+            .unwrap_err();
+        let diagnostic = Diagnostic::from_error(&err);
+        assert_eq!(diagnostic.code, "invalid-data");
+        assert_eq!(diagnostic.file.as_deref(), Some("data.txt"));
+        assert_eq!(diagnostic.line, Some(3));
+        assert_eq!(diagnostic.message, "data.txt: line 3: bad utf-8");
+        assert_eq!(
+            diagnostic.to_json("error"),
+            r#"{"code":"invalid-data","file":"data.txt","line":3,"message":"data.txt: line 3: bad utf-8","severity":"error"}"#
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::Result;
+
+use crate::{i18n::trf, resolve_index};
+
+// What --explode asked for: split one field of the selected record on
+// a sub-delimiter, into as many columns as there are names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explode {
+    pub field: isize,
+    pub delim: String,
+    pub names: Vec<String>,
+}
+
+// Parse `N:DELIM:NAME,...`.  DELIM runs up to the last colon, so it
+// can be a colon itself, as in `2:::user,host`.
+pub fn parse_explode(s: &str) -> Result<Explode> {
+    let invalid = || format_err!(trf("invalid explode '{}'", &[s]));
+    let (field, rest) = s.split_once(':').ok_or_else(invalid)?;
+    let (delim, names) = rest.rsplit_once(':').ok_or_else(invalid)?;
+    let field = field
+        .trim()
+        .parse::<isize>()
+        .ok()
+        .filter(|idx| *idx != 0)
+        .ok_or_else(invalid)?;
+    let names: Vec<String> = names
+        .split(',')
+        .map(|name| String::from(name.trim()))
+        .collect();
+    if delim.is_empty() || names.iter().any(String::is_empty) {
+        return Err(invalid());
+    }
+    Ok(Explode {
+        field,
+        delim: String::from(delim),
+        names,
+    })
+}
+
+impl Explode {
+    // Replace the field with its parts.  There are always exactly as
+    // many parts as names: the last keeps any extra delimiters, and a
+    // value with too few parts is padded with empty ones.  A record
+    // without the field is left alone.
+    pub fn apply<'a>(&self, fields: &[&'a str]) -> Vec<&'a str> {
+        let offset = match resolve_index(self.field, fields.len()) {
+            Some(offset) => offset,
+            None => return fields.to_vec(),
+        };
+        let mut exploded = fields[..offset].to_vec();
+        let parts: Vec<&str> = fields[offset]
+            .splitn(self.names.len(), self.delim.as_str())
+            .collect();
+        exploded.extend((0..self.names.len()).map(|i| parts.get(i).copied().unwrap_or("")));
+        exploded.extend_from_slice(&fields[offset + 1..]);
+        exploded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_explode() {
+        let explode = parse_explode("4:/:proto,host,path").unwrap();
+        assert_eq!(explode.field, 4);
+        assert_eq!(explode.delim, "/");
+        assert_eq!(explode.names, vec!["proto", "host", "path"]);
+        assert_eq!(parse_explode("-1:::user,host").unwrap().delim, ":");
+        assert!(parse_explode("0:/:a").is_err());
+        assert!(parse_explode("1::a,b").is_err());
+        assert!(parse_explode("1:/:a,,b").is_err());
+        assert!(parse_explode("1/a").is_err());
+    }
+
+    #[test]
+    fn test_apply() {
+        let explode = parse_explode("2:/:host,path").unwrap();
+        assert_eq!(
+            explode.apply(&["GET", "example.com/a/b", "200"]),
+            vec!["GET", "example.com", "a/b", "200"]
+        );
+        assert_eq!(
+            explode.apply(&["GET", "example.com"]),
+            vec!["GET", "example.com", ""]
+        );
+        assert_eq!(explode.apply(&["GET"]), vec!["GET"]);
+    }
+}
@@ -0,0 +1,523 @@
+use std::{cmp::Ordering, fmt};
+
+use anyhow::Result;
+
+use crate::{aggregate::NumericLocale, i18n::tr, i18n::trf, resolve_index};
+
+// A deliberately tiny awk: `[print] EXPR, ... [if COND]`, run once per
+// record.  Expressions see the record's fields as `$N` (`$-N` from the
+// end), the field count as NF and the record count as NR, and have
+// arithmetic, comparisons and `&&`/`||`/`!`, but no functions and no
+// variables.
+#[derive(Debug, Clone)]
+pub struct Program {
+    source: String,
+    outputs: Vec<Expr>,
+    condition: Option<Expr>,
+    locale: NumericLocale,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    Str(String),
+    Field(isize),
+    FieldCount,
+    RecordNumber,
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Word(String),
+    Dollar,
+    Comma,
+    Open,
+    Close,
+    Op(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Str(s) => write!(f, "{:?}", s),
+            Token::Word(w) => write!(f, "{}", w),
+            Token::Dollar => write!(f, "$"),
+            Token::Comma => write!(f, ","),
+            Token::Open => write!(f, "("),
+            Token::Close => write!(f, ")"),
+            Token::Op(op) => write!(f, "{}", op),
+        }
+    }
+}
+
+// Longest first, so `<=` isn't read as `<` then `=`.
+const OPERATORS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "<", ">", "+", "-", "*", "/", "%", "!",
+];
+
+fn tokenize(s: &str) -> Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut rest = s.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let len = if c.is_ascii_digit() || c == '.' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse::<f64>()
+                .map_err(|_| format_err!(trf("unexpected '{}' in expression", &[&rest[..len]])))?;
+            tokens.push(Token::Number(number));
+            len
+        } else if c == '"' {
+            let end = rest[1..]
+                .find('"')
+                .ok_or_else(|| format_err!(tr("unterminated string in expression")))?;
+            tokens.push(Token::Str(String::from(&rest[1..end + 1])));
+            end + 2
+        } else if c.is_ascii_alphabetic() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len());
+            tokens.push(Token::Word(String::from(&rest[..len])));
+            len
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            op.len()
+        } else {
+            tokens.push(match c {
+                '$' => Token::Dollar,
+                ',' => Token::Comma,
+                '(' => Token::Open,
+                ')' => Token::Close,
+                _ => bail!(trf("unexpected '{}' in expression", &[&c.to_string()])),
+            });
+            c.len_utf8()
+        };
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+// Limits on what's parsed, so no program can run the parser, or the
+// evaluation of what it builds, out of stack.
+const MAX_TOKENS: usize = 1000;
+const MAX_NESTING: usize = 64;
+
+// A recursive-descent parser, one method per precedence level, from
+// `||` binding loosest down to unary minus.
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    // How deep in parentheses and prefix operators the parser is.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| format_err!(tr("unexpected end of expression")))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    // Consume the next token if it is `token`.
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Parse something nested inside whatever is being parsed now.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Expr>) -> Result<Expr> {
+        if self.depth == MAX_NESTING {
+            bail!(tr("expression is nested too deeply"));
+        }
+        self.depth += 1;
+        let expr = parse(self);
+        self.depth -= 1;
+        expr
+    }
+
+    fn unexpected(&self) -> anyhow::Error {
+        match self.peek() {
+            Some(token) => format_err!(trf("unexpected '{}' in expression", &[&token.to_string()])),
+            None => format_err!(tr("unexpected end of expression")),
+        }
+    }
+
+    // Parse one level of left-associative binary operators.
+    fn binary(
+        &mut self,
+        ops: &[(&'static str, Op)],
+        operand: fn(&mut Self) -> Result<Expr>,
+    ) -> Result<Expr> {
+        let mut lhs = operand(self)?;
+        'outer: loop {
+            for (text, op) in ops {
+                if self.eat(&Token::Op(text)) {
+                    lhs = Expr::Binary(*op, Box::new(lhs), Box::new(operand(self)?));
+                    continue 'outer;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        self.binary(&[("||", Op::Or)], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        self.binary(&[("&&", Op::And)], Self::not)
+    }
+
+    fn not(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Op("!")) {
+            return Ok(Expr::Not(Box::new(self.nested(Self::not)?)));
+        }
+        self.comparison()
+    }
+
+    // Comparisons don't chain: `a < b < c` is an error, not a puzzle.
+    fn comparison(&mut self) -> Result<Expr> {
+        let lhs = self.sum()?;
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+        for (text, op) in ops {
+            if self.eat(&Token::Op(text)) {
+                return Ok(Expr::Binary(op, Box::new(lhs), Box::new(self.sum()?)));
+            }
+        }
+        Ok(lhs)
+    }
+
+    fn sum(&mut self) -> Result<Expr> {
+        self.binary(&[("+", Op::Add), ("-", Op::Sub)], Self::term)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        self.binary(
+            &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+            Self::unary,
+        )
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.eat(&Token::Op("-")) {
+            return Ok(Expr::Neg(Box::new(self.nested(Self::unary)?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let token = self.peek().cloned();
+        let expr = match token {
+            Some(Token::Number(n)) => Expr::Number(n),
+            Some(Token::Str(s)) => Expr::Str(s),
+            Some(Token::Word(ref w)) if w == "NF" => Expr::FieldCount,
+            Some(Token::Word(ref w)) if w == "NR" => Expr::RecordNumber,
+            Some(Token::Dollar) => {
+                self.pos += 1;
+                return self.field();
+            }
+            Some(Token::Open) => {
+                self.pos += 1;
+                let expr = self.nested(Self::or)?;
+                if !self.eat(&Token::Close) {
+                    return Err(self.unexpected());
+                }
+                return Ok(expr);
+            }
+            _ => return Err(self.unexpected()),
+        };
+        self.pos += 1;
+        Ok(expr)
+    }
+
+    // What follows a `$`: a field number, maybe negative.
+    fn field(&mut self) -> Result<Expr> {
+        let negative = self.eat(&Token::Op("-"));
+        match self.next()? {
+            Token::Number(n) if n.fract() == 0.0 && n >= 1.0 => {
+                let idx = n as isize;
+                Ok(Expr::Field(if negative { -idx } else { idx }))
+            }
+            token => bail!(trf("unexpected '{}' in expression", &[&token.to_string()])),
+        }
+    }
+}
+
+// A value is a number if it looks like one, as in awk, so `$3 > 10`
+// compares numerically while `$2 == "ERR"` compares text.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(f64),
+    Str(String),
+}
+
+impl Value {
+    fn from_field(s: &str, locale: NumericLocale) -> Self {
+        match locale.parse(s) {
+            Some(n) => Value::Number(n),
+            None => Value::Str(String::from(s)),
+        }
+    }
+
+    fn bool(b: bool) -> Self {
+        Value::Number(if b { 1.0 } else { 0.0 })
+    }
+
+    // Non-numeric text counts as zero in arithmetic.
+    fn number(&self) -> f64 {
+        match self {
+            Value::Number(n) => *n,
+            Value::Str(_) => 0.0,
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Number(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+
+    fn compare(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+            _ => self.to_string().cmp(&other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, fields: &[&str], records: u64, locale: NumericLocale) -> Result<Value> {
+        let eval = |expr: &Expr| expr.eval(fields, records, locale);
+        Ok(match self {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Str(s) => Value::Str(s.clone()),
+            Expr::Field(idx) => Value::from_field(
+                resolve_index(*idx, fields.len()).map_or("", |i| fields[i]),
+                locale,
+            ),
+            Expr::FieldCount => Value::Number(fields.len() as f64),
+            Expr::RecordNumber => Value::Number(records as f64),
+            Expr::Neg(expr) => Value::Number(-eval(expr)?.number()),
+            Expr::Not(expr) => Value::bool(!eval(expr)?.truthy()),
+            Expr::Binary(Op::And, lhs, rhs) => {
+                Value::bool(eval(lhs)?.truthy() && eval(rhs)?.truthy())
+            }
+            Expr::Binary(Op::Or, lhs, rhs) => {
+                Value::bool(eval(lhs)?.truthy() || eval(rhs)?.truthy())
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (eval(lhs)?, eval(rhs)?);
+                let (a, b) = (lhs.number(), rhs.number());
+                match op {
+                    Op::Add => Value::Number(a + b),
+                    Op::Sub => Value::Number(a - b),
+                    Op::Mul => Value::Number(a * b),
+                    Op::Div | Op::Rem if b == 0.0 => bail!(tr("division by zero")),
+                    Op::Div => Value::Number(a / b),
+                    Op::Rem => Value::Number(a % b),
+                    Op::Eq => Value::bool(lhs.compare(&rhs) == Ordering::Equal),
+                    Op::Ne => Value::bool(lhs.compare(&rhs) != Ordering::Equal),
+                    Op::Lt => Value::bool(lhs.compare(&rhs) == Ordering::Less),
+                    Op::Le => Value::bool(lhs.compare(&rhs) != Ordering::Greater),
+                    Op::Gt => Value::bool(lhs.compare(&rhs) == Ordering::Greater),
+                    Op::Ge => Value::bool(lhs.compare(&rhs) != Ordering::Less),
+                    Op::And | Op::Or => unreachable!(),
+                }
+            }
+        })
+    }
+}
+
+impl Program {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Read numbers in fields as `locale` writes them; those in the
+    // program itself are always plain.
+    pub fn with_locale(mut self, locale: NumericLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    // Run the program over record number `records`, returning the
+    // fields to print, or None if the condition turned it away.  With
+    // nothing after `print`, the whole record is printed.
+    pub fn run(&self, fields: &[&str], records: u64) -> Result<Option<Vec<String>>> {
+        if let Some(ref condition) = self.condition {
+            if !condition.eval(fields, records, self.locale)?.truthy() {
+                return Ok(None);
+            }
+        }
+        if self.outputs.is_empty() {
+            return Ok(Some(fields.iter().map(|val| String::from(*val)).collect()));
+        }
+        self.outputs
+            .iter()
+            .map(|expr| Ok(expr.eval(fields, records, self.locale)?.to_string()))
+            .collect::<Result<Vec<String>>>()
+            .map(Some)
+    }
+}
+
+pub fn parse_program(s: &str) -> Result<Program> {
+    let tokens = tokenize(s)?;
+    if tokens.len() > MAX_TOKENS {
+        bail!(tr("expression is too long"));
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let word = |w: &str| Token::Word(String::from(w));
+    parser.eat(&word("print"));
+    let mut outputs = vec![];
+    if parser.peek().is_some() && parser.peek() != Some(&word("if")) {
+        outputs.push(parser.or()?);
+        while parser.eat(&Token::Comma) {
+            outputs.push(parser.or()?);
+        }
+    }
+    let condition = if parser.eat(&word("if")) {
+        Some(parser.or()?)
+    } else {
+        None
+    };
+    if parser.peek().is_some() {
+        return Err(parser.unexpected());
+    }
+    Ok(Program {
+        source: String::from(s),
+        outputs,
+        condition,
+        locale: NumericLocale::C,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &str, fields: &[&str]) -> Option<Vec<String>> {
+        parse_program(program).unwrap().run(fields, 1).unwrap()
+    }
+
+    #[test]
+    fn test_parse_program() {
+        assert!(parse_program("print $1, $3*1000 if $2 == \"ERR\"").is_ok());
+        assert!(parse_program("").is_ok());
+        assert!(parse_program("if NF > 2").is_ok());
+        assert!(parse_program("print $0").is_err());
+        assert!(parse_program("print $1 +").is_err());
+        assert!(parse_program("print (1").is_err());
+        assert!(parse_program("print \"open").is_err());
+        assert!(parse_program("print 1 2").is_err());
+        assert!(parse_program("print foo").is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        assert!(parse_program(&format!("{}1{}", "(".repeat(50), ")".repeat(50))).is_ok());
+        assert!(parse_program(&format!("{}1", "(".repeat(10000))).is_err());
+        assert!(parse_program(&format!("{}1", "!".repeat(10000))).is_err());
+        assert!(parse_program(&"1+".repeat(10000)).is_err());
+    }
+
+    #[test]
+    fn test_run() {
+        let program = "print $1, $3*1000 if $2 == \"ERR\"";
+        assert_eq!(
+            run(program, &["web", "ERR", "1.5"]),
+            Some(vec![String::from("web"), String::from("1500")])
+        );
+        assert_eq!(run(program, &["web", "OK", "1.5"]), None);
+        assert_eq!(
+            run("print $-1, NF, -(1 + 2) * 2 % 4", &["a", "b"]),
+            Some(vec![
+                String::from("b"),
+                String::from("2"),
+                String::from("-2")
+            ])
+        );
+        assert_eq!(
+            run("if $1 > 9 && !($2 < \"m\")", &["10", "x"])
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(run("if $1 > 9", &["9"]), None);
+        assert!(parse_program("print 1 / $1")
+            .unwrap()
+            .run(&["0"], 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_record_number() {
+        let program = parse_program("print NR if NR % 2 == 0").unwrap();
+        let outputs: Vec<_> = (1..=4).map(|nr| program.run(&[], nr).unwrap()).collect();
+        assert_eq!(
+            outputs,
+            vec![
+                None,
+                Some(vec![String::from("2")]),
+                None,
+                Some(vec![String::from("4")])
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{self, BufRead, BufReader},
+};
+
+use anyhow::{Context, Result};
+
+use crate::diagnostics::InputFile;
+
+// Where --files-from and --files0-from find the names of the inputs,
+// each ended by a newline or by a NUL, as `find -print0` writes them.
+// A LIST of `-` is stdin.
+#[derive(Debug, Clone)]
+pub struct FileList {
+    pub path: OsString,
+    pub terminator: u8,
+}
+
+impl FileList {
+    pub fn describe(&self) -> String {
+        let ended = match self.terminator {
+            0 => "NUL-separated",
+            _ => "one per line",
+        };
+        format!("listed in {}, {}", self.path.to_string_lossy(), ended)
+    }
+
+    // The names in the list, read as they're asked for rather than all
+    // at once, so that a list of millions of files never has to be held
+    // in memory.  Empty names are skipped.
+    pub fn names(&self) -> Result<impl Iterator<Item = Result<OsString>>> {
+        let name = self.path.to_string_lossy().into_owned();
+        let reader: Box<dyn BufRead> = if self.path == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            let file = File::open(&self.path).with_context(|| InputFile(name.clone()))?;
+            Box::new(BufReader::new(file))
+        };
+        Ok(reader
+            .split(self.terminator)
+            .filter(|entry| !matches!(entry, Ok(entry) if entry.is_empty()))
+            .map(move |entry| {
+                entry
+                    .map(os_string)
+                    .with_context(|| InputFile(name.clone()))
+            }))
+    }
+}
+
+#[cfg(unix)]
+fn os_string(name: Vec<u8>) -> OsString {
+    std::os::unix::ffi::OsStringExt::from_vec(name)
+}
+
+#[cfg(not(unix))]
+fn os_string(name: Vec<u8>) -> OsString {
+    OsString::from(String::from_utf8_lossy(&name).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        let path = std::env::temp_dir().join(format!("rcut-filelist-{}", std::process::id()));
+        std::fs::write(&path, b"a.csv\0\0dir/b c.csv\0").unwrap();
+        let names = |terminator| -> Vec<OsString> {
+            let list = FileList {
+                path: path.clone().into_os_string(),
+                terminator,
+            };
+            list.names().unwrap().map(Result::unwrap).collect()
+        };
+        assert_eq!(names(0), ["a.csv", "dir/b c.csv"]);
+        assert_eq!(names(b'\n'), ["a.csv\0\0dir/b c.csv\0"]);
+        std::fs::remove_file(&path).unwrap();
+        let missing = FileList {
+            path: path.into_os_string(),
+            terminator: b'\n',
+        };
+        assert!(missing.names().is_err());
+    }
+}
@@ -0,0 +1,100 @@
+// Entry points for the fuzz targets in fuzz/, each taking arbitrary
+// input and running it through one of the parsers or tokenizers.  None
+// of them should ever panic, whatever they're given.
+
+use std::io;
+
+use crate::{
+    cli::{parse_delimiter, parse_fields, parse_selector, parse_stage, unescape},
+    columns, csv, default_separator, expr, CutJob, Delimiter, MAX_PADDED,
+};
+
+// A field selector, tried against a line of a few fields, padded out
+// where it's short when the selector is within --pad's reach, and with
+// any column names in it looked up in a header of a few names.
+pub fn selector(data: &[u8]) {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(selector) = parse_selector(s) {
+        selector.select(&["a", "b", "c", "d"]);
+        if selector.needs() <= MAX_PADDED {
+            selector.select_padded(&["a", "b"], &"-");
+        }
+        selector.describe();
+    }
+    if let Ok(selector) = parse_fields(s) {
+        selector.describe();
+        if let Ok(resolved) = selector.resolve_names(&["id", "name", "a-b"]) {
+            resolved.select(&["a", "b", "c", "d"]);
+        }
+    }
+}
+
+// A delimiter on the first line, then lines to split on it, both as
+// it's written and with its escapes read.
+pub fn delimiter(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let (delim, lines) = text.split_once('\n').unwrap_or((&text, ""));
+    let delims = [Some(String::from(delim)), unescape(delim).ok()];
+    for delim in delims.iter().flatten() {
+        if let Ok(delim) = parse_delimiter(delim) {
+            for line in lines.lines() {
+                delim.split(line);
+            }
+        }
+    }
+}
+
+// A CSV separator as the first character, then a record to split on
+// it.  Quoting the fields again has to give back the same fields.
+pub fn csv(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let mut chars = text.chars();
+    let Some(sep) = chars.next().filter(|sep| *sep != '"') else {
+        return;
+    };
+    let fields = csv::split(chars.as_str(), sep);
+    let requoted: Vec<_> = fields
+        .iter()
+        .map(|field| csv::quote(field, &sep.to_string()))
+        .collect();
+    assert_eq!(csv::split(&requoted.join(&sep.to_string()), sep), fields);
+}
+
+// Lines to find fixed-width columns in, and then split into them.
+pub fn columns(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let sample: Vec<String> = text.lines().map(String::from).collect();
+    let starts = columns::infer_columns(&sample);
+    for line in &sample {
+        columns::split_columns(line, &starts);
+    }
+}
+
+// An --expr program, run over a couple of records.
+pub fn expr(data: &[u8]) {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(program) = expr::parse_program(s) {
+        let _ = program.run(&["1", "two", "-3.5"], 1);
+        let _ = program.run(&[], 2);
+    }
+}
+
+// A --then stage on the first line, and input to cut with it.
+pub fn cut(data: &[u8]) {
+    let text = String::from_utf8_lossy(data);
+    let (spec, input) = text.split_once('\n').unwrap_or((&text, ""));
+    let Ok(stage) = parse_stage(spec) else {
+        return;
+    };
+    let job = CutJob {
+        output_separator: default_separator(stage.output_separator, &stage.delimiter),
+        input_delim: stage.delimiter.unwrap_or(Delimiter::Whitespace),
+        selector: stage.selector,
+        ..Default::default()
+    };
+    let _ = job.process_reader(input.as_bytes(), &mut io::sink());
+}
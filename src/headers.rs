@@ -0,0 +1,61 @@
+// Line up files whose columns come in different orders, going by the
+// names in each file's header.  The first file's header sets the order:
+// later files have their columns rearranged to match it, with columns
+// it doesn't know dropped and columns they lack left empty.
+#[derive(Debug, Default)]
+pub struct Alignment {
+    names: Option<Vec<String>>,
+    // Where each of the first file's columns is in the current file;
+    // None while reading the first file itself.
+    columns: Option<Vec<Option<usize>>>,
+}
+
+impl Alignment {
+    // Take in the header of the next file.  Returns true for the
+    // first file's, which is the only one to be printed.
+    pub fn header(&mut self, fields: &[&str]) -> bool {
+        match self.names {
+            None => {
+                self.names = Some(fields.iter().map(|name| String::from(*name)).collect());
+                true
+            }
+            Some(ref names) => {
+                self.columns = Some(
+                    names
+                        .iter()
+                        .map(|name| fields.iter().position(|field| field == name))
+                        .collect(),
+                );
+                false
+            }
+        }
+    }
+
+    // Rearrange a line's fields into the first file's column order.
+    pub fn align<'a>(&self, fields: Vec<&'a str>) -> Vec<&'a str> {
+        match self.columns {
+            None => fields,
+            Some(ref columns) => columns
+                .iter()
+                .map(|col| col.and_then(|col| fields.get(col).copied()).unwrap_or(""))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alignment() {
+        let mut alignment = Alignment::default();
+        assert!(alignment.header(&["id", "name", "email"]));
+        assert_eq!(alignment.align(vec!["1", "ann"]), vec!["1", "ann"]);
+        assert!(!alignment.header(&["email", "host", "id"]));
+        assert_eq!(
+            alignment.align(vec!["bo@example.com", "h1", "2"]),
+            vec!["2", "", "bo@example.com"]
+        );
+    }
+}
@@ -0,0 +1,1236 @@
+use std::{env, sync::OnceLock};
+
+use clap::Command;
+
+// Locales with a translation catalog.  Anything else falls back to
+// English, which is also the source language: messages are looked up
+// by their English text, gettext style, so an untranslated string
+// still reads correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    De,
+    Fr,
+}
+
+impl Locale {
+    // Follow the POSIX precedence: LC_ALL, then LC_MESSAGES, then LANG.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|val| !val.is_empty())
+            .map_or(Locale::En, |val| Locale::from_tag(&val))
+    }
+
+    // Map a tag like `de_DE.UTF-8` or `fr` to a Locale.
+    pub fn from_tag(tag: &str) -> Self {
+        let lang = tag
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "de" => Locale::De,
+            "fr" => Locale::Fr,
+            _ => Locale::En,
+        }
+    }
+}
+
+pub fn locale() -> Locale {
+    static LOCALE: OnceLock<Locale> = OnceLock::new();
+    *LOCALE.get_or_init(Locale::from_env)
+}
+
+// Translate a message into the current locale.
+pub fn tr(msg: &'static str) -> &'static str {
+    translate(locale(), msg)
+}
+
+// Translate a message and fill each `{}` placeholder in turn.
+pub fn trf(msg: &'static str, args: &[&str]) -> String {
+    let mut args = args.iter();
+    let mut pieces = tr(msg).split("{}");
+    let mut out = String::from(pieces.next().unwrap_or(""));
+    for piece in pieces {
+        out.push_str(args.next().copied().unwrap_or(""));
+        out.push_str(piece);
+    }
+    out
+}
+
+pub fn translate(locale: Locale, msg: &'static str) -> &'static str {
+    lookup(locale, msg).unwrap_or(msg)
+}
+
+// The translation of a message, if the locale's catalog has one.
+fn lookup(locale: Locale, msg: &str) -> Option<&'static str> {
+    match locale {
+        Locale::En => None,
+        Locale::De => german(msg),
+        Locale::Fr => french(msg),
+    }
+}
+
+// Translate the about and after-help text and every argument's help and
+// heading in a Command and its subcommands.
+pub fn localize(cmd: Command<'static>) -> Command<'static> {
+    let subcommands: Vec<String> = cmd
+        .get_subcommands()
+        .map(|sub| String::from(sub.get_name()))
+        .collect();
+    let cmd = subcommands
+        .into_iter()
+        .fold(cmd, |cmd, name| cmd.mut_subcommand(name.as_str(), localize));
+    let helps: Vec<(&'static str, Option<&'static str>, Option<&'static str>)> = cmd
+        .get_arguments()
+        .filter(|arg| arg.get_id() != "help" && arg.get_id() != "version")
+        .map(|arg| (arg.get_id(), arg.get_help(), arg.get_help_heading()))
+        .collect();
+    let about = cmd.get_about().map(tr);
+    let after_help = cmd.get_after_help().and_then(|help| lookup(locale(), help));
+    let has_version = cmd.get_version().is_some();
+    let mut cmd = cmd.mut_arg("help", |arg| arg.help(tr("Print help information")));
+    // mut_arg() would conjure up a --version for a command without one.
+    if has_version {
+        cmd = cmd.mut_arg("version", |arg| arg.help(tr("Print version information")));
+    }
+    if let Some(about) = about {
+        cmd = cmd.about(about);
+    }
+    if let Some(after_help) = after_help {
+        cmd = cmd.after_help(after_help);
+    }
+    helps.into_iter().fold(cmd, |cmd, (id, help, heading)| {
+        cmd.mut_arg(id, |arg| {
+            arg.help(help.map(tr)).help_heading(heading.map(tr))
+        })
+    })
+}
+
+fn german(msg: &str) -> Option<&'static str> {
+    Some(match msg {
+        "cut-like tool with smoother aesthetics" => {
+            "cut-ähnliches Werkzeug mit angenehmerer Bedienung"
+        }
+        "field delimiter" => "Feldtrenner",
+        "replace output field N with the PIECES of it between each DELIM, as N:DELIM:PIECES, like 3:@:2; repeatable" => {
+            "Ausgabefeld N durch seine Stücke PIECES zwischen den DELIM ersetzen, als N:DELIM:PIECES, \
+             etwa 3:@:2; wiederholbar"
+        }
+        "invalid subfield '{}': expected N:DELIM:PIECES" => "ungültiges Teilfeld '{}': N:DELIM:PIECES erwartet",
+        "join the lines of each file but the last with the lines of the last that share a key, given by --on" => {
+            "die Zeilen jeder Datei außer der letzten mit den Zeilen der letzten verbinden, die \
+             einen Schlüssel teilen, wie ihn --on angibt"
+        }
+        "rcut join joins the last file it's given, so it doesn't take --join" => {
+            "rcut join verbindet mit der letzten angegebenen Datei und nimmt daher kein --join"
+        }
+        "rcut join needs a file to read and one to join with it" => {
+            "rcut join braucht eine zu lesende Datei und eine, die damit verbunden wird"
+        }
+        "cut characters at the positions in LIST, like `cut -c`" => {
+            "Zeichen an den Positionen in LIST ausschneiden, wie `cut -c`"
+        }
+        "cut bytes at the positions in LIST, like `cut -b`" => {
+            "Bytes an den Positionen in LIST ausschneiden, wie `cut -b`"
+        }
+        "cut fields of RFC 4180 CSV, whose first line names its columns" => {
+            "Felder aus CSV nach RFC 4180 ausschneiden, deren erste Zeile die Spalten benennt"
+        }
+        "cut the values of KEYS out of each line's JSON object" => {
+            "die Werte von KEYS aus dem JSON-Objekt jeder Zeile ausschneiden"
+        }
+        "summarize numeric fields once all the input is read: count, min, max, mean and sum" => {
+            "numerische Felder zusammenfassen, sobald die ganze Eingabe gelesen ist: Anzahl, \
+             Minimum, Maximum, Mittelwert und Summe"
+        }
+        "positions to select, like 1-5,8" => "auszuwählende Positionen, etwa 1-5,8",
+        "fields to select, by column name or number" => {
+            "auszuwählende Felder, nach Spaltenname oder Nummer"
+        }
+        "read the first line as a record like the rest, not as column names" => {
+            "die erste Zeile wie die übrigen als Datensatz lesen, nicht als Spaltennamen"
+        }
+        "keys to select, like user.id,name" => "auszuwählende Schlüssel, etwa user.id,name",
+        "fields to summarize, by number or, with --header, by column name" => {
+            "zusammenzufassende Felder, nach Nummer oder, mit --header, nach Spaltenname"
+        }
+        "rcut {} takes what to select as its first argument, not -f, -b, -c or --order-from" => {
+            "rcut {} nimmt die Auswahl als erstes Argument, nicht per -f, -b, -c oder --order-from"
+        }
+        "--on and --join-fields need --join" => "--on und --join-fields benötigen --join",
+        "summarize each selected field as numbers once all the input is read: count, min, max, mean and sum" => {
+            "jedes gewählte Feld als Zahlen zusammenfassen, sobald die ganze Eingabe gelesen ist: \
+             Anzahl, Minimum, Maximum, Mittelwert und Summe"
+        }
+        "print only the first record for each distinct combination of the selected fields" => {
+            "nur den ersten Datensatz für jede unterschiedliche Kombination der gewählten Felder \
+             ausgeben"
+        }
+        "leave out a record with the same selected fields as the one before it, as uniq does" => {
+            "einen Datensatz mit denselben gewählten Feldern wie der vorige auslassen, wie uniq es \
+             tut"
+        }
+        "print how often each distinct record of the selected fields occurs, as `count fields`, most frequent first" => {
+            "ausgeben, wie oft jeder unterschiedliche Datensatz der gewählten Felder vorkommt, \
+             als `Anzahl Felder`, der häufigste zuerst"
+        }
+        "read each line as logfmt `key=value` pairs, with -f naming keys like level,msg" => {
+            "jede Zeile als logfmt-Paare `key=value` lesen, wobei -f Schlüssel wie level,msg nennt"
+        }
+        "--logfmt selects keys by name, like -f level,msg" => "--logfmt wählt Schlüssel nach Namen aus, etwa -f level,msg",
+        "cut doesn't know logfmt" => "cut kennt kein logfmt",
+        "print the selected fields in aligned columns, as column -t does, once all the input is read" => {
+            "die ausgewählten Felder in ausgerichteten Spalten ausgeben, wie column -t, sobald \
+             die ganze Eingabe gelesen ist"
+        }
+        "compress the output with CODEC, one of gz, zst, bz2 or xz, as -O FILE's extension would" => {
+            "die Ausgabe mit CODEC komprimieren, einem von gz, zst, bz2 oder xz, wie es die \
+             Endung von -O FILE täte"
+        }
+        "unknown compression '{}': expected gz, zst, bz2 or xz" => {
+            "unbekannte Kompression '{}': erwartet wird gz, zst, bz2 oder xz"
+        }
+        "compressing needs {}, which could not be run" => "zum Komprimieren wird {} gebraucht, das nicht ausgeführt werden konnte",
+        "{} could not compress the output ({})" => "{} konnte die Ausgabe nicht komprimieren ({})",
+        "rewrite each input file with just what is cut from it, keeping the old one with SUFFIX added if given" => {
+            "jede Eingabedatei mit dem neu schreiben, was aus ihr geschnitten wird, und die alte \
+             mit angehängtem SUFFIX behalten, falls angegeben"
+        }
+        "-i writes each file out as it's read, which can't be done while holding records back" => {
+            "-i schreibt jede Datei aus, während sie gelesen wird, was nicht geht, solange \
+             Datensätze zurückgehalten werden"
+        }
+        "-i rewrites files, so it needs some to rewrite" => {
+            "-i schreibt Dateien neu und braucht daher welche zum Neuschreiben"
+        }
+        "write the output to FILE, which is only replaced once the whole run has succeeded" => {
+            "die Ausgabe nach FILE schreiben, das erst ersetzt wird, wenn der ganze Lauf \
+             gelungen ist"
+        }
+        "'{}' is a directory, which only -r cuts the files of" => {
+            "'{}' ist ein Verzeichnis, dessen Dateien nur -r schneidet"
+        }
+        "cut every file under any directory given as an input" => {
+            "jede Datei unter den als Eingabe angegebenen Verzeichnissen schneiden"
+        }
+        "with -r, cut only files whose names match GLOB, such as '*.csv'; repeatable" => {
+            "mit -r nur Dateien schneiden, deren Namen auf GLOB passen, etwa '*.csv'; wiederholbar"
+        }
+        "with -r, skip files whose names match GLOB; repeatable" => {
+            "mit -r Dateien überspringen, deren Namen auf GLOB passen; wiederholbar"
+        }
+        "invalid glob '{}': a [ is never closed" => "ungültiges Muster '{}': ein [ wird nie geschlossen",
+        "read the names of more input files from LIST, one per line, or from stdin if LIST is -" => {
+            "die Namen weiterer Eingabedateien aus LIST lesen, einen pro Zeile, oder von der \
+             Standardeingabe, wenn LIST - ist"
+        }
+        "read the names of more input files from LIST, each ended by a NUL, as find -print0 writes them" => {
+            "die Namen weiterer Eingabedateien aus LIST lesen, jeweils mit NUL abgeschlossen, \
+             wie find -print0 sie schreibt"
+        }
+        "start each output line with the number of the line it was cut from, as a field of its own" => {
+            "jede Ausgabezeile mit der Nummer der Zeile beginnen, aus der sie geschnitten wurde, \
+             als eigenes Feld"
+        }
+        "start each output line with the name of the file it came from, as grep -H does" => {
+            "jede Ausgabezeile mit dem Namen der Datei beginnen, aus der sie stammt, wie grep -H"
+        }
+        "don't start output lines with the name of their file, which is the default" => {
+            "Ausgabezeilen nicht mit dem Namen ihrer Datei beginnen (Voreinstellung)"
+        }
+        "write lines that can't be cut, as they were, to FILE instead of failing" => {
+            "Zeilen, die sich nicht schneiden lassen, unverändert nach FILE schreiben, statt \
+             abzubrechen"
+        }
+        "print STRING for each selected field a line doesn't have, rather than leaving it out" => {
+            "STRING für jedes ausgewählte Feld ausgeben, das einer Zeile fehlt, statt es wegzulassen"
+        }
+        "--pad fills in no more than {} fields, which the selector goes past" => {
+            "--pad füllt höchstens {} Felder auf, und der Selektor reicht darüber hinaus"
+        }
+        "fail on a line with fewer fields than the selector names, rather than skip those missing" => {
+            "bei einer Zeile mit weniger Feldern, als der Selektor nennt, abbrechen, statt die \
+             fehlenden zu übergehen"
+        }
+        "the line has only {} of the {} fields the selector needs" => {
+            "die Zeile hat nur {} der {} Felder, die der Selektor braucht"
+        }
+        "drop comment lines, those starting with PREFIX, like '#'" => {
+            "Kommentarzeilen verwerfen, also solche, die mit PREFIX beginnen, etwa '#'"
+        }
+        "print comment lines unchanged instead of dropping them" => {
+            "Kommentarzeilen unverändert ausgeben, statt sie zu verwerfen"
+        }
+        "an empty prefix would make every line a comment" => {
+            "ein leeres Präfix machte jede Zeile zum Kommentar"
+        }
+        "cut the first line as a header, printing it whatever --where, --expr or the like would do" => {
+            "die erste Zeile als Kopfzeile schneiden und ausgeben, egal was --where, --expr und \
+             dergleichen täten"
+        }
+        "ignore the first N lines of each input, as for a banner before the data" => {
+            "die ersten N Zeilen jeder Eingabe übergehen, etwa ein Banner vor den Daten"
+        }
+        "only cut lines where COND holds, as FIELD OP VALUE with ==, !=, <, <=, >, >=, ~ or !~; repeatable" => {
+            "nur Zeilen schneiden, für die COND gilt, als FIELD OP VALUE mit ==, !=, <, <=, >, >=, \
+             ~ oder !~; wiederholbar"
+        }
+        "invalid condition '{}': expected FIELD OP VALUE" => {
+            "ungültige Bedingung '{}': FIELD OP VALUE erwartet"
+        }
+        "make the capture groups of PATTERN the fields, by number or name, leaving out lines it doesn't match" => {
+            "die Gruppen von PATTERN zu den Feldern machen, nach Nummer oder Name, und Zeilen \
+             auslassen, auf die es nicht passt"
+        }
+        "the pattern has no capture groups to make fields of" => {
+            "das Muster hat keine Gruppen, aus denen Felder werden könnten"
+        }
+        "the pattern has no group named '{}'" => "das Muster hat keine Gruppe namens '{}'",
+        "cut can't pick out capture groups" => "cut kann keine Gruppen eines Musters herausgreifen",
+        "change output field N with TRANSFORM, one of upper, lower, trim or squeeze-space; repeatable" => {
+            "Ausgabefeld N mit TRANSFORM ändern, einem von upper, lower, trim oder squeeze-space; \
+             wiederholbar"
+        }
+        "invalid map '{}': expected N:TRANSFORM" => "ungültige Abbildung '{}': N:TRANSFORM erwartet",
+        "unknown transform '{}': expected upper, lower, trim or squeeze-space" => {
+            "unbekannte Umwandlung '{}': upper, lower, trim oder squeeze-space erwartet"
+        }
+        "print each line's fields into TEMPLATE, where {N} is field N, as -f numbers them" => {
+            "die Felder jeder Zeile in TEMPLATE einsetzen, wobei {N} Feld N ist, gezählt wie bei -f"
+        }
+        "a { in the template is never closed" => "eine { in der Vorlage wird nie geschlossen",
+        "fields are numbered from 1" => "Felder werden ab 1 gezählt",
+        "a } in the template has to be doubled, as }}" => {
+            "eine } in der Vorlage muss verdoppelt werden, als }}"
+        }
+        "with --max-fields, split from the end of the line, so the first field holds the rest" => {
+            "mit --max-fields vom Zeilenende her trennen, sodass das erste Feld den Rest enthält"
+        }
+        "split each line into at most N fields, the last of them holding the rest of the line" => {
+            "jede Zeile in höchstens N Felder zerlegen, von denen das letzte den Rest der Zeile enthält"
+        }
+        "a line can't be split into 0 fields" => "eine Zeile kann nicht in 0 Felder zerlegt werden",
+        "cut can't stop splitting after so many fields" => {
+            "cut kann nicht nach einer bestimmten Zahl von Feldern aufhören zu trennen"
+        }
+        "split fields on any one of the characters in CHARS, joining them with the first" => {
+            "Felder an jedem der Zeichen in CHARS trennen und mit dem ersten davon verbinden"
+        }
+        "--any-delim needs at least one character" => "--any-delim braucht mindestens ein Zeichen",
+        "count a run of the delimiter as one, so `a,,b` has two fields, as whitespace does" => {
+            "eine Folge von Trennern als einen zählen, sodass `a,,b` zwei Felder hat, wie bei Leerraum"
+        }
+        "split on every whitespace character, so two spaces in a row have an empty field between them" => {
+            "an jedem Leerraumzeichen trennen, sodass zwischen zwei Leerzeichen ein leeres Feld steht"
+        }
+        "cut each line into fixed-width columns of these widths, in characters, like 10,8,20" => {
+            "jede Zeile in Spalten fester Breite zerlegen, in Zeichen, etwa 10,8,20"
+        }
+        "take the --widths from FILE, one or more to a line" => {
+            "die --widths aus FILE lesen, eine oder mehrere pro Zeile"
+        }
+        "a column can't be 0 characters wide" => "eine Spalte kann nicht 0 Zeichen breit sein",
+        "no column widths given" => "keine Spaltenbreiten angegeben",
+        "cut can't cut columns by width" => "cut kann keine Spalten nach Breite schneiden",
+        "end records with SEP rather than a newline, reading escapes in it as --escapes does" => {
+            "Datensätze mit SEP statt einem Zeilenumbruch beenden, mit Escape-Sequenzen wie bei --escapes"
+        }
+        "the record separator cannot be empty" => "das Datensatztrennzeichen darf nicht leer sein",
+        "cut only ends records with a newline, or NUL with -z" => {
+            "cut beendet Datensätze nur mit einem Zeilenumbruch, oder mit -z mit NUL"
+        }
+        "end records with NUL rather than a newline, on input and output, like `cut -z`" => {
+            "Datensätze bei Ein- und Ausgabe mit NUL statt einem Zeilenumbruch beenden, wie `cut -z`"
+        }
+        "read and write tab-separated fields, like `-d '\\t'` with a literal tab" => {
+            "tabulatorgetrennte Felder lesen und schreiben, wie `-d '\\t'` mit einem echten Tabulator"
+        }
+        "cut can't knock fields out of a selection" => "cut kann keine Felder aus einer Auswahl herausnehmen",
+        "a range can't step by 0" => "ein Bereich kann nicht in Schritten von 0 laufen",
+        "cut can't step through a range" => "cut kann einen Bereich nicht in Schritten durchlaufen",
+        "the line is not UTF-8, which only -b, or -f with a plain -d or whitespace, can cut" => {
+            "die Zeile ist kein UTF-8, und nur -b oder -f mit einfachem -d oder Leerraum können sie schneiden"
+        }
+        "read files through a memory mapping, as files over 64 MiB are anyway" => {
+            "Dateien über eine Speicherabbildung lesen, wie es bei Dateien über 64 MiB ohnehin geschieht"
+        }
+        "decompress any input that starts like gzip, zstd, bzip2 or xz data; .gz, .zst, .bz2 and .xz files always are" => {
+            "jede Eingabe entpacken, die wie gzip-, zstd-, bzip2- oder xz-Daten beginnt; .gz-, .zst-, .bz2- und .xz-Dateien werden immer entpackt"
+        }
+        "decompressing needs {}, which could not be run" => "zum Entpacken wird {} gebraucht, das sich nicht ausführen ließ",
+        "{} could not decompress the input ({})" => "{} konnte die Eingabe nicht entpacken ({})",
+        "write each record as text, as CSV quoted where a field needs it, as JSON: an object keyed by the header or --json keys, or else an array, or as kv: name=value pairs, named f1, f2... without a header" => {
+            "jeden Datensatz als Text, als CSV mit Anführungszeichen, wo ein Feld sie braucht, \
+             als JSON: als Objekt mit dem Kopf oder den --json-Schlüsseln als Schlüssel, sonst \
+             als Array, oder als kv ausgeben: als Paare name=wert, ohne Kopf f1, f2... benannt"
+        }
+        "read each line as a JSON object, with -f naming keys like user.id" => "jede Zeile als JSON-Objekt lesen, wobei -f Schlüssel wie user.id nennt",
+        "--json selects keys by name, like -f user.id,msg" => "--json wählt Schlüssel nach Namen aus, etwa -f user.id,msg",
+        "'{}' is not a key path" => "'{}' ist kein Schlüsselpfad",
+        "the line is not a JSON object" => "die Zeile ist kein JSON-Objekt",
+        "fields to select, by number or, with --header, by column name" => "auszuwählende Felder, nach Nummer oder mit --header nach Spaltenname",
+        "take each input's first line as column names, which -f can select by; only the first input's is printed" => {
+            "die erste Zeile jeder Eingabe als Spaltennamen nehmen, nach denen -f auswählen kann; nur die der ersten Eingabe wird ausgegeben"
+        }
+        "'{}' isn't a field number; give --header to select columns by name" => {
+            "'{}' ist keine Feldnummer; mit --header lassen sich Spalten nach Namen auswählen"
+        }
+        "there is no column named '{}'" => "es gibt keine Spalte namens '{}'",
+        "cut can't select columns by name" => "cut kann Spalten nicht nach Namen auswählen",
+        "cut doesn't know CSV quoting" => "cut kennt keine CSV-Anführungszeichen",
+        "read RFC 4180 CSV, where a quoted field can hold the delimiter, and quote output fields that need it" => {
+            "RFC-4180-CSV lesen, in dem ein Feld in Anführungszeichen den Trenner enthalten kann, und Ausgabefelder bei Bedarf in Anführungszeichen setzen"
+        }
+        "--csv fields are separated by a single character" => "--csv-Felder werden durch ein einzelnes Zeichen getrennt",
+        "cut can't split on a regular expression" => "cut kann nicht an einem regulären Ausdruck trennen",
+        "split fields on anything matching the regular expression PATTERN" => "Felder an allem trennen, was auf den regulären Ausdruck PATTERN passt",
+        "the delimiter pattern cannot match an empty string" => "das Trennermuster darf nicht auf eine leere Zeichenkette passen",
+        "drop lines without the delimiter in them, like `cut -s`" => "Zeilen ohne Trenner verwerfen, wie bei `cut -s`",
+        "print lines without the delimiter in them whole, as cut does, instead of as a single field" => {
+            "Zeilen ohne Trenner wie cut ganz ausgeben, statt als ein einzelnes Feld"
+        }
+        "'{}' is field {} from the end, but cut reads it as fields 1-{}: write ..{} for those, or {}..{} to keep this meaning without a warning" => {
+            "'{}' ist das {}. Feld von hinten, cut liest es aber als Felder 1-{}: ..{} schreiben, um diese zu meinen, oder {}..{}, um diese Bedeutung ohne Warnung zu behalten"
+        }
+        "print the fields (or bytes or characters) not selected, in their original order" => {
+            "die nicht ausgewählten Felder (oder Bytes oder Zeichen) in ihrer ursprünglichen Reihenfolge ausgeben"
+        }
+        "characters to select, like `cut -c`, rather than fields" => "auszuwählende Zeichen statt Felder, wie bei `cut -c`",
+        "cut puts the pieces back together as they were, so drop -o" => "cut setzt die Teile unverändert zusammen, also -o weglassen",
+        "bytes to select, like `cut -b`, rather than fields" => "auszuwählende Bytes statt Felder, wie bei `cut -b`",
+        "read numbers for --expr, --where, --agg, --pivot and --sort-by as LOCALE writes them, like 1.234,56 for de" => {
+            "Zahlen für --expr, --where, --agg, --pivot und --sort-by so lesen, wie LOCALE sie schreibt, etwa 1.234,56 für de"
+        }
+        "export metrics every 10s to a Prometheus text file (prometheus:PATH) or to statsd (statsd:HOST:PORT)" => {
+            "alle 10 s Metriken in eine Prometheus-Textdatei (prometheus:PATH) oder an statsd (statsd:HOST:PORT) exportieren"
+        }
+        "invalid metrics sink '{}': expected prometheus:PATH or statsd:ADDR" => {
+            "ungültiges Metrikziel '{}': erwartet wird prometheus:PATH oder statsd:ADDR"
+        }
+        "stop after DURATION (60s, 5m), flushing what has been cut so far and exiting with status 124" => {
+            "nach DURATION (60s, 5m) anhalten, das bisher Ausgeschnittene ausgeben und mit Status 124 beenden"
+        }
+        "invalid duration '{}'" => "ungültige Dauer '{}'",
+        "timed out" => "Zeitlimit überschritten",
+        "fail rather than let buffered records (for sorting, grouping, dedup and the like) or a single line take more than SIZE bytes, like 512M" => {
+            "abbrechen, statt gepufferte Datensätze (zum Sortieren, Gruppieren, Deduplizieren usw.) oder eine einzelne Zeile mehr als SIZE Bytes belegen zu lassen, etwa 512M"
+        }
+        "invalid size '{}'" => "ungültige Größe '{}'",
+        "{} took more than the --max-memory limit of {} bytes" => "{}: Grenze von --max-memory ({} Bytes) überschritten",
+        "buffered records" => "gepufferte Datensätze",
+        "a single line" => "eine einzelne Zeile",
+        "write a JSON record of the run (inputs, job, record counts) to FILE" => "einen JSON-Bericht über den Lauf (Eingaben, Auftrag, Datensatzzahlen) nach FILE schreiben",
+        "limit output to RATE per second, in bytes (10MB/s) or records (1000/s)" => {
+            "die Ausgabe auf RATE pro Sekunde begrenzen, in Bytes (10MB/s) oder Datensätzen (1000/s)"
+        }
+        "invalid rate '{}'" => "ungültige Rate '{}'",
+        "every few seconds, save how far through the input files the job has got to FILE" => {
+            "alle paar Sekunden in FILE festhalten, wie weit die Eingabedateien verarbeitet sind"
+        }
+        "carry on from where the --checkpoint file says an earlier run got to" => {
+            "dort weitermachen, wo ein früherer Lauf laut --checkpoint-Datei aufgehört hat"
+        }
+        "--checkpoint needs input files, since stdin can't be resumed" => {
+            "--checkpoint benötigt Eingabedateien, da stdin nicht fortgesetzt werden kann"
+        }
+        "the checkpoint is for '{}', which is not input {} this time" => {
+            "der Checkpoint gilt für '{}', das diesmal nicht Eingabe {} ist"
+        }
+        "'{}' is not an rcut checkpoint" => "'{}' ist kein rcut-Checkpoint",
+        "take each input's first line as a header and line its columns up with the first input's" => {
+            "die erste Zeile jeder Eingabe als Kopfzeile lesen und ihre Spalten nach der ersten Eingabe ausrichten"
+        }
+        "select the fields listed in FILE, one index, range or, with --header, column name per line, in that order" => {
+            "die in FILE aufgeführten Felder auswählen, ein Index, Bereich oder, mit --header, \
+             Spaltenname pro Zeile, in dieser Reihenfolge"
+        }
+        "no fields to order by" => "keine Felder für die Reihenfolge",
+        "also copy the input, unchanged, to FILE" => "die Eingabe zusätzlich unverändert nach FILE kopieren",
+        "find fixed-width columns from how the first lines of input line up" => {
+            "Spalten fester Breite daraus ableiten, wie die ersten Eingabezeilen ausgerichtet sind"
+        }
+        "separator used when printing fields" => "Trennzeichen für die Ausgabe der Felder",
+        "read \\t, \\n, \\0, \\\\ and \\xNN in -d and -o as the characters they stand for" => {
+            "\\t, \\n, \\0, \\\\ und \\xNN in -d und -o als die Zeichen lesen, für die sie stehen"
+        }
+        "\\x{} isn't the hex code of an ASCII character" => "\\x{} ist nicht der Hex-Code eines ASCII-Zeichens",
+        "unknown escape \\{}" => "unbekannte Escape-Sequenz \\{}",
+        "a trailing \\ escapes nothing" => "ein \\ am Ende maskiert nichts",
+        "print every line unchanged, with the selected fields appended or prepended" => {
+            "jede Zeile unverändert ausgeben, die ausgewählten Felder angehängt oder vorangestellt"
+        }
+        "cut the output again, as given by STAGE: `[-d DELIM] [-o SEP] [-f] FIELDS`; repeatable" => {
+            "die Ausgabe erneut ausschneiden, wie in STAGE angegeben: \
+             `[-d DELIM] [-o SEP] [-f] FIELDS`; wiederholbar"
+        }
+        "invalid stage '{}'" => "ungültige Stufe '{}'",
+        "split field N of the selection on DELIM into named columns, as N:DELIM:NAME,..." => {
+            "Feld N der Auswahl an DELIM in benannte Spalten aufteilen, als N:DELIM:NAME,..."
+        }
+        "invalid explode '{}'" => "ungültige Aufteilung '{}'",
+        "append the fields of matching lines in FILE, keyed as given by --on" => {
+            "Felder passender Zeilen aus FILE anhängen, verknüpft über --on"
+        }
+        "key fields for --join, as INPUT=FILE or a single field for both [default: 1]" => {
+            "Schlüsselfelder für --join, als INPUT=FILE oder ein Feld für beide [Standard: 1]"
+        }
+        "fields of FILE to append for --join [default: all but the key]" => {
+            "anzuhängende Felder aus FILE für --join [Standard: alle außer dem Schlüssel]"
+        }
+        "invalid join key '{}'" => "ungültiger Verknüpfungsschlüssel '{}'",
+        "swap rows and columns of the selected fields" => {
+            "Zeilen und Spalten der ausgewählten Felder vertauschen"
+        }
+        "print only one record for each distinct value of these output fields" => {
+            "nur einen Datensatz je Wert dieser Ausgabefelder ausgeben"
+        }
+        "which record --dedup-key keeps" => "welchen Datensatz --dedup-key behält",
+        "print the output ordered by column N, as N[:numeric][:desc]" => {
+            "die Ausgabe nach Spalte N geordnet ausgeben, als N[:numeric][:desc]"
+        }
+        "invalid sort key '{}'" => "ungültiger Sortierschlüssel '{}'",
+        "print one row per distinct value of these fields, summarized by --agg" => {
+            "eine Zeile je Wert dieser Felder ausgeben, zusammengefasst durch --agg"
+        }
+        "summaries for --group-by: count, sum(N), min(N), max(N), mean(N) [default: count]" => {
+            "Zusammenfassungen für --group-by: count, sum(N), min(N), max(N), mean(N) \
+             [Standard: count]"
+        }
+        "invalid aggregate '{}'" => "ungültige Zusammenfassung '{}'",
+        "cross-tabulate two fields, as rows=N,cols=M[,values=AGG] [default values: count]" => {
+            "Kreuztabelle zweier Felder, als rows=N,cols=M[,values=AGG] [Standard für values: count]"
+        }
+        "invalid pivot '{}'" => "ungültige Kreuztabelle '{}'",
+        "read all inputs in lockstep and print their selected fields side by side, like paste; without -f, every positional is a file" => {
+            "alle Eingaben im Gleichschritt lesen und ihre Felder nebeneinander ausgeben, wie paste; \
+             ohne -f ist jedes Positionsargument eine Datei"
+        }
+        "read one line from each input in turn, padding inputs that run out; without -f, every positional is a file" => {
+            "abwechselnd eine Zeile aus jeder Eingabe lesen, erschöpfte Eingaben auffüllen; ohne -f \
+             ist jedes Positionsargument eine Datei"
+        }
+        "fields to select" => "auszuwählende Felder",
+        "print per line instead of selecting: `[print] EXPR, ... [if COND]` over $N, NF and NR" => {
+            "je Zeile ausgeben statt auszuwählen: `[print] EXPR, ... [if COND]` über $N, NF und NR"
+        }
+        "unexpected '{}' in expression" => "unerwartetes '{}' im Ausdruck",
+        "unexpected end of expression" => "unerwartetes Ende des Ausdrucks",
+        "expression is nested too deeply" => "Ausdruck ist zu tief verschachtelt",
+        "expression is too long" => "Ausdruck ist zu lang",
+        "unterminated string in expression" => "nicht abgeschlossene Zeichenkette im Ausdruck",
+        "division by zero" => "Division durch Null",
+        "file(s) to process, - meaning stdin, or field selectors" => {
+            "zu verarbeitende Datei(en), - für die Standardeingabe, oder Feldauswahl"
+        }
+        "file(s) to process, even if they look like field selectors" => {
+            "zu verarbeitende Datei(en), auch wenn sie wie eine Feldauswahl aussehen"
+        }
+        "print the resolved job and its inputs without processing data" => {
+            "aufgelösten Auftrag und Eingaben anzeigen, ohne Daten zu verarbeiten"
+        }
+        "log progress to stderr; repeat for more detail (RUST_LOG overrides)" => {
+            "Fortschritt auf stderr protokollieren; für mehr Details wiederholen (RUST_LOG hat Vorrang)"
+        }
+        "log debugging detail to stderr, same as -vv" => {
+            "Debug-Details auf stderr protokollieren, wie -vv"
+        }
+        "how to report warnings and errors on stderr" => {
+            "Format für Warnungen und Fehler auf stderr"
+        }
+        "also run the system cut on the same input and fail if its output differs" => {
+            "zusätzlich das System-cut mit derselben Eingabe ausführen und bei Abweichung scheitern"
+        }
+        "these options are not cut-compatible" => "diese Optionen sind nicht cut-kompatibel",
+        "cut only splits on a single character" => "cut trennt nur an einem einzelnen Zeichen",
+        "cut splits on tabs unless told otherwise, so give -d" => {
+            "cut trennt ohne Angabe an Tabulatoren, daher -d angeben"
+        }
+        "cut joins fields with the delimiter, so -o must match it" => {
+            "cut verbindet Felder mit dem Feldtrenner, daher muss -o ihm entsprechen"
+        }
+        "cut can't count fields from the end" => "cut kann Felder nicht vom Ende her zählen",
+        "cut prints fields in input order, so the selector must be ascending" => {
+            "cut gibt Felder in Eingabereihenfolge aus, daher muss die Auswahl aufsteigend sein"
+        }
+        "<no line>" => "<keine Zeile>",
+        "rcut and cut disagree on {} of {} lines; the first is line {}:\n  rcut: {}\n  cut:  {}" => {
+            "rcut und cut weichen in {} von {} Zeilen ab; die erste ist Zeile {}:\n  rcut: {}\n  cut:  {}"
+        }
+        "could not run cut" => "cut konnte nicht ausgeführt werden",
+        "cut failed: {}" => "cut ist fehlgeschlagen: {}",
+        "Print help information" => "Hilfe anzeigen",
+        "Print this message or the help of the given subcommand(s)" => {
+            "Diese Hilfe oder die Hilfe der angegebenen Unterbefehle anzeigen"
+        }
+        "cut delimited fields, the default when no subcommand is given" => {
+            "getrennte Felder ausschneiden, Standard ohne Unterbefehl"
+        }
+        "Print version information" => "Versionsinformationen anzeigen",
+        "choose columns interactively from the start of FILE and print the rcut command for them" => {
+            "Spalten am Anfang von FILE interaktiv auswählen und den passenden rcut-Befehl ausgeben"
+        }
+        "field delimiter to start with" => "anfänglicher Feldtrenner",
+        "run the chosen command instead of printing it" => {
+            "den gewählten Befehl ausführen statt ihn auszugeben"
+        }
+        "file to preview" => "anzuzeigende Datei",
+        "show a live dashboard of the most common values of some fields, and their rates" => {
+            "eine Live-Übersicht der häufigsten Werte einiger Felder und ihrer Raten anzeigen"
+        }
+        "fields to count" => "zu zählende Felder",
+        "keep reading as FILE grows, like `tail -f`" => {
+            "weiterlesen, während FILE wächst, wie `tail -f`"
+        }
+        "file to read [default: stdin]" => "zu lesende Datei [Standard: stdin]",
+        "rcut top needs a terminal" => "rcut top benötigt ein Terminal",
+        "--follow needs a file to follow" => "--follow benötigt eine Datei",
+        "print a random sample of the selected records" => {
+            "eine Zufallsstichprobe der ausgewählten Datensätze ausgeben"
+        }
+        "how many records to keep, or how many of each --stratify value" => {
+            "wie viele Datensätze behalten werden, oder wie viele je Wert von --stratify"
+        }
+        "seed every random choice, as made by `rcut sample`, for a reproducible run" => {
+            "Startwert für jede Zufallsauswahl, etwa von `rcut sample`, für reproduzierbare Läufe"
+        }
+        "sample each value of output field N separately" => {
+            "für jeden Wert des Ausgabefelds N eine eigene Stichprobe ziehen"
+        }
+        "SAMPLING" => "STICHPROBE",
+        "finished" => "fertig",
+        "reading" => "liest",
+        "interrupted" => "unterbrochen",
+        "offset" => "Position",
+        "records written" => "geschriebene Datensätze",
+        "lines" => "Zeilen",
+        "q to quit" => "q zum Beenden",
+        "count" => "Anzahl",
+        "value" => "Wert",
+        "could not draw the screen" => "Bildschirm konnte nicht gezeichnet werden",
+        "cut the body of each HTTP POST as given by --spec and send back the result" => {
+            "den Rumpf jedes HTTP-POST wie in --spec angegeben ausschneiden und zurücksenden"
+        }
+        "address to listen on" => "Adresse, auf der gelauscht wird",
+        "rcut options to cut each request with, quoted as for a shell" => {
+            "rcut-Optionen für jede Anfrage, wie für eine Shell quotiert"
+        }
+        "log requests to stderr; repeat for more detail (RUST_LOG overrides)" => {
+            "Anfragen auf stderr protokollieren; für mehr Details wiederholen (RUST_LOG hat Vorrang)"
+        }
+        "unterminated quote in '{}'" => "nicht abgeschlossenes Anführungszeichen in '{}'",
+        "a serve spec can only say how to cut, not what to read or where to write" => {
+            "eine serve-Spezifikation legt nur fest, wie ausgeschnitten wird, \
+             nicht was gelesen oder wohin geschrieben wird"
+        }
+        "rcut pick needs a terminal" => "rcut pick benötigt ein Terminal",
+        "select at least one column first" => "zuerst mindestens eine Spalte auswählen",
+        "new delimiter (empty for whitespace):" => "neuer Feldtrenner (leer für Leerraum):",
+        "←/→ move  space toggle  a all/none  d delimiter  enter accept  q quit" => {
+            "←/→ bewegen  Leertaste umschalten  a alle/keine  d Trenner  Enter übernehmen  q beenden"
+        }
+        "'{}' is both a field selector and an existing file; treating it as a selector (use `-- {}` to read it as a file)" => {
+            "'{}' ist sowohl eine Feldauswahl als auch eine vorhandene Datei; \
+             wird als Feldauswahl behandelt (`-- {}` verwenden, um sie als Datei zu lesen)"
+        }
+        "invalid field selector '{}'" => "ungültige Feldauswahl '{}'",
+        "expected n, last, n-N or last-N" => "n, last, n-N oder last-N erwartet",
+        "warning" => "Warnung",
+        "Error" => "Fehler",
+        "the delimiter cannot be empty" => "der Feldtrenner darf nicht leer sein",
+        "SELECTION" => "AUSWAHL",
+        "INPUT" => "EINGABE",
+        "OUTPUT" => "AUSGABE",
+        "DIAGNOSTICS" => "DIAGNOSE",
+        crate::cli::EXAMPLES => {
+            "BEISPIELE:
+    ps aux | rcut 2 11               PID- und Befehlsspalte ausgeben
+    rcut -d : -f 1,7 /etc/passwd     jeden Benutzer und seine Shell ausgeben
+    rcut -d , -o ' | ' -f 1-3 a.csv  die ersten drei Spalten neu verbunden ausgeben
+    rcut -f -1 log.txt               das letzte Feld jeder Zeile ausgeben
+    rcut 2 -- 1-5                    die Datei namens 1-5 lesen, keine Feldauswahl"
+        }
+        _ => return None,
+    })
+}
+
+fn french(msg: &str) -> Option<&'static str> {
+    Some(match msg {
+        "cut-like tool with smoother aesthetics" => {
+            "outil de type cut à l'ergonomie plus agréable"
+        }
+        "field delimiter" => "délimiteur de champs",
+        "replace output field N with the PIECES of it between each DELIM, as N:DELIM:PIECES, like 3:@:2; repeatable" => {
+            "remplacer le champ de sortie N par ses morceaux PIECES entre chaque DELIM, sous la forme \
+             N:DELIM:PIECES, comme 3:@:2 ; répétable"
+        }
+        "invalid subfield '{}': expected N:DELIM:PIECES" => "sous-champ « {} » invalide : N:DELIM:PIECES attendu",
+        "join the lines of each file but the last with the lines of the last that share a key, given by --on" => {
+            "joindre les lignes de chaque fichier sauf le dernier aux lignes du dernier qui \
+             partagent une clé, donnée par --on"
+        }
+        "rcut join joins the last file it's given, so it doesn't take --join" => {
+            "rcut join joint le dernier fichier donné, il ne prend donc pas --join"
+        }
+        "rcut join needs a file to read and one to join with it" => {
+            "rcut join a besoin d'un fichier à lire et d'un autre à y joindre"
+        }
+        "cut characters at the positions in LIST, like `cut -c`" => {
+            "extraire les caractères aux positions de LIST, comme `cut -c`"
+        }
+        "cut bytes at the positions in LIST, like `cut -b`" => {
+            "extraire les octets aux positions de LIST, comme `cut -b`"
+        }
+        "cut fields of RFC 4180 CSV, whose first line names its columns" => {
+            "extraire des champs d'un CSV RFC 4180, dont la première ligne nomme les colonnes"
+        }
+        "cut the values of KEYS out of each line's JSON object" => {
+            "extraire les valeurs de KEYS de l'objet JSON de chaque ligne"
+        }
+        "summarize numeric fields once all the input is read: count, min, max, mean and sum" => {
+            "résumer des champs numériques une fois toute l'entrée lue : nombre, minimum, \
+             maximum, moyenne et somme"
+        }
+        "positions to select, like 1-5,8" => "positions à sélectionner, comme 1-5,8",
+        "fields to select, by column name or number" => {
+            "champs à sélectionner, par nom de colonne ou par numéro"
+        }
+        "read the first line as a record like the rest, not as column names" => {
+            "lire la première ligne comme un enregistrement, et non comme des noms de colonnes"
+        }
+        "keys to select, like user.id,name" => "clés à sélectionner, comme user.id,name",
+        "fields to summarize, by number or, with --header, by column name" => {
+            "champs à résumer, par numéro ou, avec --header, par nom de colonne"
+        }
+        "rcut {} takes what to select as its first argument, not -f, -b, -c or --order-from" => {
+            "rcut {} prend la sélection en premier argument, pas avec -f, -b, -c ou --order-from"
+        }
+        "--on and --join-fields need --join" => "--on et --join-fields nécessitent --join",
+        "summarize each selected field as numbers once all the input is read: count, min, max, mean and sum" => {
+            "résumer chaque champ choisi comme des nombres une fois toute l'entrée lue : nombre, \
+             minimum, maximum, moyenne et somme"
+        }
+        "print only the first record for each distinct combination of the selected fields" => {
+            "n'afficher que le premier enregistrement de chaque combinaison distincte des champs \
+             choisis"
+        }
+        "leave out a record with the same selected fields as the one before it, as uniq does" => {
+            "omettre un enregistrement dont les champs choisis sont ceux du précédent, comme le \
+             fait uniq"
+        }
+        "print how often each distinct record of the selected fields occurs, as `count fields`, most frequent first" => {
+            "afficher combien de fois chaque enregistrement distinct des champs choisis apparaît, \
+             sous la forme `nombre champs`, le plus fréquent d'abord"
+        }
+        "read each line as logfmt `key=value` pairs, with -f naming keys like level,msg" => {
+            "lire chaque ligne comme des paires logfmt `key=value`, -f nommant des clés comme level,msg"
+        }
+        "--logfmt selects keys by name, like -f level,msg" => "--logfmt sélectionne des clés par nom, comme -f level,msg",
+        "cut doesn't know logfmt" => "cut ne connaît pas logfmt",
+        "print the selected fields in aligned columns, as column -t does, once all the input is read" => {
+            "afficher les champs sélectionnés en colonnes alignées, comme column -t, une fois \
+             toute l'entrée lue"
+        }
+        "compress the output with CODEC, one of gz, zst, bz2 or xz, as -O FILE's extension would" => {
+            "compresser la sortie avec CODEC, parmi gz, zst, bz2 ou xz, comme le ferait \
+             l'extension de -O FILE"
+        }
+        "unknown compression '{}': expected gz, zst, bz2 or xz" => {
+            "compression inconnue '{}' : gz, zst, bz2 ou xz attendu"
+        }
+        "compressing needs {}, which could not be run" => "la compression nécessite {}, qui n'a pas pu être lancé",
+        "{} could not compress the output ({})" => "{} n'a pas pu compresser la sortie ({})",
+        "rewrite each input file with just what is cut from it, keeping the old one with SUFFIX added if given" => {
+            "réécrire chaque fichier d'entrée avec ce qui en est découpé, en gardant l'ancien \
+             avec SUFFIX ajouté s'il est donné"
+        }
+        "-i writes each file out as it's read, which can't be done while holding records back" => {
+            "-i écrit chaque fichier au fur et à mesure de sa lecture, ce qui est impossible \
+             tant que des enregistrements sont retenus"
+        }
+        "-i rewrites files, so it needs some to rewrite" => {
+            "-i réécrit des fichiers, il lui en faut donc à réécrire"
+        }
+        "write the output to FILE, which is only replaced once the whole run has succeeded" => {
+            "écrire la sortie dans FILE, qui n'est remplacé qu'une fois toute l'exécution réussie"
+        }
+        "'{}' is a directory, which only -r cuts the files of" => {
+            "'{}' est un répertoire, dont seul -r découpe les fichiers"
+        }
+        "cut every file under any directory given as an input" => {
+            "découper chaque fichier sous les répertoires donnés en entrée"
+        }
+        "with -r, cut only files whose names match GLOB, such as '*.csv'; repeatable" => {
+            "avec -r, ne découper que les fichiers dont le nom correspond à GLOB, comme '*.csv' ; \
+             répétable"
+        }
+        "with -r, skip files whose names match GLOB; repeatable" => {
+            "avec -r, ignorer les fichiers dont le nom correspond à GLOB ; répétable"
+        }
+        "invalid glob '{}': a [ is never closed" => "motif invalide '{}' : un [ n'est jamais fermé",
+        "read the names of more input files from LIST, one per line, or from stdin if LIST is -" => {
+            "lire les noms d'autres fichiers d'entrée dans LIST, un par ligne, ou sur l'entrée \
+             standard si LIST vaut -"
+        }
+        "read the names of more input files from LIST, each ended by a NUL, as find -print0 writes them" => {
+            "lire les noms d'autres fichiers d'entrée dans LIST, chacun terminé par un NUL, \
+             comme les écrit find -print0"
+        }
+        "start each output line with the number of the line it was cut from, as a field of its own" => {
+            "commencer chaque ligne de sortie par le numéro de la ligne dont elle est extraite, \
+             comme un champ à part"
+        }
+        "start each output line with the name of the file it came from, as grep -H does" => {
+            "commencer chaque ligne de sortie par le nom du fichier dont elle vient, comme grep -H"
+        }
+        "don't start output lines with the name of their file, which is the default" => {
+            "ne pas commencer les lignes de sortie par le nom de leur fichier (par défaut)"
+        }
+        "write lines that can't be cut, as they were, to FILE instead of failing" => {
+            "écrire les lignes impossibles à découper, telles quelles, dans FILE au lieu \
+             d'échouer"
+        }
+        "print STRING for each selected field a line doesn't have, rather than leaving it out" => {
+            "afficher STRING pour chaque champ sélectionné qui manque à une ligne, au lieu de \
+             l'omettre"
+        }
+        "--pad fills in no more than {} fields, which the selector goes past" => {
+            "--pad ne complète pas plus de {} champs, et le sélecteur va au-delà"
+        }
+        "fail on a line with fewer fields than the selector names, rather than skip those missing" => {
+            "échouer sur une ligne qui a moins de champs que n'en nomme le sélecteur, au lieu \
+             d'ignorer ceux qui manquent"
+        }
+        "the line has only {} of the {} fields the selector needs" => {
+            "la ligne n'a que {} des {} champs dont le sélecteur a besoin"
+        }
+        "drop comment lines, those starting with PREFIX, like '#'" => {
+            "écarter les lignes de commentaire, celles qui commencent par PREFIX, comme '#'"
+        }
+        "print comment lines unchanged instead of dropping them" => {
+            "afficher les lignes de commentaire telles quelles au lieu de les écarter"
+        }
+        "an empty prefix would make every line a comment" => {
+            "un préfixe vide ferait de chaque ligne un commentaire"
+        }
+        "cut the first line as a header, printing it whatever --where, --expr or the like would do" => {
+            "découper la première ligne comme en-tête et l'afficher quoi que fassent --where, \
+             --expr et consorts"
+        }
+        "ignore the first N lines of each input, as for a banner before the data" => {
+            "ignorer les N premières lignes de chaque entrée, comme une bannière avant les données"
+        }
+        "only cut lines where COND holds, as FIELD OP VALUE with ==, !=, <, <=, >, >=, ~ or !~; repeatable" => {
+            "ne découper que les lignes où COND est vraie, sous la forme FIELD OP VALUE avec ==, \
+             !=, <, <=, >, >=, ~ ou !~ ; répétable"
+        }
+        "invalid condition '{}': expected FIELD OP VALUE" => {
+            "condition « {} » invalide : FIELD OP VALUE attendu"
+        }
+        "make the capture groups of PATTERN the fields, by number or name, leaving out lines it doesn't match" => {
+            "faire des groupes de PATTERN les champs, par numéro ou par nom, en omettant les lignes \
+             qu'il ne reconnaît pas"
+        }
+        "the pattern has no capture groups to make fields of" => {
+            "le motif n'a aucun groupe dont faire des champs"
+        }
+        "the pattern has no group named '{}'" => "le motif n'a aucun groupe nommé « {} »",
+        "cut can't pick out capture groups" => "cut ne sait pas extraire les groupes d'un motif",
+        "change output field N with TRANSFORM, one of upper, lower, trim or squeeze-space; repeatable" => {
+            "modifier le champ de sortie N avec TRANSFORM, parmi upper, lower, trim ou \
+             squeeze-space ; répétable"
+        }
+        "invalid map '{}': expected N:TRANSFORM" => "transformation « {} » invalide : N:TRANSFORM attendu",
+        "unknown transform '{}': expected upper, lower, trim or squeeze-space" => {
+            "transformation « {} » inconnue : upper, lower, trim ou squeeze-space attendu"
+        }
+        "print each line's fields into TEMPLATE, where {N} is field N, as -f numbers them" => {
+            "insérer les champs de chaque ligne dans TEMPLATE, où {N} est le champ N, numéroté \
+             comme pour -f"
+        }
+        "a { in the template is never closed" => "une { du modèle n'est jamais fermée",
+        "fields are numbered from 1" => "les champs sont numérotés à partir de 1",
+        "a } in the template has to be doubled, as }}" => {
+            "une } du modèle doit être doublée, en }}"
+        }
+        "with --max-fields, split from the end of the line, so the first field holds the rest" => {
+            "avec --max-fields, découper depuis la fin de la ligne, le premier champ contenant le reste"
+        }
+        "split each line into at most N fields, the last of them holding the rest of the line" => {
+            "découper chaque ligne en au plus N champs, le dernier contenant le reste de la ligne"
+        }
+        "a line can't be split into 0 fields" => "une ligne ne peut pas être découpée en 0 champ",
+        "cut can't stop splitting after so many fields" => {
+            "cut ne sait pas cesser de découper après un certain nombre de champs"
+        }
+        "split fields on any one of the characters in CHARS, joining them with the first" => {
+            "séparer les champs sur chacun des caractères de CHARS, en les joignant avec le premier"
+        }
+        "--any-delim needs at least one character" => "--any-delim demande au moins un caractère",
+        "count a run of the delimiter as one, so `a,,b` has two fields, as whitespace does" => {
+            "compter une suite de délimiteurs comme un seul, pour que `a,,b` ait deux champs, comme pour les blancs"
+        }
+        "split on every whitespace character, so two spaces in a row have an empty field between them" => {
+            "séparer à chaque caractère blanc, pour que deux espaces de suite encadrent un champ vide"
+        }
+        "cut each line into fixed-width columns of these widths, in characters, like 10,8,20" => {
+            "découper chaque ligne en colonnes de largeur fixe, en caractères, comme 10,8,20"
+        }
+        "take the --widths from FILE, one or more to a line" => {
+            "lire les --widths dans FILE, une ou plusieurs par ligne"
+        }
+        "a column can't be 0 characters wide" => "une colonne ne peut pas faire 0 caractère de large",
+        "no column widths given" => "aucune largeur de colonne donnée",
+        "cut can't cut columns by width" => "cut ne sait pas découper des colonnes par largeur",
+        "end records with SEP rather than a newline, reading escapes in it as --escapes does" => {
+            "terminer les enregistrements par SEP plutôt que par un saut de ligne, en y lisant les séquences d'échappement comme --escapes"
+        }
+        "the record separator cannot be empty" => "le séparateur d'enregistrements ne peut pas être vide",
+        "cut only ends records with a newline, or NUL with -z" => {
+            "cut ne termine les enregistrements que par un saut de ligne, ou par NUL avec -z"
+        }
+        "end records with NUL rather than a newline, on input and output, like `cut -z`" => {
+            "terminer les enregistrements par NUL plutôt que par un saut de ligne, en entrée comme en sortie, comme `cut -z`"
+        }
+        "read and write tab-separated fields, like `-d '\\t'` with a literal tab" => {
+            "lire et écrire des champs séparés par des tabulations, comme `-d '\\t'` avec une vraie tabulation"
+        }
+        "cut can't knock fields out of a selection" => "cut ne sait pas retirer des champs d'une sélection",
+        "a range can't step by 0" => "une plage ne peut pas avancer par pas de 0",
+        "cut can't step through a range" => "cut ne sait pas parcourir une plage par pas",
+        "the line is not UTF-8, which only -b, or -f with a plain -d or whitespace, can cut" => {
+            "la ligne n'est pas en UTF-8, et seuls -b ou -f avec un simple -d ou des blancs peuvent la découper"
+        }
+        "read files through a memory mapping, as files over 64 MiB are anyway" => {
+            "lire les fichiers par projection en mémoire, comme c'est déjà le cas au-delà de 64 Mio"
+        }
+        "decompress any input that starts like gzip, zstd, bzip2 or xz data; .gz, .zst, .bz2 and .xz files always are" => {
+            "décompresser toute entrée qui commence comme des données gzip, zstd, bzip2 ou xz ; les fichiers .gz, .zst, .bz2 et .xz le sont toujours"
+        }
+        "decompressing needs {}, which could not be run" => "la décompression nécessite {}, qui n'a pas pu être lancé",
+        "{} could not decompress the input ({})" => "{} n'a pas pu décompresser l'entrée ({})",
+        "write each record as text, as CSV quoted where a field needs it, as JSON: an object keyed by the header or --json keys, or else an array, or as kv: name=value pairs, named f1, f2... without a header" => {
+            "écrire chaque enregistrement en texte, en CSV entre guillemets là où un champ en a \
+             besoin, en JSON : un objet dont les clés sont l'en-tête ou les clés --json, sinon \
+             un tableau, ou en kv : des paires nom=valeur, nommées f1, f2... sans en-tête"
+        }
+        "read each line as a JSON object, with -f naming keys like user.id" => "lire chaque ligne comme un objet JSON, -f nommant des clés comme user.id",
+        "--json selects keys by name, like -f user.id,msg" => "--json sélectionne des clés par nom, comme -f user.id,msg",
+        "'{}' is not a key path" => "'{}' n'est pas un chemin de clés",
+        "the line is not a JSON object" => "la ligne n'est pas un objet JSON",
+        "fields to select, by number or, with --header, by column name" => "champs à sélectionner, par numéro ou, avec --header, par nom de colonne",
+        "take each input's first line as column names, which -f can select by; only the first input's is printed" => {
+            "prendre la première ligne de chaque entrée comme noms de colonnes, utilisables par -f ; seule celle de la première entrée est affichée"
+        }
+        "'{}' isn't a field number; give --header to select columns by name" => {
+            "'{}' n'est pas un numéro de champ ; ajoutez --header pour sélectionner des colonnes par nom"
+        }
+        "there is no column named '{}'" => "il n'y a pas de colonne nommée '{}'",
+        "cut can't select columns by name" => "cut ne sait pas sélectionner des colonnes par nom",
+        "cut doesn't know CSV quoting" => "cut ne connaît pas les guillemets CSV",
+        "read RFC 4180 CSV, where a quoted field can hold the delimiter, and quote output fields that need it" => {
+            "lire du CSV RFC 4180, où un champ entre guillemets peut contenir le délimiteur, et mettre entre guillemets les champs de sortie qui en ont besoin"
+        }
+        "--csv fields are separated by a single character" => "les champs --csv sont séparés par un seul caractère",
+        "cut can't split on a regular expression" => "cut ne sait pas séparer sur une expression régulière",
+        "split fields on anything matching the regular expression PATTERN" => "séparer les champs sur tout ce qui correspond à l'expression régulière PATTERN",
+        "the delimiter pattern cannot match an empty string" => "le motif de délimiteur ne peut pas correspondre à une chaîne vide",
+        "drop lines without the delimiter in them, like `cut -s`" => "ignorer les lignes sans délimiteur, comme avec `cut -s`",
+        "print lines without the delimiter in them whole, as cut does, instead of as a single field" => {
+            "afficher entières les lignes sans délimiteur, comme cut, plutôt que comme un seul champ"
+        }
+        "'{}' is field {} from the end, but cut reads it as fields 1-{}: write ..{} for those, or {}..{} to keep this meaning without a warning" => {
+            "'{}' est le champ {} en partant de la fin, mais cut le lit comme les champs 1-{} : écrivez ..{} pour ceux-ci, ou {}..{} pour garder ce sens sans avertissement"
+        }
+        "print the fields (or bytes or characters) not selected, in their original order" => {
+            "afficher les champs (ou octets ou caractères) non sélectionnés, dans leur ordre d'origine"
+        }
+        "characters to select, like `cut -c`, rather than fields" => "caractères à sélectionner, comme avec `cut -c`, plutôt que des champs",
+        "cut puts the pieces back together as they were, so drop -o" => "cut recolle les morceaux tels quels, donc retirez -o",
+        "bytes to select, like `cut -b`, rather than fields" => "octets à sélectionner, comme avec `cut -b`, plutôt que des champs",
+        "read numbers for --expr, --where, --agg, --pivot and --sort-by as LOCALE writes them, like 1.234,56 for de" => {
+            "lire les nombres pour --expr, --where, --agg, --pivot et --sort-by comme LOCALE les écrit, par exemple 1.234,56 pour de"
+        }
+        "export metrics every 10s to a Prometheus text file (prometheus:PATH) or to statsd (statsd:HOST:PORT)" => {
+            "exporter les métriques toutes les 10 s vers un fichier texte Prometheus (prometheus:PATH) ou vers statsd (statsd:HOST:PORT)"
+        }
+        "invalid metrics sink '{}': expected prometheus:PATH or statsd:ADDR" => {
+            "destination de métriques invalide '{}' : prometheus:PATH ou statsd:ADDR attendu"
+        }
+        "stop after DURATION (60s, 5m), flushing what has been cut so far and exiting with status 124" => {
+            "s'arrêter après DURATION (60s, 5m), en écrivant ce qui a déjà été découpé, et sortir avec le code 124"
+        }
+        "invalid duration '{}'" => "durée invalide '{}'",
+        "timed out" => "délai dépassé",
+        "fail rather than let buffered records (for sorting, grouping, dedup and the like) or a single line take more than SIZE bytes, like 512M" => {
+            "échouer plutôt que laisser les enregistrements en mémoire (pour trier, grouper, dédoublonner, etc.) ou une seule ligne dépasser SIZE octets, par exemple 512M"
+        }
+        "invalid size '{}'" => "taille invalide '{}'",
+        "{} took more than the --max-memory limit of {} bytes" => "{} : limite --max-memory de {} octets dépassée",
+        "buffered records" => "les enregistrements en mémoire",
+        "a single line" => "une seule ligne",
+        "write a JSON record of the run (inputs, job, record counts) to FILE" => "écrire un compte rendu JSON de l'exécution (entrées, tâche, nombre d'enregistrements) dans FILE",
+        "limit output to RATE per second, in bytes (10MB/s) or records (1000/s)" => {
+            "limiter la sortie à RATE par seconde, en octets (10MB/s) ou en enregistrements (1000/s)"
+        }
+        "invalid rate '{}'" => "débit invalide '{}'",
+        "every few seconds, save how far through the input files the job has got to FILE" => {
+            "enregistrer dans FILE, toutes les quelques secondes, l'avancement dans les fichiers d'entrée"
+        }
+        "carry on from where the --checkpoint file says an earlier run got to" => {
+            "reprendre là où le fichier --checkpoint indique qu'une exécution précédente s'est arrêtée"
+        }
+        "--checkpoint needs input files, since stdin can't be resumed" => {
+            "--checkpoint nécessite des fichiers d'entrée, car stdin ne peut pas être repris"
+        }
+        "the checkpoint is for '{}', which is not input {} this time" => {
+            "le point de reprise concerne '{}', qui n'est pas l'entrée {} cette fois"
+        }
+        "'{}' is not an rcut checkpoint" => "'{}' n'est pas un point de reprise rcut",
+        "take each input's first line as a header and line its columns up with the first input's" => {
+            "lire la première ligne de chaque entrée comme en-tête et aligner ses colonnes sur celles de la première entrée"
+        }
+        "select the fields listed in FILE, one index, range or, with --header, column name per line, in that order" => {
+            "sélectionner les champs listés dans FILE, un indice, intervalle ou, avec --header, \
+             nom de colonne par ligne, dans cet ordre"
+        }
+        "no fields to order by" => "aucun champ pour fixer l'ordre",
+        "also copy the input, unchanged, to FILE" => "copier aussi l'entrée, telle quelle, dans FILE",
+        "find fixed-width columns from how the first lines of input line up" => {
+            "déduire des colonnes de largeur fixe de l'alignement des premières lignes"
+        }
+        "separator used when printing fields" => "séparateur utilisé pour afficher les champs",
+        "read \\t, \\n, \\0, \\\\ and \\xNN in -d and -o as the characters they stand for" => {
+            "lire \\t, \\n, \\0, \\\\ et \\xNN dans -d et -o comme les caractères qu'ils représentent"
+        }
+        "\\x{} isn't the hex code of an ASCII character" => "\\x{} n'est pas le code hexadécimal d'un caractère ASCII",
+        "unknown escape \\{}" => "séquence d'échappement inconnue \\{}",
+        "a trailing \\ escapes nothing" => "un \\ final n'échappe rien",
+        "print every line unchanged, with the selected fields appended or prepended" => {
+            "afficher chaque ligne telle quelle, suivie ou précédée des champs sélectionnés"
+        }
+        "cut the output again, as given by STAGE: `[-d DELIM] [-o SEP] [-f] FIELDS`; repeatable" => {
+            "découper à nouveau la sortie selon STAGE : `[-d DELIM] [-o SEP] [-f] FIELDS` ; \
+             répétable"
+        }
+        "invalid stage '{}'" => "étape invalide « {} »",
+        "split field N of the selection on DELIM into named columns, as N:DELIM:NAME,..." => {
+            "découper le champ N de la sélection sur DELIM en colonnes nommées, sous la forme \
+             N:DELIM:NAME,..."
+        }
+        "invalid explode '{}'" => "découpage invalide « {} »",
+        "append the fields of matching lines in FILE, keyed as given by --on" => {
+            "ajouter les champs des lignes correspondantes de FILE, selon la clé de --on"
+        }
+        "key fields for --join, as INPUT=FILE or a single field for both [default: 1]" => {
+            "champs clés pour --join, sous la forme INPUT=FILE ou un seul champ pour les deux \
+             [défaut : 1]"
+        }
+        "fields of FILE to append for --join [default: all but the key]" => {
+            "champs de FILE à ajouter pour --join [défaut : tous sauf la clé]"
+        }
+        "invalid join key '{}'" => "clé de jointure invalide « {} »",
+        "swap rows and columns of the selected fields" => {
+            "échanger les lignes et les colonnes des champs sélectionnés"
+        }
+        "print only one record for each distinct value of these output fields" => {
+            "n'afficher qu'un enregistrement par valeur distincte de ces champs de sortie"
+        }
+        "which record --dedup-key keeps" => "l'enregistrement que --dedup-key conserve",
+        "print the output ordered by column N, as N[:numeric][:desc]" => {
+            "afficher la sortie triée selon la colonne N, sous la forme N[:numeric][:desc]"
+        }
+        "invalid sort key '{}'" => "clé de tri invalide « {} »",
+        "print one row per distinct value of these fields, summarized by --agg" => {
+            "afficher une ligne par valeur distincte de ces champs, résumée par --agg"
+        }
+        "summaries for --group-by: count, sum(N), min(N), max(N), mean(N) [default: count]" => {
+            "résumés pour --group-by : count, sum(N), min(N), max(N), mean(N) [défaut : count]"
+        }
+        "invalid aggregate '{}'" => "agrégat invalide « {} »",
+        "cross-tabulate two fields, as rows=N,cols=M[,values=AGG] [default values: count]" => {
+            "tableau croisé de deux champs, sous la forme rows=N,cols=M[,values=AGG] \
+             [values par défaut : count]"
+        }
+        "invalid pivot '{}'" => "tableau croisé invalide « {} »",
+        "read all inputs in lockstep and print their selected fields side by side, like paste; without -f, every positional is a file" => {
+            "lire toutes les entrées en parallèle et afficher leurs champs côte à côte, comme paste ; \
+             sans -f, chaque argument positionnel est un fichier"
+        }
+        "read one line from each input in turn, padding inputs that run out; without -f, every positional is a file" => {
+            "lire une ligne de chaque entrée à tour de rôle, en complétant les entrées épuisées ; \
+             sans -f, chaque argument positionnel est un fichier"
+        }
+        "fields to select" => "champs à sélectionner",
+        "print per line instead of selecting: `[print] EXPR, ... [if COND]` over $N, NF and NR" => {
+            "afficher par ligne au lieu de sélectionner : `[print] EXPR, ... [if COND]` sur $N, \
+             NF et NR"
+        }
+        "unexpected '{}' in expression" => "« {} » inattendu dans l'expression",
+        "unexpected end of expression" => "fin d'expression inattendue",
+        "expression is nested too deeply" => "expression trop profondément imbriquée",
+        "expression is too long" => "expression trop longue",
+        "unterminated string in expression" => "chaîne non terminée dans l'expression",
+        "division by zero" => "division par zéro",
+        "file(s) to process, - meaning stdin, or field selectors" => {
+            "fichier(s) à traiter, - désignant l'entrée standard, ou sélecteurs de champs"
+        }
+        "file(s) to process, even if they look like field selectors" => {
+            "fichier(s) à traiter, même s'ils ressemblent à des sélecteurs de champs"
+        }
+        "print the resolved job and its inputs without processing data" => {
+            "afficher la tâche résolue et ses entrées sans traiter de données"
+        }
+        "log progress to stderr; repeat for more detail (RUST_LOG overrides)" => {
+            "journaliser la progression sur stderr ; répéter pour plus de détails \
+             (RUST_LOG est prioritaire)"
+        }
+        "log debugging detail to stderr, same as -vv" => {
+            "journaliser les détails de débogage sur stderr, comme -vv"
+        }
+        "how to report warnings and errors on stderr" => {
+            "format des avertissements et des erreurs sur stderr"
+        }
+        "also run the system cut on the same input and fail if its output differs" => {
+            "exécuter aussi le cut du système sur la même entrée et échouer si sa sortie diffère"
+        }
+        "these options are not cut-compatible" => "ces options ne sont pas compatibles avec cut",
+        "cut only splits on a single character" => "cut ne découpe que sur un seul caractère",
+        "cut splits on tabs unless told otherwise, so give -d" => {
+            "cut découpe sur les tabulations par défaut, indiquez donc -d"
+        }
+        "cut joins fields with the delimiter, so -o must match it" => {
+            "cut joint les champs avec le délimiteur, -o doit donc lui correspondre"
+        }
+        "cut can't count fields from the end" => "cut ne sait pas compter les champs depuis la fin",
+        "cut prints fields in input order, so the selector must be ascending" => {
+            "cut affiche les champs dans l'ordre de l'entrée, le sélecteur doit donc être croissant"
+        }
+        "<no line>" => "<aucune ligne>",
+        "rcut and cut disagree on {} of {} lines; the first is line {}:\n  rcut: {}\n  cut:  {}" => {
+            "rcut et cut divergent sur {} lignes sur {} ; la première est la ligne {} :\n  rcut : {}\n  cut :  {}"
+        }
+        "could not run cut" => "impossible d'exécuter cut",
+        "cut failed: {}" => "cut a échoué : {}",
+        "Print help information" => "Afficher l'aide",
+        "Print this message or the help of the given subcommand(s)" => {
+            "Afficher ce message ou l'aide des sous-commandes indiquées"
+        }
+        "cut delimited fields, the default when no subcommand is given" => {
+            "découper des champs délimités, le mode par défaut sans sous-commande"
+        }
+        "Print version information" => "Afficher la version",
+        "choose columns interactively from the start of FILE and print the rcut command for them" => {
+            "choisir des colonnes au début de FILE de façon interactive et afficher la commande \
+             rcut correspondante"
+        }
+        "field delimiter to start with" => "délimiteur de champs initial",
+        "run the chosen command instead of printing it" => {
+            "exécuter la commande choisie au lieu de l'afficher"
+        }
+        "file to preview" => "fichier à prévisualiser",
+        "show a live dashboard of the most common values of some fields, and their rates" => {
+            "afficher en direct les valeurs les plus fréquentes de certains champs et leurs débits"
+        }
+        "fields to count" => "champs à compter",
+        "keep reading as FILE grows, like `tail -f`" => {
+            "continuer à lire à mesure que FILE grandit, comme `tail -f`"
+        }
+        "file to read [default: stdin]" => "fichier à lire [défaut : stdin]",
+        "rcut top needs a terminal" => "rcut top nécessite un terminal",
+        "--follow needs a file to follow" => "--follow nécessite un fichier à suivre",
+        "print a random sample of the selected records" => {
+            "afficher un échantillon aléatoire des enregistrements sélectionnés"
+        }
+        "how many records to keep, or how many of each --stratify value" => {
+            "nombre d'enregistrements à garder, ou nombre par valeur de --stratify"
+        }
+        "seed every random choice, as made by `rcut sample`, for a reproducible run" => {
+            "graine de tous les tirages aléatoires, comme ceux de `rcut sample`, pour une exécution reproductible"
+        }
+        "sample each value of output field N separately" => {
+            "échantillonner séparément chaque valeur du champ de sortie N"
+        }
+        "SAMPLING" => "ÉCHANTILLONNAGE",
+        "finished" => "terminé",
+        "reading" => "lecture",
+        "interrupted" => "interrompu",
+        "offset" => "position",
+        "records written" => "enregistrements écrits",
+        "lines" => "lignes",
+        "q to quit" => "q pour quitter",
+        "count" => "nombre",
+        "value" => "valeur",
+        "could not draw the screen" => "impossible de dessiner l'écran",
+        "cut the body of each HTTP POST as given by --spec and send back the result" => {
+            "découper le corps de chaque POST HTTP selon --spec et renvoyer le résultat"
+        }
+        "address to listen on" => "adresse d'écoute",
+        "rcut options to cut each request with, quoted as for a shell" => {
+            "options rcut appliquées à chaque requête, citées comme pour un shell"
+        }
+        "log requests to stderr; repeat for more detail (RUST_LOG overrides)" => {
+            "journaliser les requêtes sur stderr ; répéter pour plus de détails \
+             (RUST_LOG est prioritaire)"
+        }
+        "unterminated quote in '{}'" => "guillemet non fermé dans « {} »",
+        "a serve spec can only say how to cut, not what to read or where to write" => {
+            "une spécification serve indique seulement comment découper, \
+             pas quoi lire ni où écrire"
+        }
+        "rcut pick needs a terminal" => "rcut pick nécessite un terminal",
+        "select at least one column first" => "sélectionnez d'abord au moins une colonne",
+        "new delimiter (empty for whitespace):" => "nouveau délimiteur (vide pour les blancs) :",
+        "←/→ move  space toggle  a all/none  d delimiter  enter accept  q quit" => {
+            "←/→ déplacer  espace basculer  a tout/rien  d délimiteur  entrée valider  q quitter"
+        }
+        "'{}' is both a field selector and an existing file; treating it as a selector (use `-- {}` to read it as a file)" => {
+            "« {} » est à la fois un sélecteur de champs et un fichier existant ; \
+             il est traité comme un sélecteur (utilisez `-- {}` pour le lire comme fichier)"
+        }
+        "invalid field selector '{}'" => "sélecteur de champs invalide « {} »",
+        "expected n, last, n-N or last-N" => "n, last, n-N ou last-N attendu",
+        "warning" => "avertissement",
+        "Error" => "Erreur",
+        "the delimiter cannot be empty" => "le délimiteur ne peut pas être vide",
+        "SELECTION" => "SÉLECTION",
+        "INPUT" => "ENTRÉE",
+        "OUTPUT" => "SORTIE",
+        "DIAGNOSTICS" => "DIAGNOSTICS",
+        crate::cli::EXAMPLES => {
+            "EXEMPLES :
+    ps aux | rcut 2 11               afficher les colonnes PID et commande
+    rcut -d : -f 1,7 /etc/passwd     afficher chaque utilisateur et son shell
+    rcut -d , -o ' | ' -f 1-3 a.csv  afficher les trois premières colonnes, rejointes
+    rcut -f -1 log.txt               afficher le dernier champ de chaque ligne
+    rcut 2 -- 1-5                    lire le fichier nommé 1-5, pas un sélecteur"
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_tag() {
+        assert_eq!(Locale::from_tag("de_DE.UTF-8"), Locale::De);
+        assert_eq!(Locale::from_tag("fr"), Locale::Fr);
+        assert_eq!(Locale::from_tag("FR_ca"), Locale::Fr);
+        assert_eq!(Locale::from_tag("C"), Locale::En);
+        assert_eq!(Locale::from_tag("en_US.UTF-8"), Locale::En);
+    }
+
+    #[test]
+    fn test_translate() {
+        assert_eq!(translate(Locale::En, "field delimiter"), "field delimiter");
+        assert_eq!(translate(Locale::De, "field delimiter"), "Feldtrenner");
+        assert_eq!(
+            translate(Locale::Fr, "not in any catalog"),
+            "not in any catalog"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::{collections::HashMap, ffi::OsString, io::BufRead};
+
+use anyhow::{Context, Result};
+
+use crate::{borrowed, diagnostics::InputLine, i18n::trf, resolve_index, Delimiter, FieldSelector};
+
+// What --join, --on and --join-fields asked for, before FILE is read.
+#[derive(Debug, Clone)]
+pub struct JoinSpec {
+    pub file: OsString,
+    pub left: isize,
+    pub right: isize,
+    pub fields: Option<FieldSelector>,
+}
+
+// Parse `LEFT=RIGHT`, the key field of the main input and the key field
+// of the joined file.  A bare `N` uses the same field for both.
+pub fn parse_on(s: &str) -> Result<(isize, isize)> {
+    let parse = |idx: &str| {
+        idx.parse::<isize>()
+            .ok()
+            .filter(|idx| *idx != 0)
+            .ok_or_else(|| format_err!(trf("invalid join key '{}'", &[s])))
+    };
+    match s.split_once('=') {
+        Some((left, right)) => Ok((parse(left)?, parse(right)?)),
+        None => {
+            let idx = parse(s)?;
+            Ok((idx, idx))
+        }
+    }
+}
+
+// The joined file, indexed by its key field.  Only the 80% case of
+// `join`: the whole file is held in memory, but neither input needs to
+// be sorted.
+#[derive(Debug)]
+pub struct JoinTable {
+    left: isize,
+    rows: HashMap<String, Vec<Vec<String>>>,
+}
+
+impl JoinTable {
+    // Read the joined file, keeping for each line the fields named by
+    // the spec, or every field but the key if it names none.
+    pub fn load(spec: &JoinSpec, delim: &Delimiter, reader: impl BufRead) -> Result<Self> {
+        let mut rows: HashMap<String, Vec<Vec<String>>> = HashMap::new();
+        for (lineno, line) in reader.lines().enumerate() {
+            let line = line.context(InputLine(lineno as u64 + 1))?;
+            let split = delim.split(&line);
+            let fields = borrowed(&split);
+            let key = match resolve_index(spec.right, fields.len()) {
+                Some(key) => key,
+                None => continue,
+            };
+            let row = match spec.fields {
+                Some(ref selector) => selector.select(&fields),
+                None => fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != key)
+                    .map(|(_, val)| *val)
+                    .collect(),
+            };
+            rows.entry(String::from(fields[key]))
+                .or_default()
+                .push(row.into_iter().map(String::from).collect());
+        }
+        Ok(JoinTable {
+            left: spec.left,
+            rows,
+        })
+    }
+
+    // The joined rows whose key matches the key field of `fields`, in
+    // the order they appeared in the joined file.
+    pub fn matches(&self, fields: &[&str]) -> &[Vec<String>] {
+        resolve_index(self.left, fields.len())
+            .and_then(|key| self.rows.get(fields[key]))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_on() {
+        assert_eq!(parse_on("1=2").unwrap(), (1, 2));
+        assert_eq!(parse_on("3").unwrap(), (3, 3));
+        assert_eq!(parse_on("-1=1").unwrap(), (-1, 1));
+        assert!(parse_on("0=1").is_err());
+        assert!(parse_on("a=b").is_err());
+    }
+
+    #[test]
+    fn test_join_table() {
+        let spec = JoinSpec {
+            file: OsString::from("users"),
+            left: 1,
+            right: 2,
+            fields: None,
+        };
+        let table = JoinTable::load(
+            &spec,
+            &Delimiter::String(String::from(":")),
+            "alice:1:admin\nbob:2:user\ncarol:1:ops\n".as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            table.matches(&["1", "x"]),
+            &[vec!["alice", "admin"], vec!["carol", "ops"]]
+        );
+        assert!(table.matches(&["3", "x"]).is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+use std::{borrow::Cow, fmt};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::i18n::{tr, trf};
+
+// Error for a line that isn't a JSON object, which is worth pointing
+// at by its line number.
+#[derive(Debug)]
+pub struct NotAnObject;
+
+impl fmt::Display for NotAnObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", tr("the line is not a JSON object"))
+    }
+}
+
+impl std::error::Error for NotAnObject {}
+
+// The keys --json picks out of each line's object, in selector order,
+// each a path like `user.id` through nested objects, where a number
+// indexes an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths(Vec<String>);
+
+impl Paths {
+    pub fn new(paths: Vec<String>) -> Result<Self> {
+        if let Some(path) = paths.iter().find(|path| path.split('.').any(str::is_empty)) {
+            bail!(trf("'{}' is not a key path", &[path]));
+        }
+        Ok(Paths(paths))
+    }
+
+    pub fn describe(&self) -> String {
+        format!("JSON keys {}", self.0.join(", "))
+    }
+
+    // The values at each path in a line of JSON, as text: strings
+    // as they are, null or a missing key as nothing, and anything else
+    // as compact JSON.
+    pub fn fields<'a>(&self, line: &str) -> Result<Vec<Cow<'a, str>>> {
+        let object: Value = serde_json::from_str(line).context(NotAnObject)?;
+        if !object.is_object() {
+            bail!(NotAnObject);
+        }
+        Ok(self
+            .0
+            .iter()
+            .map(|path| Cow::Owned(text(lookup(&object, path))))
+            .collect())
+    }
+}
+
+fn lookup<'v>(value: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.').try_fold(value, |value, key| match value {
+        Value::Object(object) => object.get(key),
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+fn text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields() {
+        let paths = |paths: &[&str]| Paths::new(paths.iter().map(|p| p.to_string()).collect());
+        let line = r#"{"user": {"id": 7, "tags": ["a", "b"]}, "msg": "hi \"there\"", "n": null}"#;
+        let fields = paths(&["user.id", "msg", "user.tags.1", "user", "n", "missing.key"])
+            .unwrap()
+            .fields(line)
+            .unwrap();
+        assert_eq!(
+            fields,
+            vec![
+                "7",
+                r#"hi "there""#,
+                "b",
+                r#"{"id":7,"tags":["a","b"]}"#,
+                "",
+                ""
+            ]
+        );
+        assert!(paths(&["msg"]).unwrap().fields("[1, 2]").is_err());
+        assert!(paths(&["msg"]).unwrap().fields("not json").is_err());
+        assert!(paths(&["user..id"]).is_err());
+    }
+}
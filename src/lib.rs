@@ -0,0 +1,4772 @@
+//! rcut cuts fields out of lines of text, like cut(1) with more ways
+//! to split and pick them.  The command line is a thin wrapper over
+//! this library, which other programs can use to do the same cutting:
+//! build a [`CutJob`] from a [`Delimiter`] and a [`FieldSelector`],
+//! then feed it input with [`CutJob::process_reader`].
+//!
+//! ```
+//! use rcut::{field_parser, CutJob, Delimiter};
+//!
+//! let job = CutJob::new(
+//!     Delimiter::String(String::from(",")),
+//!     field_parser("3,1")?,
+//!     ";",
+//! );
+//! let mut output = vec![];
+//! job.process_reader("a,b,c\nd,e,f\n".as_bytes(), &mut output)?;
+//! job.finish(&mut output)?;
+//! assert_eq!(output, b"c;a\nf;d\n");
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    clone::Clone,
+    ffi::OsString,
+    fs::File,
+    io::{self, BufRead, BufWriter, IsTerminal, Read, Write},
+    ops::Range,
+    path::Path,
+    sync::OnceLock,
+    time::Duration,
+};
+
+use clap::FromArgMatches;
+
+#[macro_use]
+extern crate anyhow;
+
+use anyhow::{Context, Result};
+
+mod aggregate;
+mod audit;
+mod checkpoint;
+mod cli;
+mod columns;
+mod csv;
+mod decompress;
+mod dedup;
+mod diagnostics;
+mod explode;
+mod expr;
+mod filelist;
+#[doc(hidden)]
+pub mod fuzzing;
+mod headers;
+mod i18n;
+mod join;
+mod json;
+mod logfmt;
+mod memory;
+mod metrics;
+mod mmap;
+mod outfile;
+mod pick;
+mod positions;
+mod predicate;
+mod rejects;
+mod rng;
+mod sample;
+mod serve;
+mod signals;
+mod subfield;
+mod tee;
+mod template;
+mod throttle;
+mod top;
+mod transform;
+mod verify;
+mod walk;
+
+use aggregate::{Aggregate, Count, GroupBy, Pivot, Sort, Stats, Table, Transpose};
+use audit::Audit;
+use checkpoint::{Checkpoint, Position};
+use cli::{Cli, Mode};
+use decompress::{Codec, Input};
+use dedup::{Distinct, Keep, KeepLast};
+use diagnostics::{Diagnostic, ErrorFormat, InputFile, InputLine, MissingFields, NotUtf8};
+use explode::Explode;
+use expr::Program;
+use filelist::FileList;
+use headers::Alignment;
+use i18n::{tr, trf};
+use join::{JoinSpec, JoinTable};
+use memory::MemoryCap;
+use mmap::Mapping;
+use outfile::{Destination, OutputFile};
+use positions::Unit;
+use predicate::Predicate;
+use regex::Regex;
+use rejects::Rejects;
+use rng::Seed;
+use sample::Sample;
+use signals::{Interrupted, Progress, TimedOut};
+use subfield::Subfield;
+use tee::Tee;
+use template::Template;
+use throttle::{Rate, Throttle};
+use tracing::{debug, info};
+use tracing_subscriber::EnvFilter;
+use transform::FieldMap;
+use walk::{Filter, Walk};
+
+/// How a line is split into fields.
+#[derive(Debug, Clone)]
+pub enum Delimiter {
+    /// Runs of whitespace, ignoring any at either end of the line.
+    Whitespace,
+    /// Every occurrence of the string, as with `cut -d`.
+    String(String),
+    /// Anything matching the pattern, from -E.
+    Regex(Regex),
+    /// RFC 4180 fields with this separator, from --csv.
+    Csv(char),
+    /// The values at these keys of a line of JSON, from --json; see
+    /// [`Delimiter::json`].
+    Json(json::Paths),
+    /// The values at these keys of a line of `key=value` pairs, from
+    /// --logfmt; see [`Delimiter::logfmt`].
+    Logfmt(logfmt::Keys),
+    /// Fixed-width columns, found from the first lines of input.  Until
+    /// then, lines split on whitespace.  See [`Delimiter::inferred`].
+    Inferred(OnceLock<Vec<usize>>),
+    /// Fixed-width columns, given by the character offset each starts
+    /// at followed by the one the last ends at.  See
+    /// [`Delimiter::widths`].
+    Fixed(Vec<usize>),
+    /// Another delimiter, but splitting a line into no more than `most`
+    /// fields, the last of them the rest of the line, from --max-fields.
+    /// Splitting `from_end`, with --rsplit, the first field is the rest
+    /// of the line instead.
+    Limited {
+        delim: Box<Delimiter>,
+        most: usize,
+        from_end: bool,
+    },
+    /// The capture groups of the pattern's first match, from
+    /// --extract.  A line it doesn't match has no fields.
+    Extract(Regex),
+}
+
+impl Delimiter {
+    /// Split lines of JSON objects into the values at `keys`, each a
+    /// path like `user.id`, where a number indexes an array.
+    pub fn json(keys: Vec<String>) -> Result<Self> {
+        Ok(Delimiter::Json(json::Paths::new(keys)?))
+    }
+
+    /// Split logfmt lines, like `level=info msg="disk full"`, into the
+    /// values at `keys`, empty where a line doesn't have one.
+    pub fn logfmt(keys: Vec<String>) -> Self {
+        Delimiter::Logfmt(logfmt::Keys::new(keys))
+    }
+
+    /// Split lines into the fixed-width columns the first lines of
+    /// input line up in.
+    pub fn inferred() -> Self {
+        Delimiter::Inferred(OnceLock::new())
+    }
+
+    /// Split lines into columns of the given widths, in characters,
+    /// each trimmed of its padding.  Anything past the last column is
+    /// dropped.
+    pub fn widths(widths: &[usize]) -> Self {
+        let bounds = std::iter::once(0)
+            .chain(widths.iter().scan(0, |end, width| {
+                *end += width;
+                Some(*end)
+            }))
+            .collect();
+        Delimiter::Fixed(bounds)
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Delimiter::Whitespace => String::from("whitespace"),
+            Delimiter::String(s) => format!("{:?}", s),
+            Delimiter::Regex(re) => format!("/{}/", re.as_str()),
+            Delimiter::Csv(sep) => format!("CSV, separated by {:?}", sep),
+            Delimiter::Json(paths) => paths.describe(),
+            Delimiter::Logfmt(keys) => keys.describe(),
+            Delimiter::Inferred(starts) => match starts.get() {
+                Some(starts) => format!(
+                    "columns starting at {}",
+                    starts
+                        .iter()
+                        .map(|start| (start + 1).to_string())
+                        .collect::<Vec<String>>()
+                        .join(",")
+                ),
+                None => String::from("inferred columns"),
+            },
+            Delimiter::Fixed(bounds) => format!(
+                "columns {} wide",
+                bounds
+                    .windows(2)
+                    .map(|bound| (bound[1] - bound[0]).to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+            Delimiter::Limited {
+                delim,
+                most,
+                from_end,
+            } => format!(
+                "{}, into at most {} fields{}",
+                delim.describe(),
+                most,
+                if *from_end { " from the end" } else { "" }
+            ),
+            Delimiter::Extract(re) => format!("the groups of /{}/", re.as_str()),
+        }
+    }
+
+    // Split a line into its fields, where a line of JSON that doesn't
+    // parse has none.
+    fn split<'a>(&self, line: &'a str) -> Vec<Cow<'a, str>> {
+        self.parse(line).unwrap_or_default()
+    }
+
+    // Split a line into its fields, failing on a line of JSON that
+    // doesn't parse.  Only unquoting a CSV field or reading JSON can
+    // make a field that isn't part of the line.
+    fn parse<'a>(&self, line: &'a str) -> Result<Vec<Cow<'a, str>>> {
+        match self {
+            Delimiter::Csv(sep) => Ok(csv::split(line, *sep)),
+            Delimiter::Json(paths) => paths.fields(line),
+            Delimiter::Logfmt(keys) => Ok(keys.fields(line)),
+            _ => Ok(self
+                .slices(line)
+                .unwrap_or_default()
+                .into_iter()
+                .map(Cow::Borrowed)
+                .collect()),
+        }
+    }
+
+    // Fill `offsets` with where each of a line's fields lies in it, as
+    // a plain string, whitespace or pattern delimiter can.  Returns
+    // false for any other.  Once `reach` fields have been found, the
+    // rest of the line is left as one more, not split any further.
+    fn offsets(&self, line: &str, offsets: &mut Vec<Range<usize>>, reach: usize) -> bool {
+        offsets.clear();
+        let mut start = 0;
+        match self {
+            Delimiter::String(s) => {
+                each_match(line.as_bytes(), s.as_bytes(), |at| {
+                    if offsets.len() == reach {
+                        return false;
+                    }
+                    offsets.push(start..at);
+                    start = at + s.len();
+                    true
+                });
+            }
+            Delimiter::Regex(re) => {
+                for found in re.find_iter(line) {
+                    if offsets.len() == reach {
+                        break;
+                    }
+                    offsets.push(start..found.start());
+                    start = found.end();
+                }
+            }
+            Delimiter::Whitespace => {
+                for field in line.split_whitespace() {
+                    let at = field.as_ptr() as usize - line.as_ptr() as usize;
+                    if offsets.len() == reach {
+                        offsets.push(at..line.trim_end().len());
+                        break;
+                    }
+                    offsets.push(at..at + field.len());
+                }
+                return true;
+            }
+            // Split from the start, the fields past `most` are all part
+            // of the last, so there's no need to look for them.
+            Delimiter::Limited {
+                delim,
+                most,
+                from_end,
+            } => {
+                let reach = match from_end {
+                    true => usize::MAX,
+                    false => reach.min(most - 1),
+                };
+                if !delim.offsets(line, offsets, reach) {
+                    return false;
+                }
+                merge_fields(offsets, *most, *from_end);
+                return true;
+            }
+            _ => return false,
+        }
+        offsets.push(start..line.len());
+        true
+    }
+
+    // Split a line into slices of itself, as every delimiter but CSV,
+    // JSON and logfmt can, without the copying `split` does.
+    fn slices<'a>(&self, line: &'a str) -> Option<Vec<&'a str>> {
+        let fields = match self {
+            Delimiter::String(s) => {
+                let mut fields = vec![];
+                let mut start = 0;
+                each_match(line.as_bytes(), s.as_bytes(), |at| {
+                    fields.push(&line[start..at]);
+                    start = at + s.len();
+                    true
+                });
+                fields.push(&line[start..]);
+                fields
+            }
+            Delimiter::Regex(re) => re.split(line).collect(),
+            Delimiter::Csv(_) | Delimiter::Json(_) | Delimiter::Logfmt(_) => return None,
+            Delimiter::Whitespace => line.split_whitespace().collect(),
+            Delimiter::Inferred(starts) => match starts.get() {
+                Some(starts) => columns::split_columns(line, starts),
+                None => line.split_whitespace().collect(),
+            },
+            Delimiter::Fixed(bounds) => {
+                let mut fields = columns::split_columns(line, bounds);
+                fields.pop();
+                fields
+            }
+            // A group that took no part in the match is empty.
+            Delimiter::Extract(re) => match re.captures(line) {
+                Some(groups) => groups
+                    .iter()
+                    .skip(1)
+                    .map(|group| group.map_or("", |group| group.as_str()))
+                    .collect(),
+                None => vec![],
+            },
+            Delimiter::Limited {
+                delim,
+                most,
+                from_end,
+            } => {
+                let fields = delim.slices(line)?;
+                if fields.len() <= *most {
+                    return Some(fields);
+                }
+                // Every field is a slice of the line, so those merged
+                // into one become the stretch of the line they cover.
+                let at = |field: &str| field.as_ptr() as usize - line.as_ptr() as usize;
+                let mut spans = fields
+                    .iter()
+                    .map(|field| at(field)..at(field) + field.len())
+                    .collect();
+                merge_fields(&mut spans, *most, *from_end);
+                spans.into_iter().map(|span| &line[span]).collect()
+            }
+        };
+        Some(fields)
+    }
+}
+
+// Make a line's fields, as where each lies in it, no more than `most`,
+// with the last of them running across those past the rest, or
+// `from_end` the first running across those before.
+fn merge_fields(fields: &mut Vec<Range<usize>>, most: usize, from_end: bool) {
+    let extra = match fields.len().checked_sub(most) {
+        Some(extra) if extra > 0 => extra,
+        _ => return,
+    };
+    if from_end {
+        let start = fields[0].start;
+        fields.drain(..extra);
+        fields[0].start = start;
+    } else {
+        let end = fields[fields.len() - 1].end;
+        fields.truncate(most);
+        fields[most - 1].end = end;
+    }
+}
+
+// A record without its ending: \n or \r\n, or whatever -z or
+// --record-sep ends records with.
+fn strip_line_ending<'a>(line: &'a [u8], terminator: &str) -> &'a [u8] {
+    match line.strip_suffix(terminator.as_bytes()) {
+        Some(line) if terminator == "\n" => line.strip_suffix(b"\r").unwrap_or(line),
+        Some(line) => line,
+        None => line,
+    }
+}
+
+// Hand `found` where each occurrence of a delimiter starts in a line,
+// for as long as it asks for more.  The usual delimiter of a single
+// byte is looked for with memchr, which is much quicker at it than a
+// search for a string, even one of one byte.
+fn each_match(line: &[u8], delim: &[u8], mut found: impl FnMut(usize) -> bool) {
+    match delim {
+        [byte] => {
+            for at in memchr::memchr_iter(*byte, line) {
+                if !found(at) {
+                    return;
+                }
+            }
+        }
+        _ => {
+            for at in memchr::memmem::find_iter(line, delim) {
+                if !found(at) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// Split a line that isn't text on every occurrence of a delimiter.
+fn split_bytes<'a>(line: &'a [u8], delim: &[u8]) -> Vec<&'a [u8]> {
+    let mut fields = vec![];
+    let mut start = 0;
+    each_match(line, delim, |at| {
+        fields.push(&line[start..at]);
+        start = at + delim.len();
+        true
+    });
+    fields.push(&line[start..]);
+    fields
+}
+
+// Borrow split fields as the plain strings the rest of the job takes.
+fn borrowed<'a>(fields: &'a [Cow<'_, str>]) -> Vec<&'a str> {
+    fields.iter().map(AsRef::as_ref).collect()
+}
+
+// Turn a 1-based field index, or a negative one counting back from the
+// end, into an offset into a line with `len` fields.
+fn resolve_index(idx: isize, len: usize) -> Option<usize> {
+    let offset = if idx < 0 { len as isize + idx } else { idx - 1 };
+    if offset >= 0 && (offset as usize) < len {
+        Some(offset as usize)
+    } else {
+        None
+    }
+}
+
+// How many selected fields --dry-run lists before giving up.
+const MAX_LISTED_FIELDS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FieldRange {
+    start: isize,
+    stop: isize,
+    // Take every `step`th field of the range, from `1-20:2`.
+    step: usize,
+}
+
+impl FieldRange {
+    fn new_span(start: isize, stop: isize) -> Self {
+        FieldRange {
+            start,
+            stop,
+            step: 1,
+        }
+    }
+    fn new_val(val: isize) -> Self {
+        FieldRange::new_span(val, val)
+    }
+    fn stepped(self, step: usize) -> Self {
+        FieldRange { step, ..self }
+    }
+
+    // Whether the range takes the field at `offset` in a line with
+    // `len` fields.
+    fn covers(&self, offset: usize, len: usize) -> bool {
+        let offsets = self.offsets(len);
+        offsets.contains(&offset) && (offset - offsets.start).is_multiple_of(self.step)
+    }
+
+    // The offsets the range covers in a line with `len` fields, which
+    // may be none.  Either end can count back from the end of the line,
+    // and nothing past the end can be selected, so a range like
+    // 1-99999999999 stops there.
+    fn offsets(&self, len: usize) -> Range<usize> {
+        let len = len as isize;
+        let resolve = |idx: isize| if idx < 0 { len + idx } else { idx - 1 };
+        let start = resolve(self.start).max(0);
+        let stop = resolve(self.stop.min(len));
+        if start > stop {
+            0..0
+        } else {
+            start as usize..stop as usize + 1
+        }
+    }
+}
+
+// The stop of a range left open, like `3-`, which runs to the end of
+// the line however long it is.
+const OPEN: isize = isize::MAX;
+
+// How far a selector can reach with --pad, which unlike plain selection
+// doesn't stop at the end of the line.
+const MAX_PADDED: usize = 1 << 16;
+
+/// Which fields of a line to cut, in the order they are to be printed.
+/// Build one with [`field_parser`].
+#[derive(Debug, Clone, Default)]
+pub struct FieldSelector {
+    fields: Vec<FieldRange>,
+    // Written as a bare `-N`, which cut reads as fields 1 to N rather
+    // than the Nth from the end.
+    bare_negative: bool,
+    // Column names, each with the place in `fields` it stands for until
+    // --header looks it up.
+    names: Vec<(usize, String)>,
+    // Ranges knocked out of the rest with `^`, as in `1-10,^4`.
+    excluded: Vec<FieldRange>,
+}
+
+impl FieldSelector {
+    /// A selector with no ranges, which takes every field as-is.  Used
+    /// when no selector is given at all.
+    pub fn all() -> Self {
+        FieldSelector::default()
+    }
+
+    // How many of a line's fields have to be found to pick out those the
+    // selector names, which is all of them if it names every field,
+    // counts from the end or runs to it.
+    fn reach(&self) -> usize {
+        let ends = self
+            .fields
+            .iter()
+            .chain(self.excluded.iter())
+            .flat_map(|range| [range.start, range.stop]);
+        if self.fields.is_empty() || ends.clone().any(|idx| idx < 0 || idx == OPEN) {
+            return usize::MAX;
+        }
+        ends.max().unwrap_or(0) as usize
+    }
+
+    // The fewest fields a line can have and still have every one the
+    // selector names, counting from either end.
+    fn needs(&self) -> usize {
+        self.fields
+            .iter()
+            .flat_map(|range| [range.start, range.stop])
+            .filter(|idx| *idx != OPEN)
+            .map(isize::unsigned_abs)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Pick out the fields named by the selector, in selector order.
+    /// Fields the line doesn't have are skipped.
+    pub fn select<T: Clone>(&self, line_fields: &[T]) -> Vec<T> {
+        if self.fields.is_empty() && self.excluded.is_empty() {
+            return line_fields.to_vec();
+        }
+        self.indexes(line_fields.len())
+            .map(|offset| line_fields[offset].clone())
+            .collect()
+    }
+
+    /// Pick out the fields named by the selector, as [`select`] does,
+    /// but with `pad` in place of each field the line doesn't have.  A
+    /// range left open still stops at the end of the line.
+    ///
+    /// [`select`]: FieldSelector::select
+    pub fn select_padded<T: Clone>(&self, line_fields: &[T], pad: &T) -> Vec<T> {
+        if self.fields.is_empty() {
+            return self.select(line_fields);
+        }
+        let len = line_fields.len() as isize;
+        let resolve = |idx: isize| if idx < 0 { len + idx } else { idx - 1 };
+        let span = |range: &FieldRange, open: isize| {
+            let stop = match range.stop {
+                OPEN => open,
+                stop => resolve(stop),
+            };
+            (resolve(range.start), stop)
+        };
+        // Whether a `^` range knocks out the field at `at`, which can be
+        // past either end of the line.
+        let excluded = |at: isize| {
+            self.excluded.iter().any(|range| {
+                let (start, stop) = span(range, isize::MAX);
+                (start..=stop).contains(&at) && (at - start) % range.step as isize == 0
+            })
+        };
+        let mut selected = vec![];
+        for range in self.fields.iter() {
+            let (start, stop) = span(range, len - 1);
+            // A field counted from before the start of the line is
+            // missing as much as one past its end.
+            for at in (start..=stop).step_by(range.step) {
+                if excluded(at) {
+                    continue;
+                }
+                match usize::try_from(at).ok().and_then(|at| line_fields.get(at)) {
+                    Some(field) => selected.push(field.clone()),
+                    None => selected.push(pad.clone()),
+                }
+            }
+        }
+        selected
+    }
+
+    // The offsets of the fields the selector picks out of a line with
+    // `len` fields, in selector order, leaving out those it excludes.
+    fn indexes(&self, len: usize) -> impl Iterator<Item = usize> + '_ {
+        let every = self.fields.is_empty().then_some(0..len);
+        every
+            .into_iter()
+            .flatten()
+            .chain(
+                self.fields
+                    .iter()
+                    .flat_map(move |range| range.offsets(len).step_by(range.step)),
+            )
+            .filter(move |&offset| !self.excludes(offset, len))
+    }
+
+    // Whether a `^` range knocks out the field at `offset`.
+    fn excludes(&self, offset: usize, len: usize) -> bool {
+        self.excluded.iter().any(|range| range.covers(offset, len))
+    }
+
+    // Which of `len` fields the selector names, wherever it names them.
+    fn chosen(&self, len: usize) -> Vec<bool> {
+        let mut chosen = vec![false; len];
+        for offset in self.indexes(len) {
+            chosen[offset] = true;
+        }
+        chosen
+    }
+
+    /// The fields the selector doesn't name, in line order, for
+    /// --complement.
+    pub fn select_complement<T: Clone>(&self, line_fields: &[T]) -> Vec<T> {
+        line_fields
+            .iter()
+            .zip(self.chosen(line_fields.len()))
+            .filter(|(_, chosen)| !chosen)
+            .map(|(field, _)| field.clone())
+            .collect()
+    }
+}
+
+/// Everything a run of rcut does to its input: how lines are split,
+/// which fields are picked out of them and how they are written, along
+/// with whatever else the command line asked for.
+#[derive(Debug)]
+pub struct CutJob {
+    input_delim: Delimiter,
+    selector: FieldSelector,
+    // With -b or -c, what the selector counts instead of fields.
+    unit: Option<Unit>,
+    // Print what the selector leaves out, rather than what it names.
+    complement: bool,
+    undelimited: Undelimited,
+    output_separator: String,
+    // With --csv, fields are quoted on output where they need it.
+    quote_output: bool,
+    // With --output-format json or kv, the names to key each record's
+    // fields by, which are empty if there are none and unset until the
+    // header has been read.
+    output_keys: Option<OnceLock<Vec<String>>>,
+    // With --output-format kv, those keys are written as `name=value`
+    // pairs rather than as JSON.
+    kv_output: bool,
+    expr: Option<Program>,
+    // --where conditions a line's fields must all meet to be cut.
+    conditions: Vec<Predicate>,
+    // With --format, what each line's fields are written into.
+    template: Option<Template>,
+    // Lines seen so far across all inputs, for the expression's NR.
+    records: Cell<u64>,
+    // --subfield splits of the output fields, taken in order.
+    subfields: Vec<Subfield>,
+    explode: Option<Explode>,
+    // --map transforms of the output fields, applied in order.
+    maps: Vec<FieldMap>,
+    join: Option<JoinTable>,
+    // Where the job is in its input, and how many records it has
+    // written, for SIGUSR1.
+    progress: RefCell<Progress>,
+    written: Cell<u64>,
+    // Saves how far through the inputs the job has got.
+    checkpoint: Option<RefCell<Checkpoint>>,
+    // Whether each input starts with a line of column names, from
+    // --header or --align-headers, and the selector with the names it
+    // uses looked up in the first of them.
+    header: bool,
+    // With --pass-header, the header is cut but nothing else is done to
+    // it.
+    pass_header: bool,
+    // With -i, each input is written out on its own, and so keeps its
+    // header, rather than only the first input's being printed.
+    each_header: bool,
+    // Lines at the start of each input to ignore, from --skip-lines.
+    skip_lines: u64,
+    // Lines starting with this are comments, from --skip-comments, and
+    // are dropped, or printed as they are with --keep-comments.
+    comments: Option<String>,
+    keep_comments: bool,
+    // With --strict, a line too short for the selector is an error.
+    strict: bool,
+    // With --pad, what stands in for a selected field a line lacks.
+    pad: Option<String>,
+    // Where lines that can't be cut go, with --rejects, rather than
+    // failing the run.
+    rejects: Option<RefCell<Rejects>>,
+    // With -H, the name of the input being read, which the last stage
+    // puts before each record it writes.
+    filename: Option<RefCell<String>>,
+    // With --line-numbers, the number within its input of the line being
+    // cut, which the last stage puts before each record as a field.
+    line_number: Option<Cell<u64>>,
+    // Lines of the current input that were neither skipped nor
+    // comments, the first of them being its header.
+    lines: Cell<u64>,
+    resolved: OnceLock<FieldSelector>,
+    // What each record ends with, on input and output: a newline, NUL
+    // with -z, or the --record-sep.
+    terminator: String,
+    // Where the fields of the line being cut lie in it, kept from line
+    // to line for plain jobs.
+    offsets: RefCell<Vec<Range<usize>>>,
+    // Lines up the columns of each input by their header names.
+    alignment: Option<RefCell<Alignment>>,
+    // Drops records whose --dedup-key has been seen before.
+    distinct: Option<RefCell<Distinct>>,
+    aggregate: Option<RefCell<Box<dyn Aggregate>>>,
+    // How much the dedup set and aggregate may hold, and how long a
+    // line may be.
+    max_memory: Option<MemoryCap>,
+    passthrough: Option<Passthrough>,
+    // The --then stage that cuts this job's output again, if any.
+    then: Option<Box<CutJob>>,
+}
+
+impl Default for CutJob {
+    fn default() -> Self {
+        CutJob {
+            input_delim: Delimiter::Whitespace,
+            selector: FieldSelector::all(),
+            unit: None,
+            complement: false,
+            undelimited: Undelimited::Split,
+            output_separator: String::from(" "),
+            quote_output: false,
+            output_keys: None,
+            kv_output: false,
+            expr: None,
+            conditions: vec![],
+            template: None,
+            records: Cell::new(0),
+            explode: None,
+            subfields: vec![],
+            maps: vec![],
+            join: None,
+            progress: RefCell::default(),
+            written: Cell::new(0),
+            checkpoint: None,
+            header: false,
+            pass_header: false,
+            each_header: false,
+            skip_lines: 0,
+            comments: None,
+            keep_comments: false,
+            strict: false,
+            pad: None,
+            rejects: None,
+            filename: None,
+            line_number: None,
+            lines: Cell::new(0),
+            resolved: OnceLock::new(),
+            terminator: String::from("\n"),
+            offsets: RefCell::default(),
+            alignment: None,
+            distinct: None,
+            aggregate: None,
+            max_memory: None,
+            passthrough: None,
+            then: None,
+        }
+    }
+}
+
+// How multiple inputs are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputMode {
+    // One after another, like `cat`.
+    Sequential,
+    // In lockstep, side by side, like `paste`.
+    Paste,
+    // In lockstep, one line from each in turn.
+    Interleave,
+}
+
+// What becomes of a line without the delimiter in it.  rcut takes it
+// as a line of one field; cut prints it whole, or with -s drops it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Undelimited {
+    Split,
+    Print,
+    Suppress,
+}
+
+// How records are written: as fields joined by the output separator,
+// the same quoted as CSV where they need it, one JSON value per line,
+// or as `name=value` pairs joined by the output separator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Csv,
+    Json,
+    Kv,
+}
+
+// Where --passthrough puts the original line relative to the fields
+// cut from it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Passthrough {
+    Append,
+    Prepend,
+}
+
+// Settings that govern how rcut runs rather than what a CutJob does.
+#[derive(Debug)]
+struct RunOptions {
+    dry_run: bool,
+    verify_against_cut: bool,
+    input_mode: InputMode,
+    join: Option<JoinSpec>,
+    tee: Option<OsString>,
+    output: Option<OsString>,
+    compress: Option<Codec>,
+    in_place: Option<String>,
+    files_from: Option<FileList>,
+    recursive: Option<Filter>,
+    resume: bool,
+    decompress: bool,
+    mmap: bool,
+    throttle: Option<Rate>,
+    timeout: Option<Duration>,
+    seed: Seed,
+    audit: Option<RefCell<Audit>>,
+    verbosity: u8,
+    error_format: ErrorFormat,
+    warnings: Vec<Diagnostic>,
+}
+
+/// Parse a selector: comma-separated fields and ranges like `1,3-5`.
+/// `3-` runs to the end of the line, as in cut.  Ranges can also be
+/// written `3..5`, where either end can be left off, `..5` being fields
+/// 1 to 5, and either end can count back from the end of the line.  A
+/// bare `-N` on its own is the Nth field from the end, and `n` or `last`
+/// is the final field, so `2-n-1` drops the first and last fields and
+/// `last-2..last` keeps the last three.  A step after a
+/// colon takes every Nth field of a range, as `1-20:2` or `2-:3` do,
+/// and a range after a `^` is left out of the rest, as in `1-10,^4`.
+pub fn field_parser<S: Into<String>>(s: S) -> Result<FieldSelector> {
+    let s = s.into();
+    if s.starts_with('-') && !s.contains("..") {
+        return Ok(FieldSelector {
+            fields: vec![FieldRange::new_val(s.parse::<isize>()?)],
+            bare_negative: true,
+            ..Default::default()
+        });
+    }
+    let mut selector = FieldSelector::default();
+    for t in s.split(',') {
+        let (t, excluded) = match t.strip_prefix('^') {
+            Some(t) => (t, true),
+            None => (t, false),
+        };
+        let (t, step) = match t.split_once(':') {
+            Some((t, step)) => (t, step.parse::<usize>()?),
+            None => (t, 1),
+        };
+        if step == 0 {
+            bail!(tr("a range can't step by 0"));
+        }
+        let range = range_parser(t)?.stepped(step);
+        match excluded {
+            true => selector.excluded.push(range),
+            false => selector.fields.push(range),
+        }
+    }
+    Ok(selector)
+}
+
+// Parse one item of a selector, without its step.
+fn range_parser(t: &str) -> Result<FieldRange> {
+    if let Some((start, stop)) = t.split_once("..") {
+        let start = if start.is_empty() {
+            1
+        } else {
+            end_parser(start)?
+        };
+        let stop = if stop.is_empty() {
+            OPEN
+        } else {
+            end_parser(stop)?
+        };
+        return Ok(FieldRange::new_span(start, stop));
+    }
+    if let Some(start) = t.strip_suffix('-') {
+        return Ok(FieldRange::new_span(end_parser(start)?, OPEN));
+    }
+    // `n-1` is one field, and `2-n-1` or `n-2-n` a range split at
+    // whichever dash leaves an end on both sides.
+    if from_end(t) {
+        if let Ok(end) = end_parser(t) {
+            return Ok(FieldRange::new_val(end));
+        }
+    }
+    let split = t
+        .match_indices('-')
+        .find_map(|(at, _)| Some((end_parser(&t[..at]).ok()?, end_parser(&t[at + 1..]).ok()?)));
+    if let Some((start, stop)) = split {
+        return Ok(FieldRange::new_span(start, stop));
+    }
+    let (start, stop) = t.split_once('-').unwrap_or((t, t));
+    Ok(FieldRange::new_span(end_parser(start)?, end_parser(stop)?))
+}
+
+// Whether an end of a range counts back from the end of the line, as
+// `n`, `last`, `n-1` and `last-2` do.
+fn from_end(t: &str) -> bool {
+    t.starts_with("last") || t.starts_with('n')
+}
+
+// Parse one end of a range: a field index, or `n` or `last` for the
+// final field and `n-N` or `last-N` for the one N before it, which come
+// out as the negative index counting back from the end that they stand
+// for.
+fn end_parser(t: &str) -> Result<isize> {
+    let back = match t.strip_prefix("last").or_else(|| t.strip_prefix('n')) {
+        Some(back) => back,
+        None => return Ok(t.parse()?),
+    };
+    match back {
+        "" => Ok(-1),
+        back => match back.strip_prefix('-') {
+            Some(back) => Ok(-1 - back.parse::<u32>()? as isize),
+            None => bail!(tr("expected n, last, n-N or last-N")),
+        },
+    }
+}
+
+// Parse a -f selector, where an item can also be a column name, for
+// --header to look up.  Anything with more than digits, `n`, `last` and
+// the punctuation of ranges in it is a name, so a column called `n` or
+// `last` has to be picked by its index.
+fn named_field_parser(s: &str) -> Result<FieldSelector> {
+    let is_name = |item: &str| {
+        item.split(|c: char| c.is_ascii_digit() || matches!(c, '-' | '.' | ':' | '^'))
+            .any(|word| !matches!(word, "" | "n" | "last"))
+    };
+    if !s.split(',').any(is_name) {
+        return field_parser(s);
+    }
+    let mut selector = FieldSelector::default();
+    for item in s.split(',') {
+        if is_name(item) {
+            selector
+                .names
+                .push((selector.fields.len(), String::from(item)));
+            selector.fields.push(FieldRange::new_val(0));
+        } else {
+            let parsed = field_parser(item)?;
+            selector.fields.extend(parsed.fields);
+            selector.excluded.extend(parsed.excluded);
+        }
+    }
+    Ok(selector)
+}
+
+// A bare `-N` means something else to cut, which matters to anyone
+// bringing a cut command line over.
+fn bare_negative_warning(selector: &FieldSelector) -> Option<Diagnostic> {
+    if !selector.bare_negative {
+        return None;
+    }
+    let first = selector.fields.first()?.start.to_string();
+    let count = first.trim_start_matches('-');
+    let message = trf(
+        "'{}' is field {} from the end, but cut reads it as fields 1-{}: write ..{} for those, or {}..{} to keep this meaning without a warning",
+        &[&first, count, count, count, &first, &first],
+    );
+    Some(Diagnostic::new("ambiguous-selector", message))
+}
+
+// Read an --order-from file: one field index, range or, for --header
+// to look up, column name per line, in output order.  Blank lines and
+// lines starting with # are skipped.
+fn load_order(reader: impl BufRead) -> Result<FieldSelector> {
+    let mut order = FieldSelector::default();
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line.context(InputLine(lineno as u64 + 1))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let selector = cli::parse_fields(line).context(InputLine(lineno as u64 + 1))?;
+        let at = order.fields.len();
+        order
+            .names
+            .extend(selector.names.into_iter().map(|(i, name)| (at + i, name)));
+        order.fields.extend(selector.fields);
+        order.excluded.extend(selector.excluded);
+    }
+    if order.fields.is_empty() {
+        bail!(tr("no fields to order by"));
+    }
+    Ok(order)
+}
+
+// Read a --widths-from file: column widths, one or more to a line,
+// skipping blank lines and lines starting with # as --order-from does.
+fn load_widths(reader: impl BufRead) -> Result<Vec<usize>> {
+    let mut widths = vec![];
+    for (lineno, line) in reader.lines().enumerate() {
+        let line = line.context(InputLine(lineno as u64 + 1))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        for width in line.split(',') {
+            widths.push(cli::parse_width(width).context(InputLine(lineno as u64 + 1))?);
+        }
+    }
+    if widths.is_empty() {
+        bail!(tr("no column widths given"));
+    }
+    Ok(widths)
+}
+
+// Bare positionals are treated as selectors when -f is absent, which
+// is surprising if one of them also names a file on disk.  Return a
+// warning for every positional that could plausibly be either.
+fn ambiguous_positionals(args: &[OsString]) -> Vec<Diagnostic> {
+    args.iter()
+        .filter_map(|arg| arg.to_str())
+        .filter(|arg| field_parser(*arg).is_ok() && Path::new(arg).exists())
+        .map(|arg| {
+            let message = trf(
+                "'{}' is both a field selector and an existing file; treating it as a selector (use `-- {}` to read it as a file)",
+                &[arg, arg],
+            );
+            Diagnostic::new("ambiguous-positional", message).with_file(arg)
+        })
+        .collect()
+}
+
+fn parse_cli<S>(params: Option<Vec<S>>) -> Result<Cli>
+where
+    S: Into<OsString> + Clone + std::fmt::Debug,
+{
+    let matcher = Cli::localized_command();
+    let args: Vec<OsString> = match params {
+        Some(p) => p.into_iter().map(Into::into).collect(),
+        None => std::env::args_os().collect(),
+    };
+    let matches = matcher.try_get_matches_from(spell_out_in_place(args))?;
+    Ok(Cli::from_arg_matches(&matches)?)
+}
+
+// Clap can't take an optional value stuck to a short flag, as sed's
+// -i.bak is, so that's spelled out as --in-place=.bak before it sees it.
+fn spell_out_in_place(mut args: Vec<OsString>) -> Vec<OsString> {
+    for arg in args.iter_mut().skip(1) {
+        let suffix = match arg.to_str() {
+            Some("--") => break,
+            Some(arg) => arg
+                .strip_prefix("-i")
+                .filter(|suffix| !suffix.is_empty() && !suffix.starts_with('=')),
+            None => None,
+        };
+        if let Some(suffix) = suffix {
+            *arg = OsString::from(format!("--in-place={}", suffix));
+        }
+    }
+    args
+}
+
+// Parse a cutting command line in one go, as the tests do.
+#[cfg(test)]
+fn parse_command_line<S>(params: Option<Vec<S>>) -> Result<(CutJob, Vec<OsString>, RunOptions)>
+where
+    S: Into<OsString> + Clone + std::fmt::Debug,
+{
+    job_from_args(parse_cli(params)?.into_args()?)
+}
+
+// Unless told otherwise, join output fields the way they were split.
+fn default_separator(separator: Option<String>, delimiter: &Option<Delimiter>) -> String {
+    separator.unwrap_or_else(|| match delimiter {
+        Some(Delimiter::String(ref s)) => s.clone(),
+        Some(Delimiter::Csv(sep)) => sep.to_string(),
+        _ => String::from(" "),
+    })
+}
+
+// A delimiter that splits on any one of `chars`, or on a run of them
+// with --collapse-delims.
+fn any_of(chars: &str, collapse: bool) -> Result<Delimiter> {
+    let class: String = chars
+        .chars()
+        .map(|c| regex::escape(&c.to_string()))
+        .collect();
+    let runs = if collapse { "+" } else { "" };
+    Ok(Delimiter::Regex(Regex::new(&format!(
+        "[{}]{}",
+        class, runs
+    ))?))
+}
+
+// Whether -d and -o have their backslash escapes read, with --escapes.
+#[derive(Clone, Copy)]
+struct Escapes(bool);
+
+impl Escapes {
+    fn delimiter(self, delimiter: Option<Delimiter>) -> Result<Option<Delimiter>> {
+        match delimiter {
+            Some(Delimiter::String(s)) if self.0 => {
+                Ok(Some(cli::parse_delimiter(&cli::unescape(&s)?)?))
+            }
+            delimiter => Ok(delimiter),
+        }
+    }
+
+    fn separator(self, separator: Option<String>) -> Result<Option<String>> {
+        match separator {
+            Some(s) if self.0 => Ok(Some(cli::unescape(&s)?)),
+            separator => Ok(separator),
+        }
+    }
+}
+
+fn job_from_args(cli: cli::Args) -> Result<(CutJob, Vec<OsString>, RunOptions)> {
+    let mut warnings = vec![];
+    // An expression does its own selecting, so positionals are files.
+    // So they are for --paste and --interleave, which are for merging
+    // files, every field of each unless -f says otherwise.
+    let order = match cli.order_from {
+        Some(ref path) => {
+            let name = path.to_string_lossy().into_owned();
+            Some(load_order(open_input(path)?).with_context(|| InputFile(name))?)
+        }
+        None => None,
+    };
+    let widths = match cli.widths_from {
+        Some(ref path) => {
+            let name = path.to_string_lossy().into_owned();
+            load_widths(open_input(path)?).with_context(|| InputFile(name))?
+        }
+        None => cli.widths,
+    };
+    let unit = if cli.bytes.is_some() {
+        Some(Unit::Bytes)
+    } else if cli.characters.is_some() {
+        Some(Unit::Characters)
+    } else {
+        None
+    };
+    let (selector, args) = match cli.fields.or(order).or(cli.bytes).or(cli.characters) {
+        Some(selector) => (selector, cli.args.into_iter().chain(cli.files).collect()),
+        None if cli.expr.is_some() || cli.format.is_some() || cli.paste || cli.interleave => (
+            FieldSelector::all(),
+            cli.args.into_iter().chain(cli.files).collect(),
+        ),
+        None if cli.args.is_empty() => (FieldSelector::all(), cli.files),
+        None => {
+            warnings.extend(ambiguous_positionals(&cli.args));
+            let selector = cli
+                .args
+                .iter()
+                .map(|s| s.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(",");
+            (cli::parse_selector(&selector)?, cli.files)
+        }
+    };
+
+    warnings.extend(bare_negative_warning(&selector));
+    // With --json or --logfmt the selector names the keys to pick out of
+    // each line, and every field they give is kept.
+    let mut keyed = None;
+    let mut keys = None;
+    let selector = if cli.logfmt {
+        if selector.fields.is_empty() || selector.names.len() < selector.fields.len() {
+            let message = format!(
+                "{}\n",
+                tr("--logfmt selects keys by name, like -f level,msg")
+            );
+            return Err(clap::Error::raw(clap::ErrorKind::ValueValidation, message).into());
+        }
+        let names: Vec<String> = selector.names.into_iter().map(|(_, name)| name).collect();
+        keys = Some(names.clone());
+        keyed = Some(Delimiter::logfmt(names));
+        FieldSelector::all()
+    } else if cli.json {
+        if selector.fields.is_empty() || selector.names.len() < selector.fields.len() {
+            let message = format!(
+                "{}\n",
+                tr("--json selects keys by name, like -f user.id,msg")
+            );
+            return Err(clap::Error::raw(clap::ErrorKind::ValueValidation, message).into());
+        }
+        let paths: Vec<String> = selector.names.into_iter().map(|(_, name)| name).collect();
+        keys = Some(paths.clone());
+        keyed = Some(Delimiter::json(paths)?);
+        FieldSelector::all()
+    } else {
+        selector
+    };
+    let header = cli.header || cli.align_headers || cli.pass_header;
+    // With --extract a named group can be selected by its name.
+    let selector = match cli.extract {
+        Some(Delimiter::Extract(ref re)) if !header && !selector.names.is_empty() => {
+            let names: Vec<&str> = re
+                .capture_names()
+                .skip(1)
+                .map(|name| name.unwrap_or_default())
+                .collect();
+            if let Some((_, name)) = selector
+                .names
+                .iter()
+                .find(|(_, name)| !names.contains(&name.as_str()))
+            {
+                let message = trf("the pattern has no group named '{}'", &[name]);
+                let message = format!("{}\n", message);
+                return Err(clap::Error::raw(clap::ErrorKind::ValueValidation, message).into());
+            }
+            selector.resolve_names(&names)?
+        }
+        _ => selector,
+    };
+    if cli.pad.is_some() && selector.needs() > MAX_PADDED {
+        bail!(trf(
+            "--pad fills in no more than {} fields, which the selector goes past",
+            &[&MAX_PADDED.to_string()]
+        ));
+    }
+    if let (Some((_, name)), false) = (selector.names.first(), header) {
+        let message = trf(
+            "'{}' isn't a field number; give --header to select columns by name",
+            &[name],
+        );
+        let message = format!("{}\n", message);
+        return Err(clap::Error::raw(clap::ErrorKind::ValueValidation, message).into());
+    }
+
+    if cli.checkpoint.is_some() && args.is_empty() {
+        bail!(tr(
+            "--checkpoint needs input files, since stdin can't be resumed"
+        ));
+    }
+
+    // With --escapes, -d and -o can spell a tab or another character
+    // that's awkward to type.
+    let escapes = Escapes(cli.escapes);
+    let output_separator = escapes.separator(cli.output_separator)?;
+    // CSV is split on its separator, a comma unless -d says otherwise.
+    // -t is a tab for -d, and so for -o unless that says otherwise.
+    let delimiter = match cli.tsv {
+        true => Some(Delimiter::String(String::from("\t"))),
+        false => escapes.delimiter(cli.delimiter)?,
+    };
+    let delimiter = match delimiter {
+        Some(Delimiter::String(sep)) if cli.csv => {
+            let mut chars = sep.chars();
+            match (chars.next(), chars.next()) {
+                (Some(sep), None) => Some(Delimiter::Csv(sep)),
+                _ => bail!(tr("--csv fields are separated by a single character")),
+            }
+        }
+        None if cli.csv => Some(Delimiter::Csv(',')),
+        None => keyed,
+        delimiter => delimiter,
+    };
+    // --any-delim splits on any of its characters, and joins with the
+    // first of them unless -o says otherwise.
+    let any_delim = escapes.separator(cli.any_delim)?;
+    let output_separator = output_separator.or_else(|| {
+        let first = any_delim.as_deref()?.chars().next()?;
+        Some(first.to_string())
+    });
+    // CSV comes out separated by commas unless -o, or a --then stage,
+    // says otherwise.
+    let csv_output = cli.output_format == OutputFormat::Csv && cli.then.is_empty();
+    let output_separator = output_separator.or_else(|| csv_output.then(|| String::from(",")));
+    // kv pairs are set apart by spaces, as logfmt sets them.
+    let kv_output = cli.output_format == OutputFormat::Kv && cli.then.is_empty();
+    let output_separator = output_separator.or_else(|| kv_output.then(|| String::from(" ")));
+    // The columns of a table are set apart by two spaces, as column -t
+    // sets them.
+    let output_separator = output_separator.or_else(|| cli.table.then(|| String::from("  ")));
+    let any_delim = match any_delim {
+        Some(chars) => Some(any_of(&chars, cli.collapse_delims)?),
+        None => None,
+    };
+    // Pieces cut by position go back together as they were.
+    let output_separator = match unit {
+        Some(_) => output_separator.unwrap_or_default(),
+        None => default_separator(output_separator, &delimiter),
+    };
+    let fixed = (!widths.is_empty()).then(|| Delimiter::widths(&widths));
+    let input_delim = match delimiter
+        .or(cli.regex_delimiter)
+        .or(any_delim)
+        .or(fixed)
+        .or(cli.extract)
+    {
+        Some(delim) => delim,
+        None if cli.infer_columns => Delimiter::inferred(),
+        None => Delimiter::Whitespace,
+    };
+    // Runs of a delimiter count as one with --collapse-delims, though a
+    // line starting with it still has an empty first field, as with
+    // awk's FS=",+".  With --no-collapse whitespace doesn't run together.
+    let input_delim = match input_delim {
+        Delimiter::String(s) if cli.collapse_delims => {
+            Delimiter::Regex(Regex::new(&format!("(?:{})+", regex::escape(&s)))?)
+        }
+        Delimiter::Whitespace if cli.no_collapse => Delimiter::Regex(Regex::new(r"\s")?),
+        delim => delim,
+    };
+    let input_delim = match cli.max_fields {
+        Some(most) => Delimiter::Limited {
+            delim: Box::new(input_delim),
+            most,
+            from_end: cli.rsplit,
+        },
+        None => input_delim,
+    };
+
+    let terminator = match cli.record_sep {
+        Some(sep) => sep,
+        None if cli.zero_terminated => String::from("\0"),
+        None => String::from("\n"),
+    };
+    // Each --then stage is a CutJob of its own, fed by the one before.
+    let mut then = None;
+    for stage in cli.then.into_iter().rev() {
+        let delimiter = escapes.delimiter(stage.delimiter)?;
+        then = Some(Box::new(CutJob {
+            output_separator: default_separator(
+                escapes.separator(stage.output_separator)?,
+                &delimiter,
+            ),
+            input_delim: delimiter.unwrap_or(Delimiter::Whitespace),
+            selector: stage.selector,
+            terminator: terminator.clone(),
+            then,
+            ..Default::default()
+        }));
+    }
+
+    // The header, unless it's passed straight through, is the first
+    // record an aggregate sees, which those that summarize name their
+    // columns from.
+    let named = header && !cli.pass_header;
+    let aggregate: Option<Box<dyn Aggregate>> = if let Some(key) = cli.group_by {
+        let aggs = if cli.agg.is_empty() {
+            vec![aggregate::AggFn::Count]
+        } else {
+            cli.agg
+        };
+        Some(Box::new(
+            GroupBy::new(key, aggs)
+                .with_locale(cli.numeric_locale)
+                .with_header(named),
+        ))
+    } else if let Some(spec) = cli.pivot {
+        Some(Box::new(Pivot::new(spec).with_locale(cli.numeric_locale)))
+    } else if cli.transpose {
+        Some(Box::new(Transpose::default()))
+    } else if cli.tally {
+        Some(Box::new(Count::default().with_header(named)))
+    } else if cli.stats {
+        Some(Box::new(Stats::new(named).with_locale(cli.numeric_locale)))
+    } else if let (Some(key), Keep::Last) = (cli.dedup_key.clone(), cli.keep) {
+        Some(Box::new(KeepLast::new(key).with_header(named)))
+    } else {
+        None
+    };
+    let aggregate = match cli.sort_by {
+        Some(spec) => Some(Box::new(
+            Sort::new(spec, aggregate)
+                .with_locale(cli.numeric_locale)
+                .with_header(named),
+        ) as Box<dyn Aggregate>),
+        None => aggregate,
+    };
+    let aggregate = match cli.table {
+        true => Some(Box::new(Table::new(aggregate)) as Box<dyn Aggregate>),
+        false => aggregate,
+    };
+
+    // --unique and --unique-consecutive key on every selected field.
+    let distinct = match (cli.dedup_key, cli.keep) {
+        (Some(key), Keep::First) => Some(Distinct::new(key)),
+        _ if cli.unique => Some(Distinct::new(FieldSelector::all())),
+        _ if cli.unique_consecutive => Some(Distinct::consecutive(FieldSelector::all())),
+        _ => None,
+    }
+    .map(|distinct| distinct.with_header(named));
+
+    // JSON and kv output are keyed by column name where the names are
+    // known: read from the header the records start with, or the --json
+    // or --logfmt keys if the records are just those.
+    let output_keys = match cli.output_format {
+        OutputFormat::Text | OutputFormat::Csv => None,
+        OutputFormat::Json | OutputFormat::Kv if header => Some(OnceLock::new()),
+        OutputFormat::Json | OutputFormat::Kv => {
+            let plain = cli.expr.is_none()
+                && cli.explode.is_none()
+                && cli.join.is_none()
+                && cli.passthrough.is_none()
+                && then.is_none()
+                && aggregate.is_none();
+            let keys = keys.filter(|_| plain).unwrap_or_default();
+            Some(OnceLock::from(keys))
+        }
+    };
+
+    let mut cut_job = CutJob {
+        input_delim,
+        selector,
+        unit,
+        complement: cli.complement,
+        undelimited: match (cli.only_delimited, cli.print_undelimited) {
+            (true, _) => Undelimited::Suppress,
+            (_, true) => Undelimited::Print,
+            _ => Undelimited::Split,
+        },
+        output_separator,
+        quote_output: cli.csv,
+        expr: cli
+            .expr
+            .map(|program| program.with_locale(cli.numeric_locale)),
+        conditions: cli
+            .r#where
+            .into_iter()
+            .map(|predicate| predicate.with_locale(cli.numeric_locale))
+            .collect(),
+        template: cli.format,
+        explode: cli.explode,
+        subfields: cli.subfield,
+        maps: cli.map,
+        passthrough: cli.passthrough,
+        then,
+        terminator,
+        progress: RefCell::default(),
+        written: Cell::new(0),
+        checkpoint: cli
+            .checkpoint
+            .as_ref()
+            .map(|path| RefCell::new(Checkpoint::new(path))),
+        header,
+        pass_header: cli.pass_header,
+        each_header: cli.in_place.is_some(),
+        skip_lines: cli.skip_lines,
+        comments: cli.skip_comments,
+        keep_comments: cli.keep_comments,
+        strict: cli.strict,
+        pad: cli.pad,
+        rejects: cli.rejects.map(|path| RefCell::new(Rejects::new(path))),
+        alignment: cli
+            .align_headers
+            .then(|| RefCell::new(Alignment::default())),
+        distinct: distinct.map(RefCell::new),
+        aggregate: aggregate.map(RefCell::new),
+        max_memory: cli.max_memory.map(MemoryCap),
+        ..Default::default()
+    };
+    cut_job.last_stage_mut().output_keys = output_keys;
+    cut_job.last_stage_mut().kv_output = cli.output_format == OutputFormat::Kv;
+    // It's whatever the last stage writes that has to read back as CSV.
+    if cli.output_format == OutputFormat::Csv {
+        cut_job.last_stage_mut().quote_output = true;
+    }
+    if cli.with_filename {
+        cut_job.last_stage_mut().filename = Some(RefCell::new(String::from("<stdin>")));
+    }
+    if cli.line_numbers {
+        cut_job.last_stage_mut().line_number = Some(Cell::new(0));
+    }
+
+    let verbosity = if cli.debug {
+        cli.verbose.max(2)
+    } else {
+        cli.verbose
+    };
+
+    let seed = Seed::new(cli.seed);
+    let run_options = RunOptions {
+        dry_run: cli.dry_run,
+        verify_against_cut: cli.verify_against_cut,
+        input_mode: if cli.paste {
+            InputMode::Paste
+        } else if cli.interleave {
+            InputMode::Interleave
+        } else {
+            InputMode::Sequential
+        },
+        join: cli.join.map(|file| {
+            let (left, right) = cli.on.unwrap_or((1, 1));
+            JoinSpec {
+                file,
+                left,
+                right,
+                fields: cli.join_fields,
+            }
+        }),
+        tee: cli.tee,
+        compress: cli.compress.or_else(|| {
+            cli.output
+                .as_ref()
+                .and_then(|path| Codec::from_name(Path::new(path)))
+        }),
+        output: cli.output,
+        in_place: cli.in_place,
+        files_from: match (cli.files_from, cli.files0_from) {
+            (Some(path), _) => Some(FileList {
+                path,
+                terminator: b'\n',
+            }),
+            (None, Some(path)) => Some(FileList {
+                path,
+                terminator: 0,
+            }),
+            (None, None) => None,
+        },
+        recursive: cli.recursive.then(|| Filter {
+            include: cli.include,
+            exclude: cli.exclude,
+        }),
+        resume: cli.resume,
+        decompress: cli.decompress,
+        mmap: cli.mmap,
+        throttle: cli.throttle,
+        timeout: cli.timeout,
+        seed,
+        audit: cli.audit.map(|path| RefCell::new(Audit::new(path, seed))),
+        verbosity,
+        error_format: cli.error_format,
+        warnings,
+    };
+
+    Ok((cut_job, args, run_options))
+}
+
+// The whole of the rcut command, which the binary just calls.  It's in
+// the library so that the fuzz targets can get at the parsers.
+pub fn main() {
+    let error_format = ErrorFormat::sniff(std::env::args_os());
+    if let Err(err) = try_main() {
+        if let Some(clap_err) = err.downcast_ref::<clap::Error>() {
+            // --help and --version arrive as "errors", and clap knows
+            // best how to print its own usage messages.
+            if error_format == ErrorFormat::Text || !clap_err.use_stderr() {
+                clap_err.exit();
+            }
+        }
+        diagnostics::error(&err, error_format);
+        if err.is::<Interrupted>() {
+            std::process::exit(signals::INTERRUPTED_STATUS);
+        }
+        if err.is::<TimedOut>() {
+            std::process::exit(signals::TIMED_OUT_STATUS);
+        }
+        std::process::exit(1);
+    }
+}
+
+fn try_main() -> Result<()> {
+    let mut cli = parse_cli::<OsString>(None)?;
+    // The picker ends with a command line, which is either printed or
+    // run here as if it had been typed.
+    if let Some(Mode::Pick(ref pick)) = cli.mode {
+        let argv = match pick::pick(pick)? {
+            Some(argv) => argv,
+            None => return Ok(()),
+        };
+        if !pick.run {
+            writeln!(io::stdout(), "{}", pick::shell_command(&argv))
+                .or_else(|err| muffle_epipe(err.into()))?;
+            return Ok(());
+        }
+        cli = parse_cli(Some(argv))?;
+    }
+    if let Some(Mode::Top(ref top)) = cli.mode {
+        return top::top(top).or_else(muffle_epipe);
+    }
+    if let Some(Mode::Serve(ref serve)) = cli.mode {
+        init_logging(serve.verbose.max(1));
+        return serve::serve(serve);
+    }
+    let sample = cli.sample_spec();
+    let (mut cut_job, args, run_options) = job_from_args(cli.into_args()?)?;
+    init_logging(run_options.verbosity);
+    if let Some(spec) = sample {
+        info!(seed = run_options.seed.0, "sampling");
+        let inner = cut_job.aggregate.take().map(RefCell::into_inner);
+        let named = cut_job.header && !cut_job.pass_header;
+        cut_job.aggregate = Some(RefCell::new(Box::new(
+            Sample::new(spec, run_options.seed, inner).with_header(named),
+        )));
+    }
+    if let Some(ref spec) = run_options.join {
+        let name = spec.file.to_string_lossy().into_owned();
+        let table = JoinTable::load(spec, &cut_job.input_delim, open_input(&spec.file)?)
+            .with_context(|| InputFile(name))?;
+        cut_job.join = Some(table);
+    }
+    for warning in &run_options.warnings {
+        diagnostics::warn(warning, run_options.error_format);
+    }
+    debug!(?cut_job, "resolved job");
+
+    // All output must go through io::stdout(): on Windows, std detects a
+    // console and writes through WriteConsoleW, so non-ASCII fields show
+    // up correctly regardless of the console code page, while
+    // redirected output is left as raw UTF-8.  Writing to the raw handle
+    // instead would bring back mangled output on legacy consoles.
+    let stdout = io::stdout();
+    let destination = match run_options.output {
+        _ if run_options.dry_run => Destination::Stdout(stdout.lock()),
+        Some(ref path) => Destination::file(OutputFile::create(path)?, run_options.compress)?,
+        None => Destination::stdout(stdout.lock(), run_options.compress)?,
+    };
+    let mut stdout = Throttle::new(
+        BufWriter::new(destination),
+        run_options.throttle,
+        cut_job.terminator.as_bytes(),
+    );
+
+    if run_options.dry_run {
+        return cut_job
+            .describe(&args, &run_options, &mut stdout)
+            .or_else(muffle_epipe);
+    }
+    if run_options.verify_against_cut {
+        return verify::verify(&cut_job, &args, &mut stdout).or_else(muffle_epipe);
+    }
+
+    signals::install()?;
+    if let Some(limit) = run_options.timeout {
+        signals::start_timer(limit);
+    }
+    let outcome = run(&cut_job, &args, &run_options, &mut stdout)
+        .and_then(|_| cut_job.finish(&mut stdout))
+        .and_then(|_| {
+            stdout.flush()?;
+            stdout.get_mut().get_mut().commit()
+        });
+    // The audit is written even when the run fails, saying how.
+    if let Some(ref audit) = run_options.audit {
+        audit.borrow().write(&cut_job, &outcome)?;
+    }
+    if let Err(err) = outcome {
+        muffle_epipe(err)?;
+    }
+    info!(warnings = run_options.warnings.len(), "finished");
+    Ok(())
+}
+
+// Process each named input in turn, then those in the --files-from
+// list as it's read, or stdin if there are none.  With -r, a directory
+// stands for the files under it.
+fn run(
+    cut_job: &CutJob,
+    args: &[OsString],
+    run_options: &RunOptions,
+    output: &mut impl Write,
+) -> Result<()> {
+    if run_options.input_mode != InputMode::Sequential && !args.is_empty() {
+        let readers = args
+            .iter()
+            .map(|filename| {
+                Ok((
+                    filename.to_string_lossy().into_owned(),
+                    open_input_sniffing(filename, run_options.decompress)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let records = cut_job.process_lockstep(readers, run_options.input_mode, output)?;
+        if let Some(ref audit) = run_options.audit {
+            for filename in args {
+                audit.borrow_mut().input(&filename.to_string_lossy(), None);
+            }
+        }
+        info!(records, "finished reading in lockstep");
+        return Ok(());
+    }
+    let mut tee = match run_options.tee {
+        Some(ref path) => {
+            let name = path.to_string_lossy().into_owned();
+            Some(BufWriter::new(
+                File::create(path).with_context(|| InputFile(name))?,
+            ))
+        }
+        None => None,
+    };
+    if run_options.in_place.is_some() && cut_job.aggregate.is_some() {
+        bail!(tr(
+            "-i writes each file out as it's read, which can't be done while holding records back"
+        ));
+    }
+    if args.is_empty() && run_options.files_from.is_none() {
+        if run_options.in_place.is_some() {
+            bail!(tr("-i rewrites files, so it needs some to rewrite"));
+        }
+        info!("reading stdin");
+        cut_job.begin_input("<stdin>");
+        let input = Tee::new(
+            Input::stdin(run_options.decompress)?,
+            tee.as_mut().map(|t| t as &mut dyn Write),
+        );
+        let records = cut_job.process_reader(input, output)?;
+        if let Some(ref audit) = run_options.audit {
+            audit.borrow_mut().input("<stdin>", Some(records));
+        }
+        info!(records, "finished stdin");
+    }
+    let resume = match cut_job.checkpoint {
+        Some(ref checkpoint) if run_options.resume => checkpoint.borrow().load()?,
+        _ => None,
+    };
+    let moved = |position: &Position| {
+        format_err!(trf(
+            "the checkpoint is for '{}', which is not input {} this time",
+            &[&position.input, &(position.index + 1).to_string()]
+        ))
+    };
+    let listed = match run_options.files_from {
+        Some(ref list) => Some(list.names()?),
+        None => None,
+    };
+    let inputs = args
+        .iter()
+        .cloned()
+        .map(Ok)
+        .chain(listed.into_iter().flatten())
+        .flat_map(|input| -> Box<dyn Iterator<Item = Result<OsString>>> {
+            match (input, &run_options.recursive) {
+                (Ok(input), Some(filter)) if Path::new(&input).is_dir() => {
+                    Box::new(Walk::new(Path::new(&input), filter))
+                }
+                (input, _) => Box::new(std::iter::once(input)),
+            }
+        });
+    let mut resumed = resume.is_none();
+    for (index, filename) in inputs.enumerate() {
+        let filename = filename?;
+        let name = filename.to_string_lossy();
+        let start = match resume {
+            Some(ref position) if index < position.index => continue,
+            Some(ref position) if index == position.index => {
+                if name != position.input.as_str() {
+                    return Err(moved(position));
+                }
+                info!(input = %position.input, offset = position.offset, "resuming");
+                resumed = true;
+                position.offset
+            }
+            _ => 0,
+        };
+        if Path::new(&filename).is_dir() {
+            bail!(trf(
+                "'{}' is a directory, which only -r cuts the files of",
+                &[&name]
+            ));
+        }
+        let mut reader = open_input_sniffing(&filename, run_options.decompress)?;
+        if start > 0 {
+            reader
+                .skip_to(start)
+                .with_context(|| InputFile(name.clone().into_owned()))?;
+        }
+        if let Some(ref checkpoint) = cut_job.checkpoint {
+            checkpoint.borrow_mut().begin(index, &name, start);
+        }
+        cut_job.begin_input(&name);
+        *cut_job.progress.borrow_mut() = Progress {
+            input: name.clone().into_owned(),
+            start,
+            offset: start,
+        };
+        let mapping = match reader {
+            Input::Plain(ref file) if tee.is_none() => Mapping::new(file.get_ref())
+                .filter(|mapping| run_options.mmap || mapping.len() >= MAP_AT_LEAST),
+            _ => None,
+        };
+        let records = match run_options.in_place {
+            // The file is written alongside and renamed over the one
+            // being read, which stays readable, and mapped, until it's
+            // closed.
+            Some(ref suffix) => {
+                let mut replacement = OutputFile::create(&filename)?;
+                if !suffix.is_empty() {
+                    replacement = replacement.with_backup(suffix);
+                }
+                // A compressed file is compressed again the same way.
+                let codec = Codec::from_name(Path::new(&filename));
+                let mut replacement = BufWriter::new(Destination::file(replacement, codec)?);
+                cut_input(cut_job, reader, mapping, start, &mut tee, &mut replacement).and_then(
+                    |records| {
+                        replacement.flush()?;
+                        replacement.get_mut().commit()?;
+                        Ok(records)
+                    },
+                )
+            }
+            None => cut_input(cut_job, reader, mapping, start, &mut tee, output),
+        }
+        .with_context(|| InputFile(name.clone().into_owned()))?;
+        if let Some(ref checkpoint) = cut_job.checkpoint {
+            checkpoint.borrow_mut().save(output)?;
+        }
+        if let Some(ref audit) = run_options.audit {
+            audit.borrow_mut().input(&name, Some(records));
+        }
+        info!(file = %name, records, "closed");
+    }
+    if let (false, Some(position)) = (resumed, &resume) {
+        return Err(moved(position));
+    }
+    if let Some(ref checkpoint) = cut_job.checkpoint {
+        checkpoint.borrow().clear()?;
+    }
+    if let (Some(mut tee), Some(path)) = (tee, run_options.tee.as_ref()) {
+        tee.flush()
+            .with_context(|| InputFile(path.to_string_lossy().into_owned()))?;
+    }
+    Ok(())
+}
+
+// Cut one input, from `start` on, reading it from memory if it's been
+// mapped.
+fn cut_input(
+    cut_job: &CutJob,
+    reader: Input,
+    mapping: Option<Mapping>,
+    start: u64,
+    tee: &mut Option<BufWriter<File>>,
+    output: &mut impl Write,
+) -> Result<u64> {
+    match mapping {
+        Some(mapping) => {
+            debug!(len = mapping.len(), "mapped");
+            cut_job.process_mapped(&mapping[(start as usize).min(mapping.len())..], output)
+        }
+        None => cut_job.process_reader(
+            Tee::new(reader, tee.as_mut().map(|t| t as &mut dyn Write)),
+            output,
+        ),
+    }
+}
+
+// Files at least this big are mapped into memory rather than read,
+// even without --mmap.
+const MAP_AT_LEAST: usize = 64 << 20;
+
+fn open_input(filename: &OsString) -> Result<Input> {
+    open_input_sniffing(filename, false)
+}
+
+// Open an input, decompressing it as its name says or, with `sniff`,
+// as its first bytes do.
+fn open_input_sniffing(filename: &OsString, sniff: bool) -> Result<Input> {
+    let name = filename.to_string_lossy();
+    let input = Input::open(Path::new(filename), sniff)
+        .with_context(|| InputFile(name.clone().into_owned()))?;
+    info!(file = %name, decoded = matches!(input, Input::Decoded(_)), "opened");
+    Ok(input)
+}
+
+// Log to stderr at a level picked by -v/-vv/-vvv, unless RUST_LOG is
+// set, in which case it wins.
+fn init_logging(verbosity: u8) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        EnvFilter::new(match verbosity {
+            0 => "warn",
+            1 => "info",
+            2 => "debug",
+            _ => "trace",
+        })
+    });
+    tracing_subscriber::fmt()
+        .with_writer(io::stderr)
+        .with_ansi(io::stderr().is_terminal())
+        .with_env_filter(filter)
+        .init();
+}
+
+// If err is actually a EPIPE, pretend things are fine; otherwise
+// propagate error.  This way if stdout is closed (say, part of a
+// pipeline) we still exit cleanly, like `cut`.
+fn muffle_epipe(err: anyhow::Error) -> Result<()> {
+    for cause in err.chain() {
+        if let Some(io_err) = cause.downcast_ref::<io::Error>() {
+            if io_err.kind() == io::ErrorKind::BrokenPipe {
+                return Ok(());
+            }
+        }
+    }
+    Err(err)
+}
+
+impl FieldRange {
+    // Human-readable form matching the selector syntax.
+    fn describe(&self) -> String {
+        match self.step {
+            1 => self.describe_span(),
+            step => format!("{}:{}", self.describe_span(), step),
+        }
+    }
+
+    fn describe_span(&self) -> String {
+        if self.start == self.stop {
+            self.start.to_string()
+        } else if self.stop == OPEN && self.start > 0 {
+            format!("{}-", self.start)
+        } else if self.stop == OPEN {
+            format!("{}..", self.start)
+        } else if self.start < 0 || self.stop < 0 {
+            format!("{}..{}", self.start, self.stop)
+        } else {
+            format!("{}-{}", self.start, self.stop)
+        }
+    }
+}
+
+impl FieldSelector {
+    /// Human-readable form matching the selector syntax.
+    pub fn describe(&self) -> String {
+        if self.fields.is_empty() && self.excluded.is_empty() {
+            return String::from("all");
+        }
+        (0..self.fields.len())
+            .map(|i| self.describe_range(i))
+            .chain(
+                self.excluded
+                    .iter()
+                    .map(|range| format!("^{}", range.describe())),
+            )
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    // The ith range, or the column name standing in for it.
+    fn describe_range(&self, i: usize) -> String {
+        match self.names.iter().find(|(at, _)| *at == i) {
+            Some((_, name)) => name.clone(),
+            None => self.fields[i].describe(),
+        }
+    }
+
+    // The selector with its column names looked up in a header, the
+    // first column of each name winning.
+    fn resolve_names(&self, header: &[&str]) -> Result<FieldSelector> {
+        let mut resolved = self.clone();
+        for (at, name) in self.names.iter() {
+            let col = header
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| format_err!(trf("there is no column named '{}'", &[name])))?;
+            resolved.fields[*at] = FieldRange::new_val(col as isize + 1);
+        }
+        resolved.names.clear();
+        Ok(resolved)
+    }
+}
+
+impl CutJob {
+    /// A job that splits lines on `input_delim` and writes the fields
+    /// `selector` picks, joined by `output_separator`.
+    pub fn new(
+        input_delim: Delimiter,
+        selector: FieldSelector,
+        output_separator: impl Into<String>,
+    ) -> Self {
+        CutJob {
+            input_delim,
+            selector,
+            output_separator: output_separator.into(),
+            ..Default::default()
+        }
+    }
+
+    // Print the fully resolved job, one setting per line, so a complex
+    // invocation can be checked without feeding it any data.
+    fn describe(
+        &self,
+        inputs: &[OsString],
+        run_options: &RunOptions,
+        output: &mut impl Write,
+    ) -> Result<()> {
+        self.describe_settings(output)?;
+        if inputs.is_empty() && run_options.files_from.is_none() {
+            writeln!(output, "input: <stdin>")?;
+        }
+        for input in inputs {
+            writeln!(output, "input: {}", input.to_string_lossy())?;
+        }
+        if let Some(ref list) = run_options.files_from {
+            writeln!(output, "inputs: {}", list.describe())?;
+        }
+        if let Some(ref filter) = run_options.recursive {
+            writeln!(output, "directories: {}", filter.describe())?;
+        }
+        if let Some(ref suffix) = run_options.in_place {
+            match suffix.as_str() {
+                "" => writeln!(output, "inputs: rewritten in place")?,
+                suffix => writeln!(
+                    output,
+                    "inputs: rewritten in place, kept with {:?} added",
+                    suffix
+                )?,
+            }
+        }
+        if let Some(codec) = run_options.compress {
+            writeln!(output, "output: compressed with {}", codec.program())?;
+        }
+        if let Some(ref path) = run_options.output {
+            writeln!(
+                output,
+                "output: {}, replaced once the run succeeds",
+                path.to_string_lossy()
+            )?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    // What --dry-run shows of the job itself, and --audit keeps: all but
+    // where it reads from and writes to.
+    fn describe_settings(&self, output: &mut impl Write) -> Result<()> {
+        match self.unit {
+            Some(unit) => writeln!(output, "unit: {}", unit.describe())?,
+            None => writeln!(output, "input delimiter: {}", self.input_delim.describe())?,
+        }
+        writeln!(output, "selector: {}", self.selector.describe())?;
+        if self.complement {
+            writeln!(output, "complement: yes")?;
+        }
+        match self.undelimited {
+            Undelimited::Split => {}
+            Undelimited::Print => writeln!(output, "undelimited lines: printed")?,
+            Undelimited::Suppress => writeln!(output, "undelimited lines: dropped")?,
+        }
+        if !self.selector.fields.is_empty() {
+            // Huge ranges are cut short, rather than listed in full, and
+            // those that depend on the length of the line or its header
+            // are left as written.
+            let mut expanded: Vec<String> = self
+                .selector
+                .fields
+                .iter()
+                .enumerate()
+                .flat_map(|(i, range)| -> Box<dyn Iterator<Item = String>> {
+                    if range.start < 1 || range.stop < 0 || range.stop == OPEN {
+                        Box::new(std::iter::once(self.selector.describe_range(i)))
+                    } else {
+                        Box::new(
+                            (range.start..=range.stop)
+                                .step_by(range.step)
+                                .map(|idx| idx.to_string()),
+                        )
+                    }
+                })
+                .take(MAX_LISTED_FIELDS + 1)
+                .collect();
+            if expanded.len() > MAX_LISTED_FIELDS {
+                expanded[MAX_LISTED_FIELDS] = String::from("...");
+            }
+            writeln!(output, "fields: {}", expanded.join(" "))?;
+        }
+        if !self.selector.excluded.is_empty() {
+            let excluded: Vec<String> = self
+                .selector
+                .excluded
+                .iter()
+                .map(FieldRange::describe)
+                .collect();
+            writeln!(output, "excluding: {}", excluded.join(" "))?;
+        }
+        if let Some(ref explode) = self.explode {
+            writeln!(
+                output,
+                "explode: field {} on {:?} into {}",
+                explode.field,
+                explode.delim,
+                explode.names.join(",")
+            )?;
+        }
+        for subfield in &self.subfields {
+            writeln!(output, "subfield: {}", subfield.describe())?;
+        }
+        for map in &self.maps {
+            writeln!(output, "map: {}", map.describe())?;
+        }
+        if let Some(ref program) = self.expr {
+            writeln!(output, "expression: {}", program.source())?;
+        }
+        for predicate in &self.conditions {
+            writeln!(output, "where: {}", predicate.source())?;
+        }
+        if let Some(ref template) = self.template {
+            writeln!(output, "template: {}", template.source())?;
+        }
+        if self.skip_lines > 0 {
+            writeln!(output, "skipped lines: {}", self.skip_lines)?;
+        }
+        if self.pass_header {
+            writeln!(output, "header: passed through")?;
+        }
+        if self.strict {
+            writeln!(output, "short lines: an error")?;
+        }
+        if let Some(ref pad) = self.pad {
+            writeln!(output, "missing fields: padded with {:?}", pad)?;
+        }
+        if self.rejects.is_some() {
+            writeln!(output, "lines that can't be cut: rejected")?;
+        }
+        if self.filename.is_some() {
+            writeln!(output, "records: prefixed with the input's name")?;
+        }
+        if self.line_number.is_some() {
+            writeln!(output, "records: numbered by the line they were cut from")?;
+        }
+        if let Some(ref prefix) = self.comments {
+            let kept = if self.keep_comments {
+                "printed"
+            } else {
+                "dropped"
+            };
+            writeln!(
+                output,
+                "comments: lines starting with {:?}, {}",
+                prefix, kept
+            )?;
+        }
+        if self.alignment.is_some() {
+            writeln!(output, "align headers: yes")?;
+        }
+        writeln!(output, "output separator: {:?}", self.output_separator)?;
+        if self.terminator != "\n" {
+            writeln!(output, "records end with: {:?}", self.terminator)?;
+        }
+        let mut last = self;
+        while let Some(ref then) = last.then {
+            last = then;
+        }
+        if last.output_keys.is_some() {
+            let format = if last.kv_output { "kv" } else { "json" };
+            writeln!(output, "output format: {}", format)?;
+        }
+        if let Some(passthrough) = self.passthrough {
+            writeln!(output, "passthrough: {:?}", passthrough)?;
+        }
+        if let Some(ref distinct) = self.distinct {
+            writeln!(output, "distinct: {:?}", distinct.borrow())?;
+        }
+        if let Some(ref aggregate) = self.aggregate {
+            writeln!(output, "aggregate: {:?}", aggregate.borrow())?;
+        }
+        let mut then = &self.then;
+        while let Some(ref stage) = then {
+            writeln!(
+                output,
+                "then: delimiter {}, selector {}, output separator {:?}",
+                stage.input_delim.describe(),
+                stage.selector.describe(),
+                stage.output_separator
+            )?;
+            then = &stage.then;
+        }
+        Ok(())
+    }
+
+    // The selector, with any column names in it looked up.
+    fn selector(&self) -> &FieldSelector {
+        self.resolved.get().unwrap_or(&self.selector)
+    }
+
+    // Split a line on the Delimiter and pick out the fields named by the
+    // Selector, in selector order.
+    fn select_fields<'a>(&self, line: &'a str) -> Vec<Cow<'a, str>> {
+        self.selector().select(&self.input_delim.split(line))
+    }
+
+    // Cut a single line, or run the expression over it, appending the
+    // joined file's matching rows if there is one; with a join, lines
+    // without a match are dropped, as are lines the expression turns
+    // away, unless passing every line through.
+    fn write_line(&self, line: &str, output: &mut impl Write) -> Result<()> {
+        let plain = self.unit.is_none() && self.is_plain() && self.pad.is_none();
+        if plain && self.write_plain(line, output)? {
+            return Ok(());
+        }
+        // Bytes are cut as bytes, as cut does, whatever they spell; only
+        // a job that does more than print them needs the pieces as text.
+        if self.unit == Some(Unit::Bytes) && self.is_plain() {
+            return self.write_bytes(line.as_bytes(), output);
+        }
+        if let Some(unit) = self.unit {
+            self.records.set(self.records.get() + 1);
+            let pieces = positions::cut(line, self.selector(), unit, self.complement)?;
+            return self.annotate(line, &pieces, output);
+        }
+        let unquoted;
+        let line_fields = match self.input_delim.slices(line) {
+            Some(fields) => fields,
+            None => {
+                unquoted = self.input_delim.parse(line)?;
+                borrowed(&unquoted)
+            }
+        };
+        self.records.set(self.records.get() + 1);
+        // A line --extract doesn't match is left out, as grep would.
+        if line_fields.is_empty() && matches!(self.input_delim, Delimiter::Extract(_)) {
+            return Ok(());
+        }
+        if line_fields.len() < 2 {
+            match self.undelimited {
+                Undelimited::Split => {}
+                Undelimited::Print => return self.record(&[line], output),
+                Undelimited::Suppress => return Ok(()),
+            }
+        }
+        self.check_length(line_fields.len())?;
+        let line_fields = match self.alignment {
+            Some(ref alignment) => alignment.borrow().align(line_fields),
+            None => line_fields,
+        };
+        if !self
+            .conditions
+            .iter()
+            .all(|predicate| predicate.holds(&line_fields))
+        {
+            return self.pass_through(line, output);
+        }
+        let evaluated;
+        let rendered;
+        let selected = match self.expr {
+            Some(ref program) => match program.run(&line_fields, self.records.get())? {
+                Some(values) => {
+                    evaluated = values;
+                    evaluated.iter().map(String::as_str).collect()
+                }
+                None => return self.pass_through(line, output),
+            },
+            None if self.complement => self.selector().select_complement(&line_fields),
+            None => match (&self.template, &self.pad) {
+                (Some(template), _) => {
+                    rendered = template.render(&line_fields);
+                    vec![rendered.as_str()]
+                }
+                (None, Some(pad)) => self.selector().select_padded(&line_fields, &pad.as_str()),
+                (None, None) => self.selector().select(&line_fields),
+            },
+        };
+        let split;
+        let selected = if self.subfields.is_empty() {
+            selected
+        } else {
+            split = subfield::apply_subfields(&self.subfields, &selected);
+            split.iter().map(AsRef::as_ref).collect()
+        };
+        let selected = match self.explode {
+            Some(ref explode) => explode.apply(&selected),
+            None => selected,
+        };
+        let mapped;
+        let selected = if self.maps.is_empty() {
+            selected
+        } else {
+            mapped = transform::apply_maps(&self.maps, &selected);
+            mapped.iter().map(String::as_str).collect()
+        };
+        match self.join {
+            None => self.annotate(line, &selected, output),
+            Some(ref join) => {
+                let rows = join.matches(&line_fields);
+                if rows.is_empty() {
+                    return self.pass_through(line, output);
+                }
+                for row in rows {
+                    let fields: Vec<&str> = selected
+                        .iter()
+                        .copied()
+                        .chain(row.iter().map(String::as_str))
+                        .collect();
+                    self.annotate(line, &fields, output)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn check_length(&self, found: usize) -> Result<()> {
+        if !self.strict {
+            return Ok(());
+        }
+        let needed = self.selector().needs();
+        if found < needed {
+            bail!(MissingFields { found, needed });
+        }
+        Ok(())
+    }
+
+    // Whether the job does no more than pick fields out of each line
+    // and write them.
+    fn is_plain(&self) -> bool {
+        self.expr.is_none()
+            && self.template.is_none()
+            && self.conditions.is_empty()
+            && self.explode.is_none()
+            && self.subfields.is_empty()
+            && self.maps.is_empty()
+            && self.join.is_none()
+            && self.alignment.is_none()
+            && self.distinct.is_none()
+            && self.aggregate.is_none()
+            && self.passthrough.is_none()
+            && self.then.is_none()
+            && self.output_keys.is_none()
+            && !self.quote_output
+    }
+
+    // Cut a line for a plain job, finding its fields as offsets into a
+    // buffer kept from line to line and writing the selected ones
+    // straight out, so that nothing is allocated per line.  Returns
+    // false, having done nothing, for a delimiter that can't be split
+    // this way.
+    fn write_plain(&self, line: &str, output: &mut impl Write) -> Result<bool> {
+        let mut offsets = self.offsets.borrow_mut();
+        let selector = self.selector();
+        // Past the last field the selector names, the line needn't be
+        // split at all, which on wide lines is most of the work.
+        let reach = match self.complement {
+            true => usize::MAX,
+            false => selector.reach(),
+        };
+        if !self.input_delim.offsets(line, &mut offsets, reach) {
+            return Ok(false);
+        }
+        self.records.set(self.records.get() + 1);
+        if offsets.len() < 2 {
+            match self.undelimited {
+                Undelimited::Split => {}
+                Undelimited::Print => {
+                    self.write_record(&[line], output)?;
+                    return Ok(true);
+                }
+                Undelimited::Suppress => return Ok(true),
+            }
+        }
+        self.check_length(offsets.len())?;
+        self.write_prefix(output)?;
+        let mut first = true;
+        let mut write = |field: &Range<usize>| -> io::Result<()> {
+            if !first {
+                output.write_all(self.output_separator.as_bytes())?;
+            }
+            first = false;
+            output.write_all(line[field.clone()].as_bytes())
+        };
+        if self.complement {
+            for (field, chosen) in offsets.iter().zip(selector.chosen(offsets.len())) {
+                if !chosen {
+                    write(field)?;
+                }
+            }
+        } else if !selector.excluded.is_empty() {
+            for offset in selector.indexes(offsets.len()) {
+                write(&offsets[offset])?;
+            }
+        } else if selector.fields.is_empty() {
+            offsets.iter().try_for_each(&mut write)?;
+        } else {
+            for range in selector.fields.iter() {
+                let mut fields = offsets[range.offsets(offsets.len())].iter();
+                match range.step {
+                    1 => fields.try_for_each(&mut write)?,
+                    step => fields.step_by(step).try_for_each(&mut write)?,
+                }
+            }
+        }
+        output.write_all(self.terminator.as_bytes())?;
+        self.written.set(self.written.get() + 1);
+        Ok(true)
+    }
+
+    // Cut a line that isn't UTF-8 as bytes, splitting it on the bytes
+    // of the delimiter or on ASCII whitespace, which works for a job
+    // that does no more than pick out fields or byte positions.
+    fn write_bytes(&self, line: &[u8], output: &mut impl Write) -> Result<()> {
+        let line_fields: Vec<&[u8]> = match (self.unit, &self.input_delim) {
+            _ if !self.is_plain() => bail!(NotUtf8),
+            (Some(Unit::Bytes), _) => {
+                self.records.set(self.records.get() + 1);
+                let pieces = positions::cut_bytes(line, self.selector(), self.complement);
+                return self.write_byte_record(&pieces, output);
+            }
+            (None, Delimiter::String(delim)) => split_bytes(line, delim.as_bytes()),
+            (None, Delimiter::Whitespace) => line
+                .split(u8::is_ascii_whitespace)
+                .filter(|field| !field.is_empty())
+                .collect(),
+            _ => bail!(NotUtf8),
+        };
+        self.records.set(self.records.get() + 1);
+        if line_fields.len() < 2 {
+            match self.undelimited {
+                Undelimited::Split => {}
+                Undelimited::Print => return self.write_byte_record(&[line], output),
+                Undelimited::Suppress => return Ok(()),
+            }
+        }
+        self.check_length(line_fields.len())?;
+        let selected = match (self.complement, &self.pad) {
+            (true, _) => self.selector().select_complement(&line_fields),
+            (false, Some(pad)) => self.selector().select_padded(&line_fields, &pad.as_bytes()),
+            (false, None) => self.selector().select(&line_fields),
+        };
+        self.write_byte_record(&selected, output)
+    }
+
+    fn write_byte_record(&self, fields: &[&[u8]], output: &mut impl Write) -> Result<()> {
+        self.write_prefix(output)?;
+        for (i, val) in fields.iter().enumerate() {
+            if i > 0 {
+                output.write_all(self.output_separator.as_bytes())?;
+            }
+            output.write_all(val)?;
+        }
+        output.write_all(self.terminator.as_bytes())?;
+        self.written.set(self.written.get() + 1);
+        Ok(())
+    }
+
+    // With --passthrough, put the line itself beside its fields.
+    fn annotate(&self, line: &str, fields: &[&str], output: &mut impl Write) -> Result<()> {
+        match self.passthrough {
+            None => self.record(fields, output),
+            Some(Passthrough::Append) => self.record(&[&[line], fields].concat(), output),
+            Some(Passthrough::Prepend) => self.record(&[fields, &[line]].concat(), output),
+        }
+    }
+
+    // A line with nothing selected from it is still printed as it
+    // was with --passthrough, and dropped otherwise.
+    fn pass_through(&self, line: &str, output: &mut impl Write) -> Result<()> {
+        match self.passthrough {
+            Some(_) => self.record(&[line], output),
+            None => Ok(()),
+        }
+    }
+
+    // Hand a selected record to the aggregate, if there is one, or else
+    // straight to the output, unless it's a duplicate.
+    fn record(&self, fields: &[&str], output: &mut impl Write) -> Result<()> {
+        if let Some(ref distinct) = self.distinct {
+            if !distinct.borrow_mut().first_time(fields) {
+                return Ok(());
+            }
+        }
+        let result = match self.aggregate {
+            Some(ref aggregate) => aggregate.borrow_mut().add(fields),
+            None => self.write_record(fields, output),
+        };
+        if let Some(cap) = self.max_memory {
+            let held = self.distinct.as_ref().map_or(0, |d| d.borrow().footprint())
+                + self
+                    .aggregate
+                    .as_ref()
+                    .map_or(0, |a| a.borrow().footprint());
+            cap.check(held, tr("buffered records"))?;
+        }
+        result
+    }
+
+    /// Once every input has been read, print whatever the aggregate has
+    /// been holding on to.
+    pub fn finish(&self, output: &mut impl Write) -> Result<()> {
+        if let Some(ref aggregate) = self.aggregate {
+            for row in aggregate.borrow_mut().finish()? {
+                let fields: Vec<&str> = row.iter().map(String::as_str).collect();
+                self.write_record(&fields, output)?;
+            }
+            output.flush()?;
+        }
+        if let Some(ref rejects) = self.rejects {
+            let rejected = rejects.borrow_mut().finish()?;
+            info!(rejected, "wrote rejects");
+        }
+        Ok(())
+    }
+
+    // Write fields joined by the output separator, ending the line, or
+    // hand that line to the next stage.
+    fn write_record(&self, fields: &[&str], output: &mut impl Write) -> Result<()> {
+        if let Some(ref then) = self.then {
+            return then.write_line(&fields.join(&self.output_separator), output);
+        }
+        if let Some(ref keys) = self.output_keys {
+            if self.kv_output {
+                return self.write_kv(keys, fields, output);
+            }
+            return self.write_json(keys, fields, output);
+        }
+        self.write_prefix(output)?;
+        for (i, val) in fields.iter().enumerate() {
+            if i > 0 {
+                output.write_all(self.output_separator.as_bytes())?;
+            }
+            if self.quote_output {
+                output.write_all(csv::quote(val, &self.output_separator).as_bytes())?;
+            } else {
+                output.write_all(val.as_bytes())?;
+            }
+        }
+        output.write_all(self.terminator.as_bytes())?;
+        self.written.set(self.written.get() + 1);
+        Ok(())
+    }
+
+    // Write a record as a JSON array, or as an object once there are
+    // names for its fields; a field past the last name is keyed by its
+    // position.  The header that gives the names isn't written.
+    fn write_json(
+        &self,
+        keys: &OnceLock<Vec<String>>,
+        fields: &[&str],
+        output: &mut impl Write,
+    ) -> Result<()> {
+        let Some(names) = keys.get() else {
+            let _ = keys.set(fields.iter().map(|field| String::from(*field)).collect());
+            return Ok(());
+        };
+        if names.is_empty() {
+            serde_json::to_writer(&mut *output, fields)?;
+        } else {
+            output.write_all(b"{")?;
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    output.write_all(b",")?;
+                }
+                match names.get(i) {
+                    Some(name) => serde_json::to_writer(&mut *output, name)?,
+                    None => serde_json::to_writer(&mut *output, &(i + 1).to_string())?,
+                }
+                output.write_all(b":")?;
+                serde_json::to_writer(&mut *output, field)?;
+            }
+            output.write_all(b"}")?;
+        }
+        output.write_all(self.terminator.as_bytes())?;
+        self.written.set(self.written.get() + 1);
+        Ok(())
+    }
+
+    // Write a record as `name=value` pairs, naming the fields from the
+    // header or keys, where there are any, and otherwise, as for a field
+    // past the last name, `f` and its position.  A value is quoted as
+    // logfmt quotes it, so that --logfmt reads it back.  Like JSON
+    // output, the header that gives the names isn't written.
+    fn write_kv(
+        &self,
+        keys: &OnceLock<Vec<String>>,
+        fields: &[&str],
+        output: &mut impl Write,
+    ) -> Result<()> {
+        let Some(names) = keys.get() else {
+            let _ = keys.set(fields.iter().map(|field| String::from(*field)).collect());
+            return Ok(());
+        };
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                output.write_all(self.output_separator.as_bytes())?;
+            }
+            match names.get(i) {
+                Some(name) => output.write_all(name.as_bytes())?,
+                None => write!(output, "f{}", i + 1)?,
+            }
+            output.write_all(b"=")?;
+            output.write_all(logfmt::quote(field).as_bytes())?;
+        }
+        output.write_all(self.terminator.as_bytes())?;
+        self.written.set(self.written.get() + 1);
+        Ok(())
+    }
+
+    // The stage that writes the output.
+    fn last_stage_mut(&mut self) -> &mut CutJob {
+        match self.then {
+            Some(ref mut then) => then.last_stage_mut(),
+            None => self,
+        }
+    }
+
+    // Note the name of the input about to be read, for -H.
+    fn begin_input(&self, name: &str) {
+        match self.then {
+            Some(ref then) => then.begin_input(name),
+            None => {
+                if let Some(ref filename) = self.filename {
+                    name.clone_into(&mut filename.borrow_mut());
+                }
+            }
+        }
+    }
+
+    // Note the number of the line about to be cut, for --line-numbers.
+    fn begin_line(&self, records: u64) {
+        match self.then {
+            Some(ref then) => then.begin_line(records),
+            None => {
+                if let Some(ref line_number) = self.line_number {
+                    line_number.set(records);
+                }
+            }
+        }
+    }
+
+    // Start a record with the name of the input it came from, as grep
+    // -H does, and with --line-numbers the number of the line it was cut
+    // from, as a field of its own.
+    fn write_prefix(&self, output: &mut impl Write) -> io::Result<()> {
+        if let Some(ref filename) = self.filename {
+            output.write_all(filename.borrow().as_bytes())?;
+            output.write_all(b":")?;
+        }
+        if let Some(ref line_number) = self.line_number {
+            write!(output, "{}", line_number.get())?;
+            output.write_all(self.output_separator.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    // The number of records written out by the last stage.
+    fn written(&self) -> u64 {
+        match self.then {
+            Some(ref then) => then.written(),
+            None => self.written.get(),
+        }
+    }
+
+    // Act on SIGINT or SIGUSR1 between lines, `read` bytes into the
+    // input.
+    fn check_signals(&self, read: u64, output: &mut impl Write) -> Result<()> {
+        if signals::interrupted() {
+            output.flush()?;
+            return Err(Interrupted.into());
+        }
+        if signals::timed_out() {
+            output.flush()?;
+            return Err(TimedOut.into());
+        }
+        // A job on a timer may be stopped while stuck waiting for more
+        // input, so what it has cut so far can't sit in a buffer.
+        if signals::timer_started() {
+            output.flush()?;
+        }
+        if signals::take_report() {
+            let mut progress = self.progress.borrow_mut();
+            progress.offset = progress.start + read;
+            progress.report(self.written());
+        }
+        Ok(())
+    }
+
+    /// Read a stream, splitting each line on the Delimiter and outputting
+    /// as requested by the field Selector.  Returns the number of lines
+    /// processed.  A job can read any number of streams in turn, as if
+    /// they were one.
+    pub fn process_reader(&self, mut reader: impl BufRead, output: &mut impl Write) -> Result<u64> {
+        self.lines.set(0);
+        let mut records = 0;
+        // Bytes read so far, for --checkpoint.
+        let mut read = 0;
+        let mut buf = vec![];
+        // Columns still to be inferred hold lines back until there
+        // are enough to go on, or the input runs out.
+        let mut sample = vec![];
+        // With --max-memory, a line is read no further than the cap.
+        let longest = self
+            .max_memory
+            .map_or(u64::MAX, |cap| cap.0.saturating_add(1));
+        loop {
+            buf.clear();
+            let len = self.read_record(&mut reader, &mut buf, longest, records + 1)?;
+            if len == 0 {
+                break;
+            }
+            records += 1;
+            read += len as u64;
+            self.process_line(
+                strip_line_ending(&buf, &self.terminator),
+                records,
+                read,
+                &mut sample,
+                output,
+            )?;
+        }
+        self.finish_reading(&mut sample, output)?;
+        Ok(records)
+    }
+
+    // Read a file mapped into memory, as process_reader does but
+    // finding lines in the mapped bytes without copying them out.
+    fn process_mapped(&self, bytes: &[u8], output: &mut impl Write) -> Result<u64> {
+        self.lines.set(0);
+        let mut records = 0;
+        let mut read = 0;
+        let mut sample = vec![];
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let len = self.mapped_record(rest, records + 1)?;
+            let (record, tail) = rest.split_at(len);
+            rest = tail;
+            records += 1;
+            read += len as u64;
+            self.process_line(
+                strip_line_ending(record, &self.terminator),
+                records,
+                read,
+                &mut sample,
+                output,
+            )?;
+        }
+        self.finish_reading(&mut sample, output)?;
+        Ok(records)
+    }
+
+    // Cut one record, the `records`th of its input, `read` bytes in.
+    // One that isn't UTF-8 can only be cut as bytes.
+    fn process_line(
+        &self,
+        line: &[u8],
+        records: u64,
+        read: u64,
+        sample: &mut Vec<String>,
+        output: &mut impl Write,
+    ) -> Result<()> {
+        if records <= self.skip_lines {
+            return self.after_record(read, output);
+        }
+        self.begin_line(records);
+        if let Some(ref prefix) = self.comments {
+            if line.starts_with(prefix.as_bytes()) {
+                if self.keep_comments {
+                    self.write_byte_record(&[line], output)?;
+                }
+                return self.after_record(read, output);
+            }
+        }
+        let first = self.lines.replace(self.lines.get() + 1) == 0;
+        let line = match std::str::from_utf8(line) {
+            Ok(line) => line,
+            Err(_) if (self.header && first) || self.awaiting_sample() => {
+                return Err(anyhow::Error::new(NotUtf8).context(InputLine(records)));
+            }
+            Err(_) => {
+                let written = self.write_bytes(line, output);
+                self.reject(written, line, records)?;
+                return self.after_record(read, output);
+            }
+        };
+        // Only the first input's header is printed, unless each input
+        // is written on its own; the rest just say where the columns are.
+        if self.header && first {
+            let names = self.input_delim.split(line);
+            if self.resolved.get().is_none() {
+                let resolved = self
+                    .selector
+                    .resolve_names(&borrowed(&names))
+                    .context(InputLine(records))?;
+                let _ = self.resolved.set(resolved);
+            } else if self.alignment.is_none() && !self.each_header {
+                return self.after_record(read, output);
+            }
+            if self.pass_header {
+                let selected = match self.complement {
+                    true => self.selector().select_complement(&borrowed(&names)),
+                    false => self.selector().select(&borrowed(&names)),
+                };
+                self.write_record(&selected, output)?;
+                return self.after_record(read, output);
+            }
+        }
+        if let (Some(ref alignment), true) = (&self.alignment, first) {
+            if !alignment
+                .borrow_mut()
+                .header(&borrowed(&self.input_delim.split(line)))
+            {
+                return self.after_record(read, output);
+            }
+        }
+        if self.awaiting_sample() {
+            sample.push(String::from(line));
+            if sample.len() == columns::SAMPLE_LINES {
+                self.infer_columns(sample, output)?;
+            }
+            return Ok(());
+        }
+        let written = self.write_line(line, output);
+        self.reject(written, line.as_bytes(), records)?;
+        self.after_record(read, output)
+    }
+
+    // Put a line that couldn't be cut, the `records`th of its input, in
+    // the --rejects file if there is one.  Otherwise, and for any other
+    // error, the run fails.
+    fn reject(&self, written: Result<()>, line: &[u8], records: u64) -> Result<()> {
+        let Err(err) = written else {
+            return Ok(());
+        };
+        let bad = err.is::<NotUtf8>() || err.is::<json::NotAnObject>() || err.is::<MissingFields>();
+        match self.rejects {
+            _ if !bad => Err(err),
+            Some(ref rejects) => {
+                debug!(line = records, error = %err, "rejected");
+                rejects.borrow_mut().write(line, &self.terminator)
+            }
+            None => Err(err.context(InputLine(records))),
+        }
+    }
+
+    // Note how far through the input the job has got, `read` bytes in.
+    fn after_record(&self, read: u64, output: &mut impl Write) -> Result<()> {
+        if let Some(ref checkpoint) = self.checkpoint {
+            checkpoint.borrow_mut().update(read, output)?;
+        }
+        self.check_signals(read, output)
+    }
+
+    // At the end of an input, cut whatever was held back for inferring
+    // columns from.
+    fn finish_reading(&self, sample: &mut Vec<String>, output: &mut impl Write) -> Result<()> {
+        if !sample.is_empty() {
+            self.infer_columns(sample, output)?;
+        }
+        output.flush()?;
+        Ok(())
+    }
+
+    // Read the next record onto the end of `buf`: a line, or with --csv
+    // as many lines as it takes to close a quoted field.  Returns the
+    // bytes read, or 0 at the end of the input.
+    fn read_record(
+        &self,
+        reader: &mut impl BufRead,
+        buf: &mut Vec<u8>,
+        longest: u64,
+        lineno: u64,
+    ) -> Result<usize> {
+        let mut len = 0;
+        loop {
+            let more = self
+                .read_terminated(reader, buf, longest)
+                .with_context(|| InputLine(lineno))?;
+            len += more;
+            if let Some(cap) = self.max_memory {
+                cap.check(
+                    len - self.terminated(buf) as usize * self.terminator.len(),
+                    tr("a single line"),
+                )
+                .with_context(|| InputLine(lineno))?;
+            }
+            let quoted = match self.input_delim {
+                Delimiter::Csv(sep) => {
+                    more > 0
+                        && std::str::from_utf8(buf)
+                            .is_ok_and(|record| csv::unterminated(record, sep))
+                }
+                _ => false,
+            };
+            if !quoted {
+                return Ok(len);
+            }
+        }
+    }
+
+    // Read up to the end of the next line, or whatever -z or
+    // --record-sep make a record, onto the end of `buf`, but no more
+    // than `longest` bytes of it.  Returns the bytes read.
+    fn read_terminated(
+        &self,
+        reader: &mut impl BufRead,
+        buf: &mut Vec<u8>,
+        longest: u64,
+    ) -> io::Result<usize> {
+        let last = *self
+            .terminator
+            .as_bytes()
+            .last()
+            .expect("a record separator");
+        let mut len = 0;
+        loop {
+            let more = reader.take(longest - len as u64).read_until(last, buf)?;
+            len += more;
+            if more == 0 || self.terminated(buf) || buf.last() != Some(&last) {
+                return Ok(len);
+            }
+        }
+    }
+
+    fn terminated(&self, buf: &[u8]) -> bool {
+        buf.ends_with(self.terminator.as_bytes())
+    }
+
+    // How long the next record in mapped bytes is, line ending and
+    // all, checked against --max-memory as read_record checks it.
+    fn mapped_record(&self, bytes: &[u8], lineno: u64) -> Result<usize> {
+        let mut len = 0;
+        loop {
+            let terminator = self.terminator.as_bytes();
+            len += match terminator {
+                [byte] => memchr::memchr(*byte, &bytes[len..]),
+                _ => memchr::memmem::find(&bytes[len..], terminator),
+            }
+            .map_or(bytes.len() - len, |i| i + terminator.len());
+            if let Some(cap) = self.max_memory {
+                cap.check(
+                    len - self.terminated(&bytes[..len]) as usize * terminator.len(),
+                    tr("a single line"),
+                )
+                .with_context(|| InputLine(lineno))?;
+            }
+            let quoted = match self.input_delim {
+                Delimiter::Csv(sep) => {
+                    len < bytes.len()
+                        && std::str::from_utf8(&bytes[..len])
+                            .is_ok_and(|record| csv::unterminated(record, sep))
+                }
+                _ => false,
+            };
+            if !quoted {
+                return Ok(len);
+            }
+        }
+    }
+
+    fn awaiting_sample(&self) -> bool {
+        matches!(self.input_delim, Delimiter::Inferred(ref starts) if starts.get().is_none())
+    }
+
+    // Settle the columns from the sample, then cut the lines in it.
+    fn infer_columns(&self, sample: &mut Vec<String>, output: &mut impl Write) -> Result<()> {
+        if let Delimiter::Inferred(ref starts) = self.input_delim {
+            let inferred = columns::infer_columns(sample);
+            debug!(?inferred, "inferred columns");
+            let _ = starts.set(inferred);
+        }
+        for line in sample.drain(..) {
+            self.write_line(&line, output)?;
+        }
+        Ok(())
+    }
+
+    // The next record of an input read in lockstep, as the text it has
+    // to be to be cut, or None at the end of the input.
+    fn next_text(
+        &self,
+        reader: &mut impl BufRead,
+        buf: &mut Vec<u8>,
+    ) -> io::Result<Option<String>> {
+        buf.clear();
+        if self.read_terminated(reader, buf, u64::MAX)? == 0 {
+            return Ok(None);
+        }
+        let record = strip_line_ending(buf, &self.terminator).to_vec();
+        String::from_utf8(record)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    // Read several named streams in lockstep.  Pasting prints the
+    // selected fields of each side by side, with a stream that ran out
+    // early contributing an empty field for each field the others
+    // gave, so the columns after it stay put; interleaving prints one record
+    // from each in turn, with an exhausted stream contributing an empty
+    // line until they have all run dry.  Returns the number of rounds.
+    fn process_lockstep<R: BufRead>(
+        &self,
+        readers: Vec<(String, R)>,
+        mode: InputMode,
+        output: &mut impl Write,
+    ) -> Result<u64> {
+        let mut inputs = readers;
+        let mut buf = vec![];
+        let mut records = 0;
+        loop {
+            records += 1;
+            let mut lines = vec![];
+            for (name, input) in inputs.iter_mut() {
+                let line = self
+                    .next_text(input, &mut buf)
+                    .context(InputLine(records))
+                    .with_context(|| InputFile(name.clone()))?;
+                lines.push(line);
+            }
+            if lines.iter().all(Option::is_none) {
+                break;
+            }
+            self.check_signals(0, output)?;
+            if mode == InputMode::Interleave {
+                for line in lines.iter() {
+                    match line {
+                        Some(line) => self.write_line(line, output)?,
+                        None => self.record(&[], output)?,
+                    }
+                }
+                continue;
+            }
+            let selected: Vec<Option<Vec<Cow<str>>>> = lines
+                .iter()
+                .map(|line| line.as_ref().map(|line| self.select_fields(line)))
+                .collect();
+            let width = selected.iter().flatten().map(Vec::len).max().unwrap_or(0);
+            let fields: Vec<Cow<str>> = selected
+                .into_iter()
+                .flat_map(|fields| fields.unwrap_or_else(|| vec![Cow::Borrowed(""); width]))
+                .collect();
+            self.record(&borrowed(&fields), output)?;
+        }
+        output.flush()?;
+        Ok(records - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use std::io::BufReader;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_cli_parsing() {
+        let (cut_job, args, _) = parse_command_line(Some(vec!["rcut_test", "-f", "1"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(1)]);
+        assert_eq!(args, Vec::<OsString>::new());
+
+        let (cut_job, args, _) = parse_command_line(Some(vec!["rcut_test", "1"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(1)]);
+        assert_eq!(args, Vec::<OsString>::new());
+
+        let (cut_job, args, _) =
+            parse_command_line(Some(vec!["rcut_test", "-f", "1", "/etc/passwd"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(1)]);
+        assert_eq!(args, vec!["/etc/passwd"]);
+
+        let (cut_job, args, _) = parse_command_line(Some(vec!["rcut_test", "1-5"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_span(1, 5)]);
+        assert_eq!(args, Vec::<OsString>::new());
+
+        let (cut_job, args, _) = parse_command_line(Some(vec!["rcut_test", "1-5"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_span(1, 5)]);
+        assert_eq!(args, Vec::<OsString>::new());
+    }
+
+    #[test]
+    fn test_ambiguous_positionals() {
+        // Tests run from the crate root, so a relative name is needed
+        // for the file to also parse as a selector.
+        let ambiguous = format!("{}-{}", std::process::id(), std::process::id());
+        File::create(&ambiguous).unwrap();
+
+        let warnings =
+            ambiguous_positionals(&[OsString::from("src"), OsString::from(ambiguous.as_str())]);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains(&ambiguous));
+
+        let (cut_job, args, _) =
+            parse_command_line(Some(vec!["rcut_test", "2", "--", ambiguous.as_str()])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(2)]);
+        assert_eq!(args, vec![ambiguous.as_str()]);
+
+        std::fs::remove_file(&ambiguous).unwrap();
+    }
+
+    #[test]
+    fn test_simple_field_parsing() {
+        assert_eq!(FieldRange::new_val(1), FieldRange::new_span(1, 1));
+        assert_eq!(
+            field_parser("1").unwrap().fields,
+            vec![FieldRange::new_val(1)]
+        );
+        assert_eq!(
+            field_parser("1,2").unwrap().fields,
+            vec![FieldRange::new_val(1), FieldRange::new_val(2)]
+        );
+        assert_eq!(
+            field_parser("1-1").unwrap().fields,
+            vec![FieldRange::new_val(1)]
+        );
+        assert_eq!(
+            field_parser("1-4").unwrap().fields,
+            vec![FieldRange::new_span(1, 4)]
+        );
+        assert_eq!(
+            field_parser("1-2,3-4").unwrap().fields,
+            vec![FieldRange::new_span(1, 2), FieldRange::new_span(3, 4)]
+        );
+        assert_eq!(
+            field_parser("2-1,3-4").unwrap().fields,
+            vec![FieldRange::new_span(2, 1), FieldRange::new_span(3, 4)]
+        );
+    }
+
+    proptest! {
+        // Fields joined with -o SEP come back apart when split with -d
+        // SEP, as long as none of them contains SEP.
+        #[test]
+        fn test_delimited_round_trip(
+            (sep, fields) in any::<char>()
+                .prop_filter("line break", |sep| *sep != '\n' && *sep != '\r')
+                .prop_flat_map(|sep| {
+                    let field = prop::collection::vec(
+                        any::<char>().prop_filter("SEP or line break", move |c| {
+                            *c != sep && *c != '\n' && *c != '\r'
+                        }),
+                        0..8,
+                    )
+                    .prop_map(String::from_iter);
+                    (Just(sep.to_string()), prop::collection::vec(field, 1..8))
+                })
+        ) {
+            let line = fields.join(&sep);
+            let (job, _, _) =
+                parse_command_line(Some(vec!["rcut_test", "-d", &sep, "-o", &sep])).unwrap();
+            prop_assert_eq!(exec_cut_job(job, &format!("{}\n", line)).unwrap(), format!("{}\n", line));
+            prop_assert_eq!(Delimiter::String(sep).split(&line), fields);
+        }
+
+        // With --csv any fields at all, quoted, come back as they were,
+        // and are written quoted the same way, even across lines.
+        #[test]
+        fn test_csv_round_trip(
+            fields in prop::collection::vec("[a-z,\"é \n]{0,6}", 1..6)
+        ) {
+            let line = fields
+                .iter()
+                .map(|field| csv::quote(field, ","))
+                .collect::<Vec<_>>()
+                .join(",");
+            let (job, _, _) = parse_command_line(Some(vec!["rcut_test", "--csv"])).unwrap();
+            prop_assert_eq!(exec_cut_job(job, &format!("{}\n", line)).unwrap(), format!("{}\n", line));
+            prop_assert_eq!(Delimiter::Csv(',').split(&line), fields);
+        }
+    }
+
+    fn exec_cut_job(job: CutJob, input: &str) -> Result<String> {
+        let input = BufReader::new(input.as_bytes());
+        let mut output = Cursor::new(vec![]);
+        job.process_reader(input, &mut output)?;
+        job.finish(&mut output)?;
+        Ok(String::from_utf8(output.get_ref().to_vec()).unwrap())
+    }
+
+    #[test]
+    fn test_align_headers() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--align-headers",
+            "-d",
+            ",",
+            "-f",
+            "3,1",
+        ]))
+        .unwrap();
+        let mut output = Cursor::new(vec![]);
+        job.process_reader(Cursor::new("id,name,email\n1,ann,a@x\n"), &mut output)
+            .unwrap();
+        job.process_reader(Cursor::new("email,id\nb@x,2\n"), &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "email,id\na@x,1\nb@x,2\n"
+        );
+    }
+
+    #[test]
+    fn test_load_order() {
+        let order = load_order(Cursor::new("# audited order\n3\n\n1-2\n-1\n")).unwrap();
+        assert_eq!(order.describe(), "3,1-2,-1");
+        let order = load_order(Cursor::new("email\n2\n# by name\nfirst name,1\n")).unwrap();
+        let header = ["id", "first name", "email"];
+        assert_eq!(order.resolve_names(&header).unwrap().describe(), "3,2,2,1");
+        assert!(order.resolve_names(&["id"]).is_err());
+        assert!(load_order(Cursor::new("2\n1-2-3\n")).is_err());
+        assert!(load_order(Cursor::new("# nothing\n")).is_err());
+    }
+
+    #[test]
+    fn test_subcommands() {
+        let (cut_job, args, _) =
+            parse_command_line(Some(vec!["rcut_test", "fields", "-f", "2", "a"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(2)]);
+        assert_eq!(args, vec!["a"]);
+        let (cut_job, _, _) = parse_command_line(Some(vec!["rcut_test", "fields", "-1"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(-1)]);
+        // Legacy syntax treats a later "fields" as a selector, not a mode.
+        assert!(parse_command_line(Some(vec!["rcut_test", "1", "fields"])).is_err());
+        // The other modes take what they select first, then files.
+        let cut = |args: &[&str], input: &str| {
+            let (job, files, _) =
+                parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            assert_eq!(files, vec!["a"]);
+            exec_cut_job(job, input).unwrap()
+        };
+        assert_eq!(cut(&["chars", "2-3", "a"], "héllo\n"), "él\n");
+        assert_eq!(cut(&["chars", "--complement", "1", "a"], "abc\n"), "bc\n");
+        assert_eq!(cut(&["bytes", "1-3", "a"], "héllo\n"), "hé\n");
+        let input = "name,age\n\"b, c\",3\n";
+        assert_eq!(
+            cut(&["csv", "age,name", "a"], input),
+            "age,name\n3,\"b, c\"\n"
+        );
+        assert_eq!(cut(&["csv", "--no-header", "2", "a"], input), "age\n3\n");
+        assert_eq!(
+            cut(&["json", "a.b,c", "a"], "{\"a\":{\"b\":1},\"c\":2}\n"),
+            "1 2\n"
+        );
+        assert_eq!(
+            cut(
+                &["stats", "--header", "-d", ",", "b", "a"],
+                "a,b\n1,2\n3,4\n"
+            ),
+            "field,count,min,max,mean,sum\nb,2,2,4,3,6\n"
+        );
+        for mode in ["chars", "bytes", "csv", "json", "stats"] {
+            let args = vec!["rcut_test", mode, "1", "-f", "2", "a"];
+            assert!(parse_command_line(Some(args)).is_err(), "{}", mode);
+            assert!(
+                parse_command_line(Some(vec!["rcut_test", mode])).is_err(),
+                "{}",
+                mode
+            );
+        }
+        // Without -f, sample's positionals are files.
+        let (cut_job, args, _) =
+            parse_command_line(Some(vec!["rcut_test", "sample", "-n", "5", "a", "2"])).unwrap();
+        assert_eq!(cut_job.selector.describe(), "all");
+        assert_eq!(args, vec!["a", "2"]);
+    }
+
+    #[test]
+    fn test_huge_ranges() {
+        let (job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "-f", "2-99999999999,1"])).unwrap();
+        assert_eq!(exec_cut_job(job, "a b c\n").unwrap(), "b c a\n");
+    }
+
+    #[test]
+    fn test_open_ranges() {
+        assert_eq!(
+            field_parser("3-,..2,4..").unwrap().fields,
+            vec![
+                FieldRange::new_span(3, OPEN),
+                FieldRange::new_span(1, 2),
+                FieldRange::new_span(4, OPEN)
+            ]
+        );
+        let line = ["a", "b", "c", "d", "e"];
+        let select = |selector: &str| field_parser(selector).unwrap().select(&line);
+        assert_eq!(select("3-"), vec!["c", "d", "e"]);
+        assert_eq!(select("-3.."), vec!["c", "d", "e"]);
+        assert_eq!(select("-2..-1"), vec!["d", "e"]);
+        assert_eq!(select("2..-2"), vec!["b", "c", "d"]);
+        assert_eq!(select("-9..2"), vec!["a", "b"]);
+        assert!(select("9-").is_empty());
+        assert_eq!(select("last-2..last"), vec!["c", "d", "e"]);
+        assert_eq!(select("2-last-1"), vec!["b", "c", "d"]);
+        assert_eq!(select("last,1"), vec!["e", "a"]);
+        assert_eq!(select("^last"), vec!["a", "b", "c", "d"]);
+        assert_eq!(field_parser("last-1..").unwrap().describe(), "-2..");
+        assert_eq!(select("2-n-1"), vec!["b", "c", "d"]);
+        assert_eq!(select("n-2-n"), vec!["c", "d", "e"]);
+        assert_eq!(select("n-1,n-"), vec!["d", "e"]);
+        assert_eq!(field_parser("1--2").unwrap().describe(), "1..-2");
+        assert!(field_parser("1,-2").is_err());
+        assert!(field_parser("lastly").is_err());
+        assert!(field_parser("nope").is_err());
+        assert!(field_parser("last-x").is_err());
+        let selector = field_parser("-2").unwrap();
+        assert!(selector.bare_negative);
+        assert!(bare_negative_warning(&selector).is_some());
+        assert!(bare_negative_warning(&field_parser("-2..-2").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_header() {
+        let cut = |args: Vec<&str>| -> Result<String> {
+            let (job, _, _) = parse_command_line(Some(args))?;
+            exec_cut_job(job, "id,name,email\n1,ann,a@x\n")
+        };
+        assert_eq!(
+            cut(vec!["rcut_test", "-d", ",", "--header", "-f", "email,1"]).unwrap(),
+            "email,id\na@x,1\n"
+        );
+        assert_eq!(
+            cut(vec!["rcut_test", "--csv", "--header", "-f", "name-"])
+                .unwrap_err()
+                .root_cause()
+                .to_string(),
+            "there is no column named 'name-'"
+        );
+        let err = cut(vec!["rcut_test", "-d", ",", "-f", "name"]).unwrap_err();
+        assert!(err.is::<clap::Error>());
+        let (job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "--header", "-f", "2-,name"])).unwrap();
+        assert_eq!(job.selector.describe(), "2-,name");
+    }
+
+    #[test]
+    fn test_json() {
+        let cut = |args: Vec<&str>, input: &str| -> Result<String> {
+            let (job, _, _) = parse_command_line(Some(args))?;
+            exec_cut_job(job, input)
+        };
+        let input = "{\"user\": {\"id\": 7}, \"msg\": \"hi\"}\n{\"msg\": \"bye\"}\n";
+        assert_eq!(
+            cut(vec!["rcut_test", "--json", "-f", "msg,user.id"], input).unwrap(),
+            "hi 7\nbye \n"
+        );
+        let err = cut(vec!["rcut_test", "--json", "-f", "msg"], "{}\n[]\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2");
+        assert!(cut(vec!["rcut_test", "--json", "-f", "msg,2"], input)
+            .unwrap_err()
+            .is::<clap::Error>());
+    }
+
+    #[test]
+    fn test_logfmt() {
+        let cut = |args: Vec<&str>, input: &str| -> Result<String> {
+            let (job, _, _) = parse_command_line(Some(args))?;
+            exec_cut_job(job, input)
+        };
+        let input = "level=info msg=\"disk full\" host=web1\nlevel=warn host=db2\n";
+        assert_eq!(
+            cut(
+                vec!["rcut_test", "--logfmt", "-f", "host,msg", "-o", "|"],
+                input
+            )
+            .unwrap(),
+            "web1|disk full\ndb2|\n"
+        );
+        assert!(cut(vec!["rcut_test", "--logfmt", "-f", "1"], input)
+            .unwrap_err()
+            .is::<clap::Error>());
+    }
+
+    #[test]
+    fn test_output_format() {
+        let cut = |args: Vec<&str>, input: &str| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        fn json<'a>(args: &[&'a str]) -> Vec<&'a str> {
+            [&["rcut_test", "--output-format", "json"], args].concat()
+        }
+        assert_eq!(
+            cut(json(&["-d", ",", "-f", "2,1"]), "a,\"b\"\n"),
+            "[\"\\\"b\\\"\",\"a\"]\n"
+        );
+        assert_eq!(
+            cut(
+                json(&["--csv", "--header", "-f", "name,1"]),
+                "id,name\n1,ann\n2,bo\n"
+            ),
+            "{\"name\":\"ann\",\"id\":\"1\"}\n{\"name\":\"bo\",\"id\":\"2\"}\n"
+        );
+        assert_eq!(
+            cut(json(&["--json", "-f", "a.b"]), "{\"a\": {\"b\": 1}}\n"),
+            "{\"a.b\":\"1\"}\n"
+        );
+        assert_eq!(cut(json(&["-f", "1", "--then", "1"]), "x y\n"), "[\"x\"]\n");
+        fn kv<'a>(args: &[&'a str]) -> Vec<&'a str> {
+            [&["rcut_test", "--output-format", "kv"], args].concat()
+        }
+        assert_eq!(
+            cut(kv(&["-d", ",", "-f", "2,1"]), "a,b c\n"),
+            "f1=\"b c\" f2=a\n"
+        );
+        assert_eq!(
+            cut(
+                kv(&["--csv", "--header", "-f", "name,1"]),
+                "id,name\n1,ann\n2,\"bo \"\"b\"\"\"\n"
+            ),
+            "name=ann id=1\nname=\"bo \\\"b\\\"\" id=2\n"
+        );
+        assert_eq!(
+            cut(
+                kv(&["--logfmt", "-f", "msg,level"]),
+                "level=info msg=\"x=1\"\n"
+            ),
+            "msg=\"x=1\" level=info\n"
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        let (job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "--csv", "-f", "3,2"])).unwrap();
+        let input = "1,\"two\nlines\",x\n2,\"a,b\",\"y\"\n";
+        assert_eq!(
+            exec_cut_job(job, input).unwrap(),
+            "x,\"two\nlines\"\ny,\"a,b\"\n"
+        );
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--csv",
+            "-d",
+            ";",
+            "-f",
+            "2",
+            "-o",
+            " ",
+        ]))
+        .unwrap();
+        assert_eq!(exec_cut_job(job, "a;\"b c\"\n").unwrap(), "\"b c\"\n");
+        assert!(parse_command_line(Some(vec!["rcut_test", "--csv", "-d", "::"])).is_err());
+    }
+
+    #[test]
+    fn test_csv_output() {
+        let cut = |args: &[&str], input: &str| {
+            let argv = [&["rcut_test", "--output-format", "csv"], args].concat();
+            let (job, _, _) = parse_command_line(Some(argv)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "a\tb,c\tsay \"hi\"\n";
+        assert_eq!(
+            cut(&["-t", "-f", "3,2,1"], input),
+            "\"say \"\"hi\"\"\",\"b,c\",a\n"
+        );
+        assert_eq!(cut(&["-t", "-o", ";", "-f", "2"], input), "b,c\n");
+        assert_eq!(
+            cut(&["-t", "-f", "1,2", "--then", "-d , -o | 2,1"], input),
+            "c|a\tb\n"
+        );
+        assert_eq!(cut(&["-f", "1", "--then", "-d a 1"], "xay\n"), "x\n");
+    }
+
+    #[test]
+    fn test_stepped_ranges() {
+        let selector = field_parser("1-7:2,2-:3").unwrap();
+        assert_eq!(
+            selector.fields,
+            vec![
+                FieldRange::new_span(1, 7).stepped(2),
+                FieldRange::new_span(2, OPEN).stepped(3)
+            ]
+        );
+        assert_eq!(selector.describe(), "1-7:2,2-:3");
+        let fields: Vec<String> = (1..=9).map(|i| i.to_string()).collect();
+        assert_eq!(
+            selector.select(&fields),
+            vec!["1", "3", "5", "7", "2", "5", "8"]
+        );
+        assert!(field_parser("1-5:0").is_err());
+        assert!(field_parser("1-5:x").is_err());
+        let cut = |args: Vec<&str>, input: &str| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        assert_eq!(
+            cut(vec!["rcut_test", "-f", "..:2"], "a b c d e\n"),
+            "a c e\n"
+        );
+        assert_eq!(
+            cut(
+                vec!["rcut_test", "-f", "2-:2", "--complement"],
+                "a b c d e\n"
+            ),
+            "a c e\n"
+        );
+        assert_eq!(
+            cut(vec!["rcut_test", "-c", "1-9:3"], "abcdefghi\n"),
+            "adg\n"
+        );
+        assert_eq!(cut(vec!["rcut_test", "-b", "2-:2"], "abcdef\n"), "bdf\n");
+    }
+
+    #[test]
+    fn test_excluded_fields() {
+        let selector = field_parser("1-10,^4,^7-8").unwrap();
+        assert_eq!(selector.describe(), "1-10,^4,^7-8");
+        let fields: Vec<String> = (1..=12).map(|i| i.to_string()).collect();
+        assert_eq!(
+            selector.select(&fields),
+            vec!["1", "2", "3", "5", "6", "9", "10"]
+        );
+        let evens = field_parser("^..:2").unwrap();
+        assert_eq!(evens.select(&fields[..5]), vec!["2", "4"]);
+        let cut = |args: Vec<&str>, input: &str| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        assert_eq!(cut(vec!["rcut_test", "-f", "^2"], "a b c\n"), "a c\n");
+        assert_eq!(cut(vec!["rcut_test", "-f", "2-,^4"], "a b c d\n"), "b c\n");
+        assert_eq!(
+            cut(
+                vec!["rcut_test", "-f", "1-3,^2", "--complement"],
+                "a b c d\n"
+            ),
+            "b d\n"
+        );
+        assert_eq!(
+            cut(vec!["rcut_test", "-b", "1-6,^3-4"], "abcdefg\n"),
+            "abef\n"
+        );
+        assert_eq!(cut(vec!["rcut_test", "-c", "^2"], "abc\n"), "ac\n");
+    }
+
+    #[test]
+    fn test_reach() {
+        let reach = |selector: &str| field_parser(selector).unwrap().reach();
+        assert_eq!(reach("3"), 3);
+        assert_eq!(reach("2,5-7:2,1"), 7);
+        assert_eq!(reach("1-9,^4"), 9);
+        for all in ["3-", "-1", "1,last", "1-5,^last"] {
+            assert_eq!(reach(all), usize::MAX, "{}", all);
+        }
+        assert_eq!(FieldSelector::all().reach(), usize::MAX);
+    }
+
+    #[test]
+    fn test_delimiter_offsets() {
+        fn fields(delim: Delimiter, line: &str) -> Vec<&str> {
+            let mut offsets = vec![];
+            assert!(delim.offsets(line, &mut offsets, usize::MAX));
+            let by_offset: Vec<&str> = offsets.iter().map(|at| &line[at.clone()]).collect();
+            assert_eq!(Some(by_offset.clone()), delim.slices(line));
+            by_offset
+        }
+        assert_eq!(
+            fields(Delimiter::String(",".into()), "a,,b,"),
+            vec!["a", "", "b", ""]
+        );
+        assert_eq!(
+            fields(Delimiter::String("::".into()), "x::y"),
+            vec!["x", "y"]
+        );
+        assert_eq!(
+            fields(Delimiter::Whitespace, "  a  b\tc "),
+            vec!["a", "b", "c"]
+        );
+        let re = Regex::new("[0-9]+").unwrap();
+        assert_eq!(fields(Delimiter::Regex(re), "a1b22c"), vec!["a", "b", "c"]);
+        assert!(!Delimiter::Csv(',').offsets("a,b", &mut vec![], usize::MAX));
+        let reached = |delim: Delimiter, line: &str, reach| -> Vec<String> {
+            let mut offsets = vec![];
+            assert!(delim.offsets(line, &mut offsets, reach));
+            offsets
+                .iter()
+                .map(|at| line[at.clone()].to_string())
+                .collect()
+        };
+        assert_eq!(
+            reached(Delimiter::String(",".into()), "a,b,c,d", 2),
+            ["a", "b", "c,d"]
+        );
+        assert_eq!(reached(Delimiter::String(",".into()), "a,b", 2), ["a", "b"]);
+        assert_eq!(reached(Delimiter::Whitespace, " a  b c ", 1), ["a", "b c"]);
+        let limited = Delimiter::Limited {
+            delim: Box::new(Delimiter::String(",".into())),
+            most: 3,
+            from_end: false,
+        };
+        assert_eq!(reached(limited.clone(), "a,b,c,d", 1), ["a", "b,c,d"]);
+        assert_eq!(reached(limited, "a,b,c,d", 5), ["a", "b", "c,d"]);
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(cli::unescape(r"a\tb\\\x2c\0\n").unwrap(), "a\tb\\,\0\n");
+        for bad in [r"\q", r"\x4", r"\xzz", r"\xe9", "a\\"] {
+            assert!(cli::unescape(bad).is_err(), "{}", bad);
+        }
+        let cut = |args: Vec<&str>| -> Result<String> {
+            let (job, _, _) = parse_command_line(Some(args))?;
+            exec_cut_job(job, "a\tb\tc\n")
+        };
+        let args = vec!["rcut_test", "-d", r"\t", "-f", "1,3", "-o", r"\x3b"];
+        assert_eq!(cut(args.clone()).unwrap(), "a\tb\tc\n");
+        assert_eq!(cut([&args[..], &["--escapes"]].concat()).unwrap(), "a;c\n");
+        assert_eq!(cut(vec!["rcut_test", "-t", "-f", "3,1"]).unwrap(), "c\ta\n");
+        assert_eq!(
+            cut(vec!["rcut_test", "--tsv", "-f", "2-", "-o", ","]).unwrap(),
+            "b,c\n"
+        );
+        assert!(cut(vec!["rcut_test", "-t", "-d", ","]).is_err());
+        let args = ["rcut_test", "-o", r"\t", "--then", r"-d \t -f 2"];
+        assert_eq!(cut([&args[..], &["--escapes"]].concat()).unwrap(), "b\n");
+        assert!(cut(vec!["rcut_test", "-d", r"\u", "--escapes"]).is_err());
+    }
+
+    #[test]
+    fn test_zero_terminated() {
+        let cut = |args: Vec<&str>, input: &str| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "a b\nc\0d e\r\0f";
+        assert_eq!(cut(vec!["rcut_test", "-z", "-f", "2"], input), "b\0e\0\0");
+        assert_eq!(cut(vec!["rcut_test", "-f", "2"], input), "b\ne\n");
+        assert_eq!(
+            cut(
+                vec!["rcut_test", "-z", "-f", "2,1", "--then", "-f 2"],
+                input
+            ),
+            "a\0d\0\0"
+        );
+        let (job, _, _) = parse_command_line(Some(vec!["rcut_test", "-z", "-f", "1"])).unwrap();
+        let mut output = vec![];
+        job.process_mapped(b"a b\0c", &mut output).unwrap();
+        assert_eq!(output, b"a\0c\0");
+    }
+
+    #[test]
+    fn test_record_separator() {
+        let job = |args: &[&str]| {
+            let argv = [&["rcut_test", "--record-sep", r"\n\n"], args].concat();
+            parse_command_line(Some(argv)).unwrap().0
+        };
+        let input = "a b\nc\n\nd\n\n\ne f";
+        assert_eq!(
+            exec_cut_job(job(&["-f", "2"]), input).unwrap(),
+            "b\n\n\n\nf\n\n"
+        );
+        // A separator can straddle what one read of the input gets.
+        let mut output = vec![];
+        let reader = io::BufReader::with_capacity(1, input.as_bytes());
+        job(&["-f", "3,1", "-o", ","])
+            .process_reader(reader, &mut output)
+            .unwrap();
+        assert_eq!(output, b"c,a\n\nd\n\ne\n\n");
+        let mut output = vec![];
+        job(&["-f", "1"])
+            .process_mapped(input.as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(output, b"a\n\nd\n\ne\n\n");
+        let input = "a".repeat(100) + "\n\n";
+        assert!(exec_cut_job(job(&["--max-memory", "50"]), &input).is_err());
+        assert!(parse_command_line(Some(vec!["rcut_test", "--record-sep", ""])).is_err());
+    }
+
+    #[test]
+    fn test_not_utf8() {
+        let cut = |args: Vec<&str>, input: &[u8]| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            let mut output = vec![];
+            job.process_reader(input, &mut output).map(|_| output)
+        };
+        let latin1 = b"caf\xe9,cr\xe8me\nok,\xff\xfe\n\xe9t\xe9\n";
+        let cut_fields = cut(vec!["rcut_test", "-d", ",", "-f", "2"], latin1).unwrap();
+        assert_eq!(cut_fields, b"cr\xe8me\n\xff\xfe\n\n");
+        let complement = cut(
+            vec!["rcut_test", "-d", ",", "-f", "1", "--complement", "-s"],
+            latin1,
+        );
+        assert_eq!(complement.unwrap(), b"cr\xe8me\n\xff\xfe\n");
+        let bytes = cut(vec!["rcut_test", "-b", "4-5"], latin1).unwrap();
+        assert_eq!(bytes, b"\xe9,\n\xff\xfe\n\n");
+        let words = cut(vec!["rcut_test", "-f", "2"], b" a\xe9  b\xe9 c\n").unwrap();
+        assert_eq!(words, b"b\xe9\n");
+        let err = cut(vec!["rcut_test", "-d", ",", "--expr", "$1"], latin1);
+        assert_eq!(err.unwrap_err().to_string(), "line 1");
+        let err = cut(vec!["rcut_test", "-c", "1"], b"ok\n\xe9\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2");
+    }
+
+    #[test]
+    fn test_process_mapped() {
+        let cut = |args: Vec<&str>, input: &[u8]| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            let mut output = vec![];
+            job.process_mapped(input, &mut output).map(|_| output)
+        };
+        let mapped = cut(vec!["rcut_test", "-f", "2"], b"a b\r\nc d\ne f").unwrap();
+        assert_eq!(mapped, b"b\nd\nf\n");
+        let input = b"1,\"two\nlines\",x\n2,\"a,b\",\"y\"\n";
+        let mapped = cut(vec!["rcut_test", "--csv", "-f", "3,2"], input).unwrap();
+        assert_eq!(mapped, b"x,\"two\nlines\"\ny,\"a,b\"\n");
+        let err = cut(vec!["rcut_test", "-f", "1", "--csv"], b"ok\nbad \xff\n").unwrap_err();
+        assert_eq!(err.to_string(), "line 2");
+        let err = cut(
+            vec!["rcut_test", "--max-memory", "4", "-f", "1"],
+            b"abc\nabcdef\n",
+        );
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_regex_delimiter() {
+        let cut = |args: Vec<&str>, input: &str| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        assert_eq!(
+            cut(
+                vec!["rcut_test", "-E", r"\s*,\s*", "-f", "3,1"],
+                "a , b,c\n"
+            ),
+            "c a\n"
+        );
+        assert_eq!(
+            cut(
+                vec!["rcut_test", "--regex-delimiter", ":+", "-f", "2-"],
+                "x::y:::z\n"
+            ),
+            "y z\n"
+        );
+        assert!(parse_command_line(Some(vec!["rcut_test", "-E", "a?", "-f", "1"])).is_err());
+        assert!(
+            parse_command_line(Some(vec!["rcut_test", "-E", ":", "-d", ":", "-f", "1"])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_undelimited() {
+        let cut = |flag: Option<&str>| {
+            let mut args = vec!["rcut_test", "-d", ",", "-f", "2"];
+            args.extend(flag);
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, "a,b\nnone\n\nc,d\n").unwrap()
+        };
+        assert_eq!(cut(None), "b\n\n\nd\n");
+        assert_eq!(cut(Some("-s")), "b\nd\n");
+        assert_eq!(cut(Some("--print-undelimited")), "b\nnone\n\nd\n");
+        assert!(parse_command_line(Some(vec![
+            "rcut_test",
+            "-s",
+            "--print-undelimited",
+            "-f",
+            "1"
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_negative_selectors() {
+        let (cut_job, _, _) = parse_command_line(Some(vec!["rcut_test", "-1"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(-1)]);
+        let (cut_job, _, _) = parse_command_line(Some(vec!["rcut_test", "-f", "-2"])).unwrap();
+        assert_eq!(cut_job.selector.fields, vec![FieldRange::new_val(-2)]);
+    }
+
+    #[test]
+    fn test_output_separator_defaults() {
+        let (cut_job, _, _) = parse_command_line(Some(vec!["rcut_test", "1"])).unwrap();
+        assert_eq!(cut_job.output_separator, " ");
+        let (cut_job, _, _) = parse_command_line(Some(vec!["rcut_test", "-d", ":", "1"])).unwrap();
+        assert_eq!(cut_job.output_separator, ":");
+        let (cut_job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "-d", ":", "-o", "|", "1"])).unwrap();
+        assert_eq!(cut_job.output_separator, "|");
+        assert!(parse_command_line(Some(vec!["rcut_test", "-d", "", "1"])).is_err());
+    }
+
+    #[test]
+    fn test_verbosity() {
+        let (_, _, run_options) = parse_command_line(Some(vec!["rcut_test", "-vv", "1"])).unwrap();
+        assert_eq!(run_options.verbosity, 2);
+        let (_, _, run_options) =
+            parse_command_line(Some(vec!["rcut_test", "-v", "--debug", "1"])).unwrap();
+        assert_eq!(run_options.verbosity, 2);
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let (cut_job, args, run_options) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--dry-run",
+            "-d",
+            ":",
+            "-f",
+            "1-3,5",
+            "a",
+        ]))
+        .unwrap();
+        assert!(run_options.dry_run);
+        assert_eq!(run_options.verbosity, 0);
+        let mut output = Cursor::new(vec![]);
+        cut_job.describe(&args, &run_options, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "input delimiter: \":\"\n\
+             selector: 1-3,5\n\
+             fields: 1 2 3 5\n\
+             output separator: \":\"\n\
+             input: a\n"
+        );
+    }
+
+    #[test]
+    fn test_paste() {
+        let job = CutJob {
+            input_delim: Delimiter::Whitespace,
+            selector: field_parser("2").unwrap(),
+            output_separator: "\t".to_string(),
+            ..Default::default()
+        };
+        let readers = vec![
+            (
+                String::from("a"),
+                BufReader::new("a b\nc d\ne f\n".as_bytes()),
+            ),
+            (String::from("b"), BufReader::new("1 2\n3 4\n".as_bytes())),
+        ];
+        let mut output = Cursor::new(vec![]);
+        assert_eq!(
+            job.process_lockstep(readers, InputMode::Paste, &mut output)
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "b\t2\nd\t4\nf\t\n"
+        );
+        // An input that runs out early leaves a gap as wide as what the
+        // others select, so the columns after it don't move.
+        let job = CutJob {
+            selector: field_parser("1,2").unwrap(),
+            output_separator: ",".to_string(),
+            ..Default::default()
+        };
+        let readers = vec![
+            (String::from("a"), BufReader::new("a b\n".as_bytes())),
+            (String::from("b"), BufReader::new("1 2\n3 4\n".as_bytes())),
+            (String::from("c"), BufReader::new("x y\nz w\n".as_bytes())),
+        ];
+        let mut output = Cursor::new(vec![]);
+        job.process_lockstep(readers, InputMode::Paste, &mut output)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "a,b,1,2,x,y\n,,3,4,z,w\n"
+        );
+        for option in ["-c1", "-b1"] {
+            let args = vec!["rcut_test", "--paste", option, "a", "b"];
+            assert!(parse_command_line(Some(args)).is_err(), "{}", option);
+        }
+        for option in ["--complement", "-s", "--print-undelimited"] {
+            let args = vec!["rcut_test", "--paste", "-f", "1", option, "a", "b"];
+            assert!(parse_command_line(Some(args)).is_err(), "{}", option);
+        }
+        let args = vec!["rcut_test", "--paste", "--where", "1==a", "a", "b"];
+        assert!(parse_command_line(Some(args)).is_err());
+    }
+
+    #[test]
+    fn test_paste_files() {
+        let (job, args, run_options) =
+            parse_command_line(Some(vec!["rcut_test", "--paste", "-o", ",", "a", "2"])).unwrap();
+        assert_eq!(args, vec![OsString::from("a"), OsString::from("2")]);
+        assert_eq!(job.selector.describe(), "all");
+        assert!(run_options.input_mode == InputMode::Paste);
+    }
+
+    #[test]
+    fn test_interleave() {
+        let job = CutJob {
+            selector: field_parser("1").unwrap(),
+            ..Default::default()
+        };
+        let readers = vec![
+            (
+                String::from("a"),
+                BufReader::new("a1 x\na2 x\na3 x\n".as_bytes()),
+            ),
+            (String::from("b"), BufReader::new("b1 y\n".as_bytes())),
+        ];
+        let mut output = Cursor::new(vec![]);
+        assert_eq!(
+            job.process_lockstep(readers, InputMode::Interleave, &mut output)
+                .unwrap(),
+            3
+        );
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "a1\nb1\na2\n\na3\n\n"
+        );
+        // Each line goes through the whole of the selection machinery.
+        let interleave = |args: &[&str]| {
+            let (job, _, _) =
+                parse_command_line(Some([&["rcut_test", "--interleave"], args].concat())).unwrap();
+            let readers = vec![
+                (String::from("a"), BufReader::new("a 1\nb 2\n".as_bytes())),
+                (String::from("b"), BufReader::new("c 3\nnone\n".as_bytes())),
+            ];
+            let mut output = Cursor::new(vec![]);
+            job.process_lockstep(readers, InputMode::Interleave, &mut output)
+                .unwrap();
+            String::from_utf8(output.into_inner()).unwrap()
+        };
+        assert_eq!(interleave(&["-f", "1", "--complement"]), "1\n3\n2\n\n");
+        assert_eq!(interleave(&["-f", "2", "--where", "1!=b"]), "1\n3\n\n");
+        assert_eq!(interleave(&["-f", "2", "-s"]), "1\n3\n2\n");
+        assert_eq!(interleave(&["-c", "3"]), "1\n3\n2\nn\n");
+        assert_eq!(interleave(&["-b", "1"]), "a\nc\nb\nn\n");
+    }
+
+    #[test]
+    fn test_join() {
+        let spec = JoinSpec {
+            file: OsString::from("hosts"),
+            left: 2,
+            right: 1,
+            fields: Some(field_parser("2").unwrap()),
+        };
+        let delim = Delimiter::Whitespace;
+        let table = JoinTable::load(&spec, &delim, "web1 10.0.0.1\ndb1 10.0.0.2\n".as_bytes());
+        let job = CutJob {
+            input_delim: delim,
+            selector: field_parser("1").unwrap(),
+            join: Some(table.unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            exec_cut_job(job, "up web1\ndown db1\nup cache1\n").unwrap(),
+            "up 10.0.0.1\ndown 10.0.0.2\n"
+        );
+    }
+
+    #[test]
+    fn test_join_mode() {
+        let (_, args, run_options) = parse_command_line(Some(vec![
+            "rcut_test",
+            "join",
+            "--on",
+            "2=1",
+            "a.tsv",
+            "b.tsv",
+        ]))
+        .unwrap();
+        assert_eq!(args, vec![OsString::from("a.tsv")]);
+        let spec = run_options.join.unwrap();
+        assert_eq!(
+            (spec.file, spec.left, spec.right),
+            (OsString::from("b.tsv"), 2, 1)
+        );
+        let (_, args, run_options) =
+            parse_command_line(Some(vec!["rcut_test", "join", "-f", "1", "-", "a", "b"])).unwrap();
+        assert_eq!(args, vec![OsString::from("-"), OsString::from("a")]);
+        assert_eq!(run_options.join.unwrap().file, OsString::from("b"));
+        for argv in [
+            vec!["rcut_test", "join", "a.tsv"],
+            vec!["rcut_test", "join", "--join", "b.tsv", "a.tsv", "c.tsv"],
+            vec!["rcut_test", "--on", "1", "-f", "1", "a.tsv"],
+        ] {
+            assert!(parse_command_line(Some(argv)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_transpose() {
+        let (job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "--transpose", "-f", "1,3"])).unwrap();
+        assert_eq!(
+            exec_cut_job(job, "a b c\nd e f\ng h\n").unwrap(),
+            "a d g\nc f \n"
+        );
+    }
+
+    #[test]
+    fn test_expr() {
+        let (job, args, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--expr",
+            "print NR, $1, $3*1000 if $2 == \"ERR\"",
+            "app.log",
+        ]))
+        .unwrap();
+        assert_eq!(args, vec![OsString::from("app.log")]);
+        assert_eq!(
+            exec_cut_job(
+                job,
+                "web ERR 1.5
+db OK 2
+db ERR 0.25
+"
+            )
+            .unwrap(),
+            "1 web 1500\n3 db 250\n"
+        );
+        assert!(parse_command_line(Some(vec!["rcut_test", "--expr", "print $1 +"])).is_err());
+    }
+
+    #[test]
+    fn test_explode() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "-f",
+            "1,3",
+            "--explode",
+            "2:/:host,path",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, "GET 200 example.com/a/b\nPUT 201 example.org\n").unwrap(),
+            "GET example.com a/b\nPUT example.org \n"
+        );
+    }
+
+    #[test]
+    fn test_then() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "-d",
+            "\t",
+            "-f",
+            "2,3",
+            "--then",
+            "-d = -f 2",
+            "--then",
+            "-o , 1-2",
+        ]))
+        .unwrap();
+        assert!(job.then.is_some());
+        assert_eq!(
+            exec_cut_job(job, "a\tk=v w\tz\nb\tx=y\n").unwrap(),
+            "v,w\ny\n"
+        );
+        assert!(cli::parse_stage("-d =").is_err());
+        assert!(cli::parse_stage("1 2").is_err());
+    }
+
+    #[test]
+    fn test_collapsing_delimiters() {
+        let cut = |args: Vec<&str>, input: &str| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "a,,b,c\n,x,,y\n";
+        assert_eq!(cut(vec!["rcut_test", "-d", ",", "-f", "2"], input), "\nx\n");
+        let args = vec!["rcut_test", "-d", ",", "-f", "2,3", "--collapse-delims"];
+        assert_eq!(cut(args, input), "b,c\nx,y\n");
+        let args = vec!["rcut_test", "-d", ".*", "-f", "2", "--collapse-delims"];
+        assert_eq!(cut(args, "a.*.*b\n"), "b\n");
+        assert_eq!(cut(vec!["rcut_test", "-f", "2"], " a  b\n"), "b\n");
+        assert_eq!(
+            cut(vec!["rcut_test", "-f", "2-", "--no-collapse"], " a  b\n"),
+            "a  b\n"
+        );
+    }
+
+    #[test]
+    fn test_any_delimiter() {
+        let cut = |args: &[&str], input: &str| {
+            let argv = [&["rcut_test", "--any-delim", ",;|^"], args].concat();
+            let (job, _, _) = parse_command_line(Some(argv)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "a,b;c|d^e\nf;;g\n";
+        assert_eq!(cut(&["-f", "1,3-"], input), "a,c,d,e\nf,g\n");
+        assert_eq!(cut(&["-f", "2", "--collapse-delims"], input), "b\ng\n");
+        assert_eq!(cut(&["-f", "1,2", "-o", " "], input), "a b\nf \n");
+        assert!(parse_command_line(Some(vec!["rcut_test", "--any-delim", ""])).is_err());
+    }
+
+    #[test]
+    fn test_skip_and_pass_header() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) = parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "# exported 2024-01-02\nname,status,ms\na,ok,5\nb,fail,900\n";
+        assert_eq!(
+            cut(&["-d", ",", "--skip-lines", "1", "-f", "1"], input),
+            "name\na\nb\n"
+        );
+        assert_eq!(
+            cut(
+                &[
+                    "-d",
+                    ",",
+                    "--skip-lines",
+                    "1",
+                    "--pass-header",
+                    "--where",
+                    "3>100",
+                    "-f",
+                    "status,name"
+                ],
+                input
+            ),
+            "status,name\nfail,b\n"
+        );
+        assert_eq!(
+            cut(
+                &[
+                    "-d",
+                    ",",
+                    "--skip-lines",
+                    "1",
+                    "--pass-header",
+                    "--map",
+                    "1:upper",
+                    "-f",
+                    "1"
+                ],
+                input
+            ),
+            "name\nA\nB\n"
+        );
+        assert_eq!(cut(&["--skip-lines", "9"], input), "");
+    }
+
+    #[test]
+    fn test_rejects() {
+        let path = std::env::temp_dir().join(format!("rcut-rejects-job-{}", std::process::id()));
+        let argv = [
+            "rcut_test",
+            "-d",
+            ",",
+            "--strict",
+            "--map",
+            "1:upper",
+            "-f",
+            "3",
+            "--rejects",
+        ];
+        let (job, _, _) =
+            parse_command_line(Some([&argv[..], &[path.to_str().unwrap()]].concat())).unwrap();
+        let mut output = vec![];
+        job.process_reader(&b"a,b,c\nd\n\xff,x\ne,f,g\n"[..], &mut output)
+            .unwrap();
+        job.finish(&mut output).unwrap();
+        assert_eq!(output, b"C\nG\n");
+        assert_eq!(std::fs::read(&path).unwrap(), b"d\n\xff,x\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_with_filename() {
+        let argv = [
+            "rcut_test",
+            "-H",
+            "-d",
+            ",",
+            "--then",
+            "-d , -o : 2,1",
+            "-f",
+            "1,3",
+        ];
+        let (job, _, _) = parse_command_line(Some(argv.to_vec())).unwrap();
+        let mut output = vec![];
+        job.process_reader(&b"a,b,c\n"[..], &mut output).unwrap();
+        job.begin_input("x.csv");
+        job.process_reader(&b"d,e,f\n"[..], &mut output).unwrap();
+        assert_eq!(output, b"<stdin>:c:a\nx.csv:f:d\n");
+        let argv = ["rcut_test", "-H", "--no-filename", "-d", ",", "-f", "2"];
+        let (job, _, _) = parse_command_line(Some(argv.to_vec())).unwrap();
+        assert_eq!(exec_cut_job(job, "a,b\n").unwrap(), "b\n");
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) =
+                parse_command_line(Some([&["rcut_test", "--line-numbers"], args].concat()))
+                    .unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "a,1\nb,2\n#c\nd,3\n";
+        assert_eq!(
+            cut(&["-d", ",", "-o", ";", "--where", "2>1", "-f", "1"], input),
+            "2;b\n4;d\n"
+        );
+        assert_eq!(
+            cut(
+                &[
+                    "-d",
+                    ",",
+                    "--skip-comments",
+                    "#",
+                    "--keep-comments",
+                    "-f",
+                    "2"
+                ],
+                input
+            ),
+            "1,1\n2,2\n3,#c\n4,3\n"
+        );
+        assert_eq!(
+            cut(&["-d", ",", "-f", "2,1", "--then", "-d , -o : 2"], input),
+            "1:a\n2:b\n3:\n4:d\n"
+        );
+    }
+
+    #[test]
+    fn test_in_place() {
+        let suffix = |args: &[&str]| {
+            let argv = [&["rcut_test", "-d", ","], args, &["-f", "1"]].concat();
+            let (_, _, run_options) = parse_command_line(Some(argv)).unwrap();
+            run_options.in_place
+        };
+        assert_eq!(suffix(&[]), None);
+        assert_eq!(suffix(&["-i"]), Some(String::new()));
+        assert_eq!(suffix(&["-i.bak"]), Some(String::from(".bak")));
+        assert_eq!(suffix(&["-i=.orig"]), Some(String::from(".orig")));
+        assert_eq!(suffix(&["--in-place=~"]), Some(String::from("~")));
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "-i",
+            "--header",
+            "-d",
+            ",",
+            "-f",
+            "b",
+        ]))
+        .unwrap();
+        let mut output = vec![];
+        for input in ["a,b\n1,2\n", "a,b\n3,4\n"] {
+            job.process_reader(input.as_bytes(), &mut output).unwrap();
+        }
+        assert_eq!(output, b"b\n2\nb\n4\n");
+    }
+
+    #[test]
+    fn test_pad() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) =
+                parse_command_line(Some([&["rcut_test", "--pad", "NA"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "a,b,c\nd\n";
+        assert_eq!(cut(&["-d", ",", "-f", "1,3"], input), "a,c\nd,NA\n");
+        assert_eq!(cut(&["-d", ",", "-f", "2-4,^3"], input), "b,NA\nNA,NA\n");
+        assert_eq!(cut(&["-d", ",", "-f", "n-3,2-"], input), "NA,b,c\nNA\n");
+        assert_eq!(
+            cut(&["-d", ",", "--map", "2:lower", "-f", "1,2"], input),
+            "a,b\nd,na\n"
+        );
+        let selected = field_parser("1-3")
+            .unwrap()
+            .select_padded(&[&b"x"[..]], &&b"-"[..]);
+        assert_eq!(selected, vec![&b"x"[..], b"-", b"-"]);
+        assert!(parse_command_line(Some(vec!["rcut_test", "--pad", "", "-f", "1-99999"])).is_err());
+    }
+
+    #[test]
+    fn test_strict() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) =
+                parse_command_line(Some([&["rcut_test", "--strict"], args].concat())).unwrap();
+            exec_cut_job(job, input)
+        };
+        let input = "a,b,c\nd,e\n";
+        assert_eq!(cut(&["-d", ",", "-f", "1-2"], input).unwrap(), "a,b\nd,e\n");
+        assert_eq!(cut(&["-d", ",", "-f", "2-"], input).unwrap(), "b,c\ne\n");
+        for selector in ["3", "1,n-2", "1-3"] {
+            let err = cut(&["-d", ",", "-f", selector], input).unwrap_err();
+            assert_eq!(err.downcast_ref::<InputLine>().unwrap().0, 2);
+            assert!(err.is::<MissingFields>());
+        }
+        // Not only through the fast path for plain jobs.
+        let err = cut(&["-d", ",", "--map", "1:upper", "-f", "3"], input).unwrap_err();
+        assert!(err.is::<MissingFields>());
+    }
+
+    #[test]
+    fn test_skip_comments() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) = parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "# generated\nname,ms\n# note\na,5\nb,900\n";
+        assert_eq!(
+            cut(
+                &["-d", ",", "--skip-comments", "#", "--header", "-f", "ms"],
+                input
+            ),
+            "ms\n5\n900\n"
+        );
+        assert_eq!(
+            cut(
+                &[
+                    "-d",
+                    ",",
+                    "--skip-comments",
+                    "#",
+                    "--keep-comments",
+                    "-f",
+                    "1"
+                ],
+                input
+            ),
+            "# generated\nname\n# note\na\nb\n"
+        );
+        assert_eq!(
+            cut(
+                &["-d", ",", "--skip-comments", "//", "-f", "2"],
+                "//a,b\nc,d\n"
+            ),
+            "d\n"
+        );
+        assert!(parse_command_line(Some(vec!["rcut_test", "--keep-comments"])).is_err());
+    }
+
+    #[test]
+    fn test_where() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) = parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let log = "web1 ERROR 250 disk\nweb2 INFO 50 ok\nweb3 ERROR 90 net\n";
+        assert_eq!(
+            cut(
+                &["--where", "2==ERROR", "--where", "3>100", "-f", "1,4"],
+                log
+            ),
+            "web1 disk\n"
+        );
+        assert_eq!(
+            cut(&["--where", "-1~^n|^o", "-f", "1"], log),
+            "web2\nweb3\n"
+        );
+        assert_eq!(
+            cut(&["--where", "2!=INFO", "--passthrough", "-f", "3"], log),
+            "web1 ERROR 250 disk 250\nweb2 INFO 50 ok\nweb3 ERROR 90 net 90\n"
+        );
+        assert_eq!(
+            cut(
+                &[
+                    "-d",
+                    ";",
+                    "--numeric-locale",
+                    "de",
+                    "--where",
+                    "2>=1000",
+                    "-f",
+                    "1"
+                ],
+                "a;1.250,5\nb;999\n"
+            ),
+            "a\n"
+        );
+    }
+
+    #[test]
+    fn test_extract() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) = parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let log = "GET /a 200 12ms\ngarbage\nPOST /b 500 3ms\n";
+        assert_eq!(
+            cut(&["--extract", r"^(\w+) (\S+) (\d+)", "-f", "3,1"], log),
+            "200 GET\n500 POST\n"
+        );
+        let named = r"(?P<method>\w+) (?P<path>/\S*)(?: (?P<status>9\d\d))?";
+        assert_eq!(
+            cut(&["--extract", named, "-f", "path,status,method"], log),
+            "/a  GET\n/b  POST\n"
+        );
+        for bad in [
+            vec!["--extract", "a+"],
+            vec!["--extract", named, "-f", "size"],
+        ] {
+            assert!(parse_command_line(Some([&["rcut_test"], &bad[..]].concat())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_subfield() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "-t",
+            "-f",
+            "3,1",
+            "--subfield",
+            "1:@:2",
+            "--subfield",
+            "2:/:1",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, "1/a\tx\tann@web1\n2\ty\tbo\n").unwrap(),
+            "web1\t1\n\t2\n"
+        );
+    }
+
+    #[test]
+    fn test_field_maps() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) = parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "alice,  New   York ,ny\n";
+        assert_eq!(
+            cut(
+                &["-d", ",", "-f", "3,2", "--map", "1:upper", "--map", "2:trim"],
+                input
+            ),
+            "NY,New   York\n"
+        );
+        assert_eq!(
+            cut(
+                &["-d", ",", "--map", "-2:squeeze-space", "--map", "1:upper"],
+                input
+            ),
+            "ALICE, New York ,ny\n"
+        );
+    }
+
+    #[test]
+    fn test_format_template() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) = parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "a:b:c\nx:y\n";
+        assert_eq!(
+            cut(&["-d", ":", "--format", r"{1}\t{3}:{2}"], input),
+            "a\tc:b\nx\t:y\n"
+        );
+        assert_eq!(
+            cut(&["-d", ":", "--format", "{-1}-{1}-{-1}"], input),
+            "c-a-c\ny-x-y\n"
+        );
+        assert!(parse_command_line(Some(vec!["rcut_test", "--format", "{1}", "-f", "2"])).is_err());
+    }
+
+    #[test]
+    fn test_max_fields() {
+        let cut = |args: &[&str], input: &str| {
+            let (job, _, _) = parse_command_line(Some([&["rcut_test"], args].concat())).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let log = "2024-01-02 ERROR disk full: /var at 99%\nshort\n";
+        assert_eq!(
+            cut(&["--max-fields", "3", "-f", "3,1"], log),
+            "disk full: /var at 99% 2024-01-02\nshort\n"
+        );
+        let input = "k=v=w=x\n";
+        assert_eq!(
+            cut(&["-d", "=", "--max-fields", "2", "-f", "2"], input),
+            "v=w=x\n"
+        );
+        assert_eq!(
+            cut(&["-d", "=", "--max-fields", "1", "-f", "1"], input),
+            "k=v=w=x\n"
+        );
+        assert_eq!(
+            cut(&["-E", "=+", "--max-fields", "2", "-f", "2"], "a==b=c\n"),
+            "b=c\n"
+        );
+        // The fields of the plain path and of the slower one agree.
+        let args = [
+            "-d",
+            ",",
+            "--max-fields",
+            "3",
+            "--passthrough",
+            "append",
+            "-f",
+            "3",
+        ];
+        assert_eq!(cut(&args, "a,b,c,d\n"), "a,b,c,d,c,d\n");
+        assert_eq!(
+            cut(&["-d", ",", "--max-fields", "3", "-f", "3"], "a,b,c,d\n"),
+            "c,d\n"
+        );
+        let plain = cut(&["--max-fields", "2", "-f", "2"], "a b c  \n");
+        assert_eq!(plain, "b c\n");
+        assert_eq!(
+            cut(&["--max-fields", "2", "-f", "2", "--unique"], "a b c  \n"),
+            plain
+        );
+        assert!(parse_command_line(Some(vec!["rcut_test", "--max-fields", "0"])).is_err());
+        let args = [
+            "-d",
+            ":",
+            "--max-fields",
+            "2",
+            "--rsplit",
+            "-f",
+            "2,1",
+            "-o",
+            " ",
+        ];
+        assert_eq!(cut(&args, "a:b:c\nd\n"), "c a:b\nd\n");
+        let args = [
+            "--max-fields",
+            "3",
+            "--rsplit",
+            "-f",
+            "1",
+            "--passthrough",
+            "append",
+        ];
+        assert_eq!(cut(&args, " a b c d \n"), " a b c d  a b\n");
+        assert!(parse_command_line(Some(vec!["rcut_test", "--rsplit"])).is_err());
+    }
+
+    #[test]
+    fn test_fixed_widths() {
+        let fixed = Delimiter::widths(&[3, 2, 4]);
+        assert_eq!(fixed.describe(), "columns 3,2,4 wide");
+        assert_eq!(fixed.split("ab cdé  fgh"), vec!["ab", "cd", "é  f"]);
+        assert_eq!(fixed.split("ab"), vec!["ab", "", ""]);
+        let cut = |args: Vec<&str>, input: &str| {
+            let (job, _, _) = parse_command_line(Some(args)).unwrap();
+            exec_cut_job(job, input).unwrap()
+        };
+        let input = "ID  NAME   \n1   Ann Lee\n";
+        assert_eq!(
+            cut(vec!["rcut_test", "--widths", "4,7", "-f", "2,1"], input),
+            "NAME ID\nAnn Lee 1\n"
+        );
+        assert!(parse_command_line(Some(vec!["rcut_test", "--widths", "4,x"])).is_err());
+        let widths = load_widths(Cursor::new("# id, name\n4\n\n7,2\n")).unwrap();
+        assert_eq!(widths, vec![4, 7, 2]);
+        assert!(load_widths(Cursor::new("# nothing\n")).is_err());
+    }
+
+    #[test]
+    fn test_infer_columns() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--infer-columns",
+            "-o",
+            "|",
+            "-f",
+            "1,3",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(
+                job,
+                "NAME      STATUS     PORTS\nweb one   Up 1 day   80/tcp\ndb        Exited     \n"
+            )
+            .unwrap(),
+            "NAME|PORTS\nweb one|80/tcp\ndb|\n"
+        );
+    }
+
+    #[test]
+    fn test_passthrough() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--passthrough",
+            "--expr",
+            "$2 * 2 if $1 == \"a\"",
+        ]))
+        .unwrap();
+        assert_eq!(exec_cut_job(job, "a 1\nb 2\n").unwrap(), "a 1 2\nb 2\n");
+        let (job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "--passthrough=prepend", "-f", "2"]))
+                .unwrap();
+        assert_eq!(exec_cut_job(job, "a 1\n").unwrap(), "1 a 1\n");
+    }
+
+    #[test]
+    fn test_sort_by() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "-f",
+            "2,1",
+            "--sort-by",
+            "2:numeric:desc",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, "9 a\n10 b\n2 c\n").unwrap(),
+            "b 10\na 9\nc 2\n"
+        );
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--header",
+            "-d",
+            ",",
+            "-f",
+            "name,n",
+            "--sort-by",
+            "2:n",
+            "--table",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, "name,n\nbb,10\na,9\n").unwrap(),
+            "name  n\na     9\nbb    10\n"
+        );
+    }
+
+    #[test]
+    fn test_complement() {
+        let job = |fields: &str| {
+            let args = vec!["rcut_test", "-d", ",", "--complement", "-f", fields];
+            parse_command_line(Some(args)).unwrap().0
+        };
+        assert_eq!(exec_cut_job(job("2"), "a,b,c\nd\n").unwrap(), "a,c\nd\n");
+        assert_eq!(exec_cut_job(job("3,1"), "a,b,c,d\n").unwrap(), "b,d\n");
+        assert_eq!(exec_cut_job(job("-1"), "a,b,c\n").unwrap(), "a,b\n");
+        assert_eq!(exec_cut_job(job("1-9"), "a,b\n").unwrap(), "\n");
+    }
+
+    #[test]
+    fn test_dedup_key() {
+        let input = "alice login\nbob login\nalice logout\n";
+        let (job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "--dedup-key", "2", "-f", "2,1"])).unwrap();
+        assert_eq!(
+            exec_cut_job(job, input).unwrap(),
+            "login alice\nlogin bob\n"
+        );
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--dedup-key",
+            "1",
+            "--keep",
+            "last",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, input).unwrap(),
+            "bob login\nalice logout\n"
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_header() {
+        // A resumed run starts partway through, where the header isn't.
+        for flag in ["--header", "--pass-header"] {
+            let args = vec!["rcut_test", "--checkpoint", "c", flag, "-f", "1", "a"];
+            assert!(parse_command_line(Some(args)).is_err(), "{}", flag);
+        }
+    }
+
+    #[test]
+    fn test_max_memory() {
+        let input = "alice login\nbob login\nalice logout\n";
+        let job = |cap: &str| {
+            let args = vec!["rcut_test", "--sort-by", "1", "--max-memory", cap];
+            parse_command_line(Some(args)).unwrap().0
+        };
+        assert!(exec_cut_job(job("1M"), input).is_ok());
+        let err = exec_cut_job(job("100"), input).unwrap_err();
+        assert!(err.to_string().contains("--max-memory"), "{}", err);
+        assert!(exec_cut_job(job("5"), input).is_err());
+    }
+
+    #[test]
+    fn test_group_by() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--group-by",
+            "1",
+            "--agg",
+            "count,sum(2)",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, "a 1\nb 2\na 3\n").unwrap(),
+            "a 2 4\nb 1 2\n"
+        );
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--csv",
+            "--header",
+            "--group-by",
+            "1",
+            "--agg",
+            "count,sum(2)",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, "host,ms\na,1\nb,2\na,3\n").unwrap(),
+            "host,count,sum(ms)\na,2,4\nb,1,2\n"
+        );
+    }
+
+    #[test]
+    fn test_unique() {
+        let cut = |flag: &str| {
+            let argv = vec!["rcut_test", flag, "-d", ",", "-f", "2,3"];
+            let (job, _, _) = parse_command_line(Some(argv)).unwrap();
+            exec_cut_job(job, "1,a,x\n2,a,x\n3,b,x\n4,a,x\n5,a,y\n").unwrap()
+        };
+        assert_eq!(cut("--unique"), "a,x\nb,x\na,y\n");
+        assert_eq!(cut("--unique-consecutive"), "a,x\nb,x\na,x\na,y\n");
+        // The header isn't a record to drop the same as later.
+        for args in [
+            &["--unique"][..],
+            &["--unique-consecutive"],
+            &["--dedup-key", "1"],
+            &["--dedup-key", "1", "--keep", "last"],
+        ] {
+            let argv = [&["rcut_test", "--header", "-f", "k"], args].concat();
+            let (job, _, _) = parse_command_line(Some(argv)).unwrap();
+            assert_eq!(exec_cut_job(job, "k\nk\na\n").unwrap(), "k\nk\na\n");
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let (job, _, _) = parse_command_line(Some(vec![
+            "rcut_test",
+            "--stats",
+            "--csv",
+            "--header",
+            "-f",
+            "ms,bytes",
+        ]))
+        .unwrap();
+        assert_eq!(
+            exec_cut_job(job, "host,ms,bytes\nweb,3,10\ndb,5,x\n").unwrap(),
+            "field,count,min,max,mean,sum\nms,2,3,5,4,8\nbytes,1,10,10,10,10\n"
+        );
+    }
+
+    #[test]
+    fn test_count() {
+        let (job, _, _) =
+            parse_command_line(Some(vec!["rcut_test", "--count", "-d", ",", "-f", "2"])).unwrap();
+        assert_eq!(
+            exec_cut_job(job, "1,b\n2,a\n3,a\n4,c\n5,b\n6,a\n").unwrap(),
+            "3,a\n2,b\n1,c\n"
+        );
+        let argv = vec!["rcut_test", "--count", "--header", "-d", ",", "-f", "name"];
+        let (job, _, _) = parse_command_line(Some(argv)).unwrap();
+        assert_eq!(
+            exec_cut_job(job, "id,name\n1,b\n2,a\n3,b\n").unwrap(),
+            "count,name\n2,b\n1,a\n"
+        );
+    }
+
+    #[test]
+    fn test_cut_job() {
+        let simple_alphabet = "a b c d e f g\np q r s t u\ni j k\n";
+        let job = CutJob {
+            input_delim: Delimiter::Whitespace,
+            selector: field_parser("-1").unwrap(),
+            output_separator: " ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(exec_cut_job(job, simple_alphabet).unwrap(), "g\nu\nk\n");
+
+        let job = CutJob {
+            input_delim: Delimiter::Whitespace,
+            selector: field_parser("1-3").unwrap(),
+            output_separator: " ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            exec_cut_job(job, simple_alphabet).unwrap(),
+            "a b c\np q r\ni j k\n"
+        );
+    }
+}
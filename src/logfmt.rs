@@ -0,0 +1,151 @@
+use std::borrow::Cow;
+
+// The keys --logfmt picks out of each line's `key=value` pairs, in
+// selector order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keys(Vec<String>);
+
+impl Keys {
+    pub fn new(keys: Vec<String>) -> Self {
+        Keys(keys)
+    }
+
+    pub fn describe(&self) -> String {
+        format!("logfmt keys {}", self.0.join(", "))
+    }
+
+    // The value of each key in a line, where a key the line doesn't
+    // have, or has without a value, is empty, and one it has twice has
+    // the later value.
+    pub fn fields<'a>(&self, line: &'a str) -> Vec<Cow<'a, str>> {
+        let pairs = pairs(line);
+        self.0
+            .iter()
+            .map(|key| {
+                pairs
+                    .iter()
+                    .rev()
+                    .find(|(name, _)| name == key)
+                    .map_or(Cow::Borrowed(""), |(_, value)| value.clone())
+            })
+            .collect()
+    }
+}
+
+// Split a line into its pairs: `key=value`, `key="a quoted value"`,
+// where a backslash escapes a quote, a backslash or, as \n or \t, a
+// newline or tab, or a bare `key`.  A quote that's never closed runs
+// to the end of the line.
+fn pairs(line: &str) -> Vec<(&str, Cow<'_, str>)> {
+    let mut pairs = vec![];
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        let end = rest.find(['=', ' ', '\t']).unwrap_or(rest.len());
+        let key = &rest[..end];
+        rest = &rest[end..];
+        let value = match rest.strip_prefix('=') {
+            Some(after) if after.starts_with('"') => {
+                let (value, after) = quoted(&after[1..]);
+                rest = after;
+                value
+            }
+            Some(after) => {
+                let end = after.find([' ', '\t']).unwrap_or(after.len());
+                rest = &after[end..];
+                Cow::Borrowed(&after[..end])
+            }
+            None => Cow::Borrowed(""),
+        };
+        if !key.is_empty() {
+            pairs.push((key, value));
+        }
+        rest = rest.trim_start();
+    }
+    pairs
+}
+
+// A quoted value, starting just after its opening quote, and what
+// follows its closing one.
+fn quoted(s: &str) -> (Cow<'_, str>, &str) {
+    let Some(end) = s.find(['"', '\\']) else {
+        return (Cow::Borrowed(s), "");
+    };
+    if s.as_bytes()[end] == b'"' {
+        return (Cow::Borrowed(&s[..end]), &s[end + 1..]);
+    }
+    let mut value = String::from(&s[..end]);
+    let mut chars = s[end..].char_indices();
+    while let Some((at, c)) = chars.next() {
+        match c {
+            '"' => return (Cow::Owned(value), &s[end + at + 1..]),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, c)) => value.push(c),
+                None => value.push('\\'),
+            },
+            c => value.push(c),
+        }
+    }
+    (Cow::Owned(value), "")
+}
+
+// A value as a logfmt pair holds it: as it is, unless it has
+// whitespace, a quote, `=` or a backslash in it, or is empty, when it's
+// quoted, with quotes, backslashes, newlines and tabs escaped.
+pub fn quote(value: &str) -> Cow<'_, str> {
+    let plain = !value.is_empty()
+        && !value
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '"' | '=' | '\\'));
+    if plain {
+        return Cow::Borrowed(value);
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    Cow::Owned(quoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields() {
+        let keys = |keys: &[&str]| Keys::new(keys.iter().map(|k| k.to_string()).collect());
+        let line = r#"level=info msg="disk \"sda\" full" host=web1 ok t=1 t=2 path="a\\b\tc""#;
+        assert_eq!(
+            keys(&["msg", "host", "level", "ok", "t", "missing", "path"]).fields(line),
+            vec![r#"disk "sda" full"#, "web1", "info", "", "2", "", "a\\b\tc"]
+        );
+        assert_eq!(
+            keys(&["a", "b"]).fields(r#"  a= b="open to the end"#),
+            vec!["", "open to the end"]
+        );
+        assert!(matches!(
+            keys(&["msg"]).fields(r#"msg="plain""#)[0],
+            Cow::Borrowed("plain")
+        ));
+        assert_eq!(keys(&["x"]).fields(""), vec![""]);
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("web1"), "web1");
+        assert_eq!(quote(""), "\"\"");
+        assert_eq!(quote("a=b"), "\"a=b\"");
+        let value = "disk \"sda\"\tfull\\\n";
+        assert_eq!(quote(value), r#""disk \"sda\"\tfull\\\n""#);
+        let keys = Keys::new(vec![String::from("v")]);
+        assert_eq!(keys.fields(&format!("v={}", quote(value))), vec![value]);
+    }
+}
@@ -0,0 +1,81 @@
+use std::mem;
+
+use anyhow::Result;
+
+use crate::{i18n::trf, throttle::byte_scale};
+
+// Parse a size like `512M`, `2GiB` or `100000`: a number of bytes with
+// an optional size suffix, as --throttle takes them.
+pub fn parse_size(s: &str) -> Result<u64> {
+    let invalid = || format_err!(trf("invalid size '{}'", &[s]));
+    let split = s
+        .trim()
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.trim().len());
+    let (number, suffix) = s.trim().split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let scale = match suffix.trim().to_ascii_lowercase().as_str() {
+        "" => 1.0,
+        suffix => byte_scale(suffix).ok_or_else(invalid)?,
+    };
+    let bytes = (number * scale) as u64;
+    if bytes == 0 {
+        return Err(invalid());
+    }
+    Ok(bytes)
+}
+
+// Roughly what a row of fields costs to hold on to, counting each
+// String as well as its text.
+pub fn row_size<S: AsRef<str>>(fields: &[S]) -> usize {
+    fields
+        .iter()
+        .map(|field| field.as_ref().len() + mem::size_of::<String>())
+        .sum::<usize>()
+        + mem::size_of::<Vec<String>>()
+}
+
+// The most memory --max-memory lets the job's buffers take up.  The
+// accounting is approximate, going by the text held rather than by
+// what the allocator hands out, but it grows with the same things.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryCap(pub u64);
+
+impl MemoryCap {
+    // Fail once `used` bytes of `what` is over the cap.
+    pub fn check(&self, used: usize, what: &str) -> Result<()> {
+        if used as u64 > self.0 {
+            bail!(trf(
+                "{} took more than the --max-memory limit of {} bytes",
+                &[what, &self.0.to_string()]
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512").unwrap(), 512);
+        assert_eq!(parse_size("512M").unwrap(), 512_000_000);
+        assert_eq!(parse_size("2GiB").unwrap(), 2 << 30);
+        assert_eq!(parse_size("1.5k").unwrap(), 1500);
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("12 parsecs").is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let cap = MemoryCap(100);
+        assert!(cap.check(100, "sorting").is_ok());
+        let err = cap.check(101, "sorting").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "sorting took more than the --max-memory limit of 100 bytes"
+        );
+    }
+}
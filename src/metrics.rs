@@ -0,0 +1,229 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    net::UdpSocket,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use tracing::warn;
+
+use crate::i18n::trf;
+
+// How often metrics are written out.
+const INTERVAL: Duration = Duration::from_secs(10);
+
+// Where --metrics sends them: a file for Prometheus' node exporter to
+// pick up as a text file collector, or a statsd daemon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sink {
+    Prometheus(PathBuf),
+    Statsd(String),
+}
+
+// Parse `prometheus:PATH` or `statsd:HOST:PORT`.
+pub fn parse_sink(s: &str) -> Result<Sink> {
+    match s.split_once(':') {
+        Some(("prometheus", path)) if !path.is_empty() => Ok(Sink::Prometheus(PathBuf::from(path))),
+        Some(("statsd", addr)) if !addr.is_empty() => Ok(Sink::Statsd(String::from(addr))),
+        _ => bail!(trf(
+            "invalid metrics sink '{}': expected prometheus:PATH or statsd:ADDR",
+            &[s]
+        )),
+    }
+}
+
+// Counters kept by the long-running modes, shared with the thread that
+// exports them.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    records: AtomicU64,
+    bytes: AtomicU64,
+    errors: AtomicU64,
+    // The file being followed, whose length less what's been read is
+    // how far behind the input is.
+    followed: Option<PathBuf>,
+}
+
+impl Metrics {
+    pub fn new(followed: Option<PathBuf>) -> Self {
+        Metrics {
+            followed,
+            ..Default::default()
+        }
+    }
+
+    pub fn read(&self, records: u64, bytes: u64) {
+        self.records.fetch_add(records, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn error(&self) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let bytes = self.bytes.load(Ordering::Relaxed);
+        Snapshot {
+            records: self.records.load(Ordering::Relaxed),
+            bytes,
+            errors: self.errors.load(Ordering::Relaxed),
+            lag: self
+                .followed
+                .as_ref()
+                .and_then(|path| fs::metadata(path).ok())
+                .map(|meta| meta.len().saturating_sub(bytes)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Snapshot {
+    records: u64,
+    bytes: u64,
+    errors: u64,
+    lag: Option<u64>,
+}
+
+impl Snapshot {
+    // The text file collector format, counters labelled by mode.
+    fn prometheus(&self, mode: &str) -> String {
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: u64| {
+            let _ = write!(
+                text,
+                "# HELP {name} {help}\n# TYPE {name} {kind}\n{name}{{mode=\"{mode}\"}} {value}\n"
+            );
+        };
+        metric(
+            "rcut_records_total",
+            "counter",
+            "Records processed.",
+            self.records,
+        );
+        metric(
+            "rcut_bytes_read_total",
+            "counter",
+            "Bytes of input read.",
+            self.bytes,
+        );
+        metric(
+            "rcut_parse_errors_total",
+            "counter",
+            "Inputs that could not be cut.",
+            self.errors,
+        );
+        if let Some(lag) = self.lag {
+            metric(
+                "rcut_lag_bytes",
+                "gauge",
+                "Bytes of the followed file not yet read.",
+                lag,
+            );
+        }
+        text
+    }
+
+    // Statsd lines, with counters sent as the change since `last`.
+    fn statsd(&self, mode: &str, last: &Snapshot) -> String {
+        let mut text = format!(
+            "rcut.{mode}.records:{}|c\nrcut.{mode}.bytes_read:{}|c\nrcut.{mode}.parse_errors:{}|c\n",
+            self.records - last.records,
+            self.bytes - last.bytes,
+            self.errors - last.errors
+        );
+        if let Some(lag) = self.lag {
+            let _ = writeln!(text, "rcut.{mode}.lag_bytes:{}|g", lag);
+        }
+        text
+    }
+}
+
+// Write the metrics out every so often, on a thread of their own, for
+// as long as the process runs.  A sink that can't be set up at all is
+// an error now; one that fails later is only logged.
+pub fn export(sink: Sink, metrics: Arc<Metrics>, mode: &'static str) -> Result<()> {
+    let socket = match sink {
+        Sink::Statsd(ref addr) => {
+            let socket = UdpSocket::bind("0.0.0.0:0")?;
+            socket.connect(addr).with_context(|| addr.clone())?;
+            Some(socket)
+        }
+        Sink::Prometheus(_) => None,
+    };
+    thread::spawn(move || {
+        let mut last = Snapshot::default();
+        loop {
+            let now = metrics.snapshot();
+            let sent = match (&sink, &socket) {
+                (Sink::Statsd(_), Some(socket)) => {
+                    socket.send(now.statsd(mode, &last).as_bytes()).map(|_| ())
+                }
+                (Sink::Prometheus(path), _) => {
+                    // Written aside and renamed, so the collector never
+                    // sees half a file.
+                    let partial = path.with_extension("prom.partial");
+                    fs::write(&partial, now.prometheus(mode))
+                        .and_then(|_| fs::rename(&partial, path))
+                }
+                _ => Ok(()),
+            };
+            if let Err(err) = sent {
+                warn!(%err, "could not export metrics");
+            }
+            last = now;
+            thread::sleep(INTERVAL);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sink() {
+        assert_eq!(
+            parse_sink("prometheus:/var/lib/node/rcut.prom").unwrap(),
+            Sink::Prometheus(PathBuf::from("/var/lib/node/rcut.prom"))
+        );
+        assert_eq!(
+            parse_sink("statsd:127.0.0.1:8125").unwrap(),
+            Sink::Statsd(String::from("127.0.0.1:8125"))
+        );
+        assert!(parse_sink("graphite:x").is_err());
+        assert!(parse_sink("statsd:").is_err());
+    }
+
+    #[test]
+    fn test_formats() {
+        let last = Snapshot {
+            records: 10,
+            bytes: 100,
+            errors: 0,
+            lag: None,
+        };
+        let now = Snapshot {
+            records: 15,
+            bytes: 160,
+            errors: 1,
+            lag: Some(40),
+        };
+        assert_eq!(
+            now.statsd("top", &last),
+            "rcut.top.records:5|c\nrcut.top.bytes_read:60|c\nrcut.top.parse_errors:1|c\nrcut.top.lag_bytes:40|g\n"
+        );
+        let text = now.prometheus("top");
+        assert!(text
+            .contains("# TYPE rcut_records_total counter\nrcut_records_total{mode=\"top\"} 15\n"));
+        assert!(text.contains("rcut_lag_bytes{mode=\"top\"} 40\n"));
+        assert!(!last.prometheus("serve").contains("rcut_lag_bytes"));
+    }
+}
@@ -0,0 +1,93 @@
+use std::{fs::File, ops::Deref};
+
+// A read-only mapping of a whole file, so that a big file can be cut
+// straight out of the page cache without being copied through a read
+// buffer first.
+pub struct Mapping {
+    #[cfg(unix)]
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl Mapping {
+    // Map `file`, or None if it can't be: only regular files with
+    // something in them can be mapped.
+    #[cfg(unix)]
+    pub fn new(file: &File) -> Option<Mapping> {
+        use std::{os::unix::io::AsRawFd, ptr};
+
+        let meta = file.metadata().ok()?;
+        let len = usize::try_from(meta.len()).ok()?;
+        if !meta.is_file() || len == 0 {
+            return None;
+        }
+        // SAFETY: a private, read-only mapping of a file held open for
+        // as long as the mapping lives.  Another process writing to the
+        // file changes what is read, as it would between reads; one
+        // truncating it makes reading past the new end fault, which is
+        // the price of mapping.
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return None;
+        }
+        // SAFETY: advice about the mapping just made; it can't fail in
+        // any way that matters.
+        unsafe { libc::madvise(ptr, len, libc::MADV_SEQUENTIAL) };
+        Some(Mapping { ptr, len })
+    }
+
+    #[cfg(not(unix))]
+    pub fn new(_file: &File) -> Option<Mapping> {
+        None
+    }
+}
+
+impl Deref for Mapping {
+    type Target = [u8];
+
+    #[cfg(unix)]
+    fn deref(&self) -> &[u8] {
+        // SAFETY: the mapping is `len` readable bytes until it's dropped.
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+
+    #[cfg(not(unix))]
+    fn deref(&self) -> &[u8] {
+        &[]
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        // SAFETY: unmapping what `new` mapped, once.
+        unsafe { libc::munmap(self.ptr, self.len) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping() {
+        let path = std::env::temp_dir().join(format!("rcut-mmap-{}", std::process::id()));
+        std::fs::write(&path, "a b\nc d\n").unwrap();
+        let mapping = Mapping::new(&File::open(&path).unwrap());
+        if cfg!(unix) {
+            assert_eq!(&mapping.unwrap()[..], b"a b\nc d\n");
+        }
+        std::fs::write(&path, "").unwrap();
+        assert!(Mapping::new(&File::open(&path).unwrap()).is_none());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
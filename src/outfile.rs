@@ -0,0 +1,193 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, StdoutLock, Write},
+    path::PathBuf,
+    process::Stdio,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    decompress::{Codec, Encoder},
+    diagnostics::InputFile,
+};
+
+// The file -O writes to, or one -i rewrites.  The output goes to a
+// file alongside it, which is only renamed over it once the run has
+// succeeded, so that a run that fails part way through leaves whatever
+// was there before as it was.  Dropped without being committed, the
+// partial file is removed.
+#[derive(Debug)]
+pub struct OutputFile {
+    path: PathBuf,
+    partial: PathBuf,
+    file: File,
+    // Where what was there before is kept, if anywhere, for -i.SUFFIX.
+    backup: Option<PathBuf>,
+    committed: bool,
+}
+
+impl OutputFile {
+    pub fn create(path: &OsString) -> Result<Self> {
+        let path = PathBuf::from(path);
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+        let name = || InputFile(path.to_string_lossy().into_owned());
+        let file = File::create(&partial).with_context(name)?;
+        // A file being replaced keeps its permissions.
+        if let Ok(meta) = fs::metadata(&path) {
+            file.set_permissions(meta.permissions())
+                .with_context(name)?;
+        }
+        Ok(OutputFile {
+            path,
+            partial,
+            file,
+            backup: None,
+            committed: false,
+        })
+    }
+
+    // Keep the file being replaced, under its name with `suffix` added.
+    pub fn with_backup(mut self, suffix: &str) -> Self {
+        let mut backup = self.path.as_os_str().to_owned();
+        backup.push(suffix);
+        self.backup = Some(PathBuf::from(backup));
+        self
+    }
+
+    pub fn commit(&mut self) -> Result<()> {
+        let name = InputFile(self.path.to_string_lossy().into_owned());
+        self.file
+            .sync_all()
+            .and_then(|_| match self.backup {
+                Some(ref backup) => fs::rename(&self.path, backup),
+                None => Ok(()),
+            })
+            .and_then(|_| fs::rename(&self.partial, &self.path))
+            .context(name)?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for OutputFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.partial);
+        }
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+// Where the output of a run goes: stdout, or with -O a file, either
+// of them maybe through a compressor, which writes there itself.
+pub enum Destination<'a> {
+    Stdout(StdoutLock<'a>),
+    File(OutputFile),
+    Encoded(Encoder, Option<OutputFile>),
+}
+
+impl<'a> Destination<'a> {
+    pub fn stdout(stdout: StdoutLock<'a>, codec: Option<Codec>) -> Result<Self> {
+        match codec {
+            Some(codec) => Ok(Destination::Encoded(
+                Encoder::spawn(codec, Stdio::inherit())?,
+                None,
+            )),
+            None => Ok(Destination::Stdout(stdout)),
+        }
+    }
+
+    pub fn file(file: OutputFile, codec: Option<Codec>) -> Result<Self> {
+        match codec {
+            Some(codec) => {
+                let name = || InputFile(file.path.to_string_lossy().into_owned());
+                let output = file.file.try_clone().with_context(name)?;
+                let encoder = Encoder::spawn(codec, Stdio::from(output))?;
+                Ok(Destination::Encoded(encoder, Some(file)))
+            }
+            None => Ok(Destination::File(file)),
+        }
+    }
+
+    // Once the output is all written and flushed, put it in place.
+    pub fn commit(&mut self) -> Result<()> {
+        match self {
+            Destination::Stdout(_) => Ok(()),
+            Destination::File(file) => file.commit(),
+            Destination::Encoded(encoder, file) => {
+                encoder.finish()?;
+                match file {
+                    Some(file) => file.commit(),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+impl Write for Destination<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Destination::Stdout(stdout) => stdout.write(buf),
+            Destination::File(file) => file.write(buf),
+            Destination::Encoded(encoder, _) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Destination::Stdout(stdout) => stdout.flush(),
+            Destination::File(file) => file.flush(),
+            Destination::Encoded(encoder, _) => encoder.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_file() {
+        let path = std::env::temp_dir().join(format!("rcut-output-{}", std::process::id()));
+        fs::write(&path, "old\n").unwrap();
+        let mut output = OutputFile::create(&path.clone().into_os_string()).unwrap();
+        output.file.write_all(b"new\n").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"old\n");
+        drop(output);
+        assert_eq!(fs::read(&path).unwrap(), b"old\n");
+        let mut output =
+            Destination::File(OutputFile::create(&path.clone().into_os_string()).unwrap());
+        output.write_all(b"new\n").unwrap();
+        output.commit().unwrap();
+        drop(output);
+        assert_eq!(fs::read(&path).unwrap(), b"new\n");
+        let mut partial = path.clone().into_os_string();
+        partial.push(".partial");
+        assert!(!PathBuf::from(partial).exists());
+        let mut output = OutputFile::create(&path.clone().into_os_string())
+            .unwrap()
+            .with_backup(".bak");
+        output.write_all(b"newer\n").unwrap();
+        output.commit().unwrap();
+        let mut backup = path.clone().into_os_string();
+        backup.push(".bak");
+        assert_eq!(fs::read(&path).unwrap(), b"newer\n");
+        assert_eq!(fs::read(&backup).unwrap(), b"new\n");
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&backup).unwrap();
+    }
+}
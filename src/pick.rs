@@ -0,0 +1,444 @@
+use std::{
+    borrow::Cow,
+    ffi::OsString,
+    io::{self, BufRead, IsTerminal, Write},
+};
+
+use anyhow::{Context, Result};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+
+use crate::{
+    cli::PickArgs, diagnostics::InputFile, i18n::tr, open_input, Delimiter, FieldRange,
+    FieldSelector,
+};
+
+// How much of the file the picker reads, to show and to size columns.
+const PREVIEW_LINES: usize = 200;
+// Wider columns are cut short on screen, though not in the output.
+const MAX_COLUMN_WIDTH: usize = 24;
+// Space between columns on screen.
+const GUTTER: usize = 2;
+
+// What the user did to leave the picker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Accept,
+    Quit,
+}
+
+// A screenful of the file split into columns, and the columns chosen
+// so far.
+#[derive(Debug)]
+struct Picker {
+    file: OsString,
+    lines: Vec<String>,
+    delimiter: Delimiter,
+    selected: Vec<bool>,
+    cursor: usize,
+    // The first column on screen, once the cursor has moved off the
+    // right edge.
+    offset: usize,
+    // The new delimiter as typed so far, while one is being edited.
+    editing: Option<String>,
+    message: Option<&'static str>,
+}
+
+impl Picker {
+    fn new(file: OsString, lines: Vec<String>, delimiter: Delimiter) -> Self {
+        let mut picker = Picker {
+            file,
+            lines,
+            delimiter,
+            selected: vec![],
+            cursor: 0,
+            offset: 0,
+            editing: None,
+            message: None,
+        };
+        picker.resplit();
+        picker
+    }
+
+    fn rows(&self) -> Vec<Vec<Cow<'_, str>>> {
+        self.lines
+            .iter()
+            .map(|line| self.delimiter.split(line))
+            .collect()
+    }
+
+    // After a delimiter change the old columns mean nothing, so start
+    // the selection over.
+    fn resplit(&mut self) {
+        let columns = self.rows().iter().map(Vec::len).max().unwrap_or(0);
+        self.selected = vec![false; columns];
+        self.cursor = 0;
+        self.offset = 0;
+    }
+
+    // How wide each column is on screen: its widest sample, or its
+    // header, whichever is wider.
+    fn widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = (1..=self.selected.len())
+            .map(|col| header(col, false).chars().count())
+            .collect();
+        for row in self.rows() {
+            for (col, val) in row.iter().enumerate() {
+                widths[col] = widths[col].max(val.chars().count().min(MAX_COLUMN_WIDTH));
+            }
+        }
+        widths
+    }
+
+    // The chosen columns as a selector, runs of neighbours merged into
+    // ranges.
+    fn selector(&self) -> FieldSelector {
+        let mut fields: Vec<FieldRange> = vec![];
+        for (col, _) in self.selected.iter().enumerate().filter(|(_, on)| **on) {
+            let idx = col as isize + 1;
+            match fields.last_mut() {
+                Some(range) if range.stop == idx - 1 => range.stop = idx,
+                _ => fields.push(FieldRange::new_val(idx)),
+            }
+        }
+        FieldSelector {
+            fields,
+            ..Default::default()
+        }
+    }
+
+    // The rcut command line that cuts what's chosen, or None if nothing
+    // is.
+    fn command_line(&self) -> Option<Vec<OsString>> {
+        if !self.selected.contains(&true) {
+            return None;
+        }
+        let mut argv = vec![OsString::from("rcut")];
+        if let Delimiter::String(ref delim) = self.delimiter {
+            argv.push(OsString::from("-d"));
+            argv.push(OsString::from(delim));
+        }
+        argv.push(OsString::from("-f"));
+        argv.push(OsString::from(self.selector().describe()));
+        if self.file.to_string_lossy().starts_with('-') {
+            argv.push(OsString::from("--"));
+        }
+        argv.push(self.file.clone());
+        Some(argv)
+    }
+
+    fn handle(&mut self, key: KeyEvent) -> Option<Outcome> {
+        self.message = None;
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Some(Outcome::Quit);
+        }
+        if let Some(ref mut typed) = self.editing {
+            match key.code {
+                KeyCode::Enter => {
+                    self.delimiter = if typed.is_empty() {
+                        Delimiter::Whitespace
+                    } else {
+                        Delimiter::String(typed.clone())
+                    };
+                    self.editing = None;
+                    self.resplit();
+                }
+                KeyCode::Esc => self.editing = None,
+                KeyCode::Backspace => {
+                    typed.pop();
+                }
+                KeyCode::Tab => typed.push('\t'),
+                KeyCode::Char(c) => typed.push(c),
+                _ => {}
+            }
+            return None;
+        }
+        let columns = self.selected.len();
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') if self.cursor + 1 < columns => self.cursor += 1,
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = columns.saturating_sub(1),
+            KeyCode::Char(' ') if columns > 0 => {
+                self.selected[self.cursor] = !self.selected[self.cursor]
+            }
+            KeyCode::Char('a') => {
+                let on = !self.selected.iter().all(|on| *on);
+                self.selected.iter_mut().for_each(|sel| *sel = on);
+            }
+            KeyCode::Char('d') => self.editing = Some(String::new()),
+            KeyCode::Enter if self.command_line().is_some() => return Some(Outcome::Accept),
+            KeyCode::Enter => self.message = Some(tr("select at least one column first")),
+            KeyCode::Esc | KeyCode::Char('q') => return Some(Outcome::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    // Slide the view sideways so the cursor's column is on screen.
+    fn scroll_into_view(&mut self, widths: &[usize], screen_width: usize) {
+        self.offset = self.offset.min(self.cursor);
+        while self.offset < self.cursor
+            && widths[self.offset..=self.cursor]
+                .iter()
+                .map(|w| w + GUTTER)
+                .sum::<usize>()
+                > screen_width
+        {
+            self.offset += 1;
+        }
+    }
+
+    fn draw(&mut self, out: &mut impl Write, (width, height): (u16, u16)) -> Result<()> {
+        let (width, height) = (width as usize, height as usize);
+        let widths = self.widths();
+        self.scroll_into_view(&widths, width);
+        queue!(out, terminal::Clear(ClearType::All))?;
+
+        self.draw_row(out, 0, &widths, width, |col| {
+            let attr = if col == self.cursor {
+                Attribute::Reverse
+            } else {
+                Attribute::Reset
+            };
+            (header(col + 1, self.selected[col]), attr)
+        })?;
+        let rows = self.rows();
+        for (y, row) in rows.iter().take(height.saturating_sub(3)).enumerate() {
+            self.draw_row(out, y + 1, &widths, width, |col| {
+                let attr = if self.selected[col] {
+                    Attribute::Reset
+                } else {
+                    Attribute::Dim
+                };
+                (String::from(row.get(col).map_or("", AsRef::as_ref)), attr)
+            })?;
+        }
+
+        let status = match (self.message, self.command_line()) {
+            (Some(message), _) => String::from(message),
+            (None, Some(argv)) => shell_command(&argv),
+            (None, None) => String::new(),
+        };
+        let prompt = match self.editing {
+            Some(ref typed) => format!(
+                "{} {}",
+                tr("new delimiter (empty for whitespace):"),
+                typed.escape_debug()
+            ),
+            None => String::from(tr(
+                "←/→ move  space toggle  a all/none  d delimiter  enter accept  q quit",
+            )),
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(2) as u16),
+            Print(clip(&status, width)),
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            Print(clip(&prompt, width))
+        )?;
+        out.flush()?;
+        Ok(())
+    }
+
+    // Draw one line of the table, from the first column on screen to the
+    // right edge, asking `cell` for each column's text and look.
+    fn draw_row(
+        &self,
+        out: &mut impl Write,
+        y: usize,
+        widths: &[usize],
+        screen_width: usize,
+        cell: impl Fn(usize) -> (String, Attribute),
+    ) -> Result<()> {
+        let mut x = 0;
+        for (col, width) in widths.iter().enumerate().skip(self.offset) {
+            if x >= screen_width {
+                break;
+            }
+            let (val, attr) = cell(col);
+            queue!(
+                out,
+                cursor::MoveTo(x as u16, y as u16),
+                SetAttribute(attr),
+                Print(clip(&pad(&val, *width), screen_width - x)),
+                SetAttribute(Attribute::Reset)
+            )?;
+            x += width + GUTTER;
+        }
+        Ok(())
+    }
+}
+
+// A column's header, marked when it's chosen.
+fn header(col: usize, selected: bool) -> String {
+    format!("[{}] {}", if selected { 'x' } else { ' ' }, col)
+}
+
+// Fit a value to exactly `width` characters, keeping control
+// characters from upsetting the screen.
+fn pad(val: &str, width: usize) -> String {
+    let mut padded: String = val
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(width)
+        .collect();
+    let len = padded.chars().count();
+    padded.extend(std::iter::repeat_n(' ', width - len));
+    padded
+}
+
+fn clip(val: &str, width: usize) -> String {
+    val.chars().take(width).collect()
+}
+
+// Quote an argument for a POSIX shell, leaving it bare when that's
+// safe.
+fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./,:=+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        String::from(arg)
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+// A command line as it would be typed into a shell.
+pub fn shell_command(argv: &[OsString]) -> String {
+    argv.iter()
+        .map(|arg| shell_quote(&arg.to_string_lossy()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// Show the start of the file in a full-screen picker and return the
+// rcut command line for the columns chosen, or None if the user quit.
+// The screen is drawn on stderr, so stdout is left for the command.
+pub fn pick(args: &PickArgs) -> Result<Option<Vec<OsString>>> {
+    if !io::stderr().is_terminal() {
+        bail!(tr("rcut pick needs a terminal"));
+    }
+    let name = args.file.to_string_lossy().into_owned();
+    let lines = open_input(&args.file)?
+        .lines()
+        .take(PREVIEW_LINES)
+        .collect::<io::Result<Vec<String>>>()
+        .with_context(|| InputFile(name))?;
+    let delimiter = args.delimiter.clone().unwrap_or(Delimiter::Whitespace);
+    let mut picker = Picker::new(args.file.clone(), lines, delimiter);
+
+    match with_screen(|screen| event_loop(&mut picker, screen))? {
+        Outcome::Accept => Ok(picker.command_line()),
+        Outcome::Quit => Ok(None),
+    }
+}
+
+// Take over the terminal for a full-screen UI drawn on stderr, and put
+// it back afterwards even if drawing failed.
+pub fn with_screen<T>(ui: impl FnOnce(&mut io::Stderr) -> Result<T>) -> Result<T> {
+    let mut screen = io::stderr();
+    terminal::enable_raw_mode()?;
+    queue!(screen, terminal::EnterAlternateScreen, cursor::Hide)?;
+    let result = ui(&mut screen);
+    queue!(screen, cursor::Show, terminal::LeaveAlternateScreen)?;
+    screen.flush()?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn event_loop(picker: &mut Picker, screen: &mut impl Write) -> Result<Outcome> {
+    loop {
+        picker.draw(screen, terminal::size()?)?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            if let Some(outcome) = picker.handle(key) {
+                return Ok(outcome);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn press(picker: &mut Picker, code: KeyCode) -> Option<Outcome> {
+        picker.handle(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn picker() -> Picker {
+        Picker::new(
+            OsString::from("data.txt"),
+            vec![String::from("a:b:c:d"), String::from("e:f:g")],
+            Delimiter::Whitespace,
+        )
+    }
+
+    #[test]
+    fn test_picking() {
+        let mut picker = picker();
+        assert_eq!(picker.selected.len(), 1);
+        assert_eq!(press(&mut picker, KeyCode::Enter), None);
+        assert!(picker.message.is_some());
+
+        press(&mut picker, KeyCode::Char('d'));
+        press(&mut picker, KeyCode::Char(':'));
+        press(&mut picker, KeyCode::Enter);
+        assert_eq!(picker.selected.len(), 4);
+        for code in [
+            KeyCode::Char(' '),
+            KeyCode::Right,
+            KeyCode::Char(' '),
+            KeyCode::End,
+            KeyCode::Char(' '),
+            KeyCode::Right,
+        ] {
+            press(&mut picker, code);
+        }
+        assert_eq!(picker.cursor, 3);
+        assert_eq!(press(&mut picker, KeyCode::Enter), Some(Outcome::Accept));
+        assert_eq!(
+            shell_command(&picker.command_line().unwrap()),
+            "rcut -d : -f 1-2,4 data.txt"
+        );
+    }
+
+    #[test]
+    fn test_scroll_into_view() {
+        let mut picker = picker();
+        picker.cursor = 3;
+        picker.scroll_into_view(&[10, 10, 10, 10], 25);
+        assert_eq!(picker.offset, 2);
+        picker.cursor = 0;
+        picker.scroll_into_view(&[10, 10, 10, 10], 25);
+        assert_eq!(picker.offset, 0);
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("a.csv"), "a.csv");
+        assert_eq!(shell_quote(" | "), "' | '");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    proptest! {
+        // The printed command has to mean the same thing when it's run,
+        // here as split by `rcut serve --spec`, which quotes the same way.
+        #[test]
+        fn test_shell_quote_round_trip(argv in prop::collection::vec(any::<String>(), 0..6)) {
+            let argv: Vec<OsString> = argv.into_iter().map(OsString::from).collect();
+            let words = crate::serve::split_words(&shell_command(&argv)).unwrap();
+            prop_assert_eq!(words.into_iter().map(OsString::from).collect::<Vec<_>>(), argv);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+
+use crate::{diagnostics::NotUtf8, FieldSelector};
+
+// What -b and -c count positions in, instead of splitting into
+// fields.  Characters are Unicode scalar values, so `é` is one
+// whichever way it's encoded in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Bytes,
+    Characters,
+}
+
+impl Unit {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Unit::Bytes => "bytes",
+            Unit::Characters => "characters",
+        }
+    }
+}
+
+// The runs of consecutive offsets a selector picks out of `len`
+// positions, or with `complement` leaves out, as inclusive (first,
+// last) pairs.  They're in line order and overlaps are taken once, as
+// cut takes them, whatever order the selector names them in.
+fn runs(selector: &FieldSelector, len: usize, complement: bool) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = vec![];
+    for (offset, chosen) in selector.chosen(len).into_iter().enumerate() {
+        match runs.last_mut() {
+            _ if chosen == complement => continue,
+            Some((_, last)) if *last + 1 == offset => *last = offset,
+            _ => runs.push((offset, offset)),
+        }
+    }
+    runs
+}
+
+// Cut the positions the selector names out of a line, or with
+// `complement` those it doesn't, one piece for each run.  Byte ranges
+// are taken as they are, as `cut -b` takes them, so a piece that ends
+// partway through a character isn't text, which only a job that just
+// prints bytes can write.
+pub fn cut<'a>(
+    line: &'a str,
+    selector: &FieldSelector,
+    unit: Unit,
+    complement: bool,
+) -> Result<Vec<&'a str>> {
+    let runs = |len| runs(selector, len, complement);
+    match unit {
+        Unit::Bytes => runs(line.len())
+            .into_iter()
+            .map(|(first, last)| line.get(first..last + 1).ok_or(NotUtf8.into()))
+            .collect(),
+        Unit::Characters => {
+            let starts: Vec<usize> = line.char_indices().map(|(start, _)| start).collect();
+            Ok(runs(starts.len())
+                .into_iter()
+                .map(|(first, last)| {
+                    &line[starts[first]..starts.get(last + 1).copied().unwrap_or(line.len())]
+                })
+                .collect())
+        }
+    }
+}
+
+// Cut byte positions out of a line that isn't text, where there are no
+// characters to keep whole.
+pub fn cut_bytes<'a>(line: &'a [u8], selector: &FieldSelector, complement: bool) -> Vec<&'a [u8]> {
+    runs(selector, line.len(), complement)
+        .into_iter()
+        .map(|(first, last)| &line[first..=last])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field_parser;
+
+    fn cut_bytes<'a>(line: &'a str, selector: &str) -> Vec<&'a [u8]> {
+        super::cut_bytes(line.as_bytes(), &field_parser(selector).unwrap(), false)
+    }
+
+    #[test]
+    fn test_cut_characters() {
+        let cut_chars = |line: &str, selector: &str| -> Vec<String> {
+            cut(
+                line,
+                &field_parser(selector).unwrap(),
+                Unit::Characters,
+                false,
+            )
+            .unwrap()
+            .into_iter()
+            .map(String::from)
+            .collect()
+        };
+        assert_eq!(cut_chars("naïve café", "1-5"), vec!["naïve"]);
+        assert_eq!(cut_chars("naïve café", "-4"), vec!["c"]);
+        assert_eq!(
+            cut_chars("日本語テキスト", "2-3,6-99"),
+            vec!["本語", "スト"]
+        );
+        assert!(cut_chars("", "1").is_empty());
+        let selector = field_parser("2-3").unwrap();
+        assert_eq!(
+            cut("naïve", &selector, Unit::Characters, true).unwrap(),
+            vec!["n", "ve"]
+        );
+    }
+
+    #[test]
+    fn test_cut_bytes() {
+        assert_eq!(cut_bytes("abcdef", "1-3,5"), vec![&b"abc"[..], b"e"]);
+        // As with cut, positions come out in line order, once each, and
+        // those next to each other run together.
+        assert_eq!(cut_bytes("abcdef", "2,3"), vec![&b"bc"[..]]);
+        assert_eq!(cut_bytes("abc", "3,1"), vec![&b"a"[..], b"c"]);
+        assert_eq!(cut_bytes("abc", "1-3,2"), vec![&b"abc"[..]]);
+        assert_eq!(cut_bytes("abcdef", "-2"), vec![&b"e"[..]]);
+        assert_eq!(cut_bytes("abc", "2-99"), vec![&b"bc"[..]]);
+        assert!(cut_bytes("abc", "5").is_empty());
+        // é is two bytes, at 2 and 3.
+        assert_eq!(cut_bytes("aéb", "1-2"), vec![&b"a\xc3"[..]]);
+        assert_eq!(cut_bytes("aéb", "1-3"), vec!["aé".as_bytes()]);
+        assert_eq!(cut_bytes("aéb", "3-4"), vec![&b"\xa9b"[..]]);
+        let selector = field_parser("1-2").unwrap();
+        assert!(cut("aéb", &selector, Unit::Bytes, false).is_err());
+        assert_eq!(
+            cut("aéb", &field_parser("2-3,1").unwrap(), Unit::Bytes, false).unwrap(),
+            vec!["aé"]
+        );
+    }
+}
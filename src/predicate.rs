@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::{
+    aggregate::{parse_number, NumericLocale},
+    end_parser,
+    i18n::tr,
+    i18n::trf,
+    resolve_index,
+};
+
+// What --where tests a field with.
+#[derive(Debug, Clone)]
+enum Test {
+    // A comparison with a value, which is numeric when both sides are
+    // numbers and textual otherwise.  The value's number, if it is one,
+    // is read once, as plainly as numbers in --expr are.
+    Compare(&'static str, String, Option<f64>),
+    // A regular expression the field has to match, or with `!~` must
+    // not.
+    Matches(Regex, bool),
+}
+
+// One --where: `FIELD OP VALUE`, where FIELD is numbered as -f numbers
+// fields and OP is one of `==`, `!=`, `<`, `<=`, `>`, `>=`, `~` or
+// `!~`.  A field the line doesn't have is empty.
+#[derive(Debug, Clone)]
+pub struct Predicate {
+    source: String,
+    field: isize,
+    test: Test,
+    locale: NumericLocale,
+}
+
+// Longest first, so `<=` isn't read as `<` then `=`.
+const OPERATORS: &[&str] = &["==", "!=", "<=", ">=", "!~", "<", ">", "~"];
+
+pub fn parse_predicate(s: &str) -> Result<Predicate> {
+    let invalid = || format_err!(trf("invalid condition '{}': expected FIELD OP VALUE", &[s]));
+    let at = s.find(['=', '!', '<', '>', '~']).ok_or_else(invalid)?;
+    let (field, rest) = s.split_at(at);
+    let op = OPERATORS
+        .iter()
+        .find(|op| rest.starts_with(**op))
+        .ok_or_else(invalid)?;
+    let value = rest[op.len()..].trim();
+    let field = end_parser(field.trim()).map_err(|_| invalid())?;
+    if field == 0 {
+        bail!(tr("fields are numbered from 1"));
+    }
+    let test = match *op {
+        "~" | "!~" => Test::Matches(Regex::new(value)?, *op == "~"),
+        op => Test::Compare(op, String::from(value), parse_number(value)),
+    };
+    Ok(Predicate {
+        source: String::from(s),
+        field,
+        test,
+        locale: NumericLocale::C,
+    })
+}
+
+impl Predicate {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Read numbers in fields as `locale` writes them.
+    pub fn with_locale(mut self, locale: NumericLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub fn holds(&self, fields: &[&str]) -> bool {
+        let field = resolve_index(self.field, fields.len()).map_or("", |i| fields[i]);
+        match self.test {
+            Test::Matches(ref re, matching) => re.is_match(field) == matching,
+            Test::Compare(op, ref value, number) => {
+                let ordering = match (self.locale.parse(field), number) {
+                    (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+                    _ => field.cmp(value.as_str()),
+                };
+                match op {
+                    "==" => ordering == Ordering::Equal,
+                    "!=" => ordering != Ordering::Equal,
+                    "<" => ordering == Ordering::Less,
+                    "<=" => ordering != Ordering::Greater,
+                    ">" => ordering == Ordering::Greater,
+                    _ => ordering != Ordering::Less,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holds(predicate: &str, fields: &[&str]) -> bool {
+        parse_predicate(predicate).unwrap().holds(fields)
+    }
+
+    #[test]
+    fn test_predicates() {
+        let fields = ["web1", "ERROR", "250", "disk full"];
+        assert!(holds("2==ERROR", &fields));
+        assert!(holds(" 2 != WARN ", &fields));
+        assert!(holds("3>100", &fields));
+        assert!(!holds("3<=99.5", &fields));
+        assert!(holds("3==250.0", &fields));
+        assert!(holds("last~^disk", &fields));
+        assert!(holds("-1!~^net", &fields));
+        assert!(holds("1<web2", &fields));
+        assert!(holds("9==", &fields));
+        assert!(!holds("9>0", &fields));
+        for bad in ["2", "x==1", "0==1", "2=ERROR", "1~(", "==1"] {
+            assert!(parse_predicate(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_locale() {
+        let predicate = parse_predicate("1>1000").unwrap();
+        assert!(!predicate.holds(&["1.234,5"]));
+        assert!(predicate.with_locale(NumericLocale::De).holds(&["1.234,5"]));
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    ffi::OsString,
+    fs::File,
+    io::{BufWriter, Write},
+};
+
+use anyhow::{Context, Result};
+
+use crate::diagnostics::InputFile;
+
+// The file --rejects quarantines lines in, those that can't be cut, each
+// written as it was read.  It's only created once there is something to
+// put in it, or at the end of the run, so that a job that never gets to
+// read anything leaves no file behind.
+#[derive(Debug)]
+pub struct Rejects {
+    path: OsString,
+    file: Option<BufWriter<File>>,
+    count: u64,
+}
+
+impl Rejects {
+    pub fn new(path: OsString) -> Self {
+        Rejects {
+            path,
+            file: None,
+            count: 0,
+        }
+    }
+
+    fn file(&mut self) -> Result<&mut BufWriter<File>> {
+        if self.file.is_none() {
+            let file = File::create(&self.path).with_context(|| self.name())?;
+            self.file = Some(BufWriter::new(file));
+        }
+        Ok(self.file.as_mut().expect("just created"))
+    }
+
+    fn name(&self) -> InputFile {
+        InputFile(self.path.to_string_lossy().into_owned())
+    }
+
+    pub fn write(&mut self, line: &[u8], terminator: &str) -> Result<()> {
+        let name = self.name();
+        let file = self.file()?;
+        file.write_all(line)
+            .and_then(|_| file.write_all(terminator.as_bytes()))
+            .context(name)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    // Make sure the file holds this run's rejects, even if there were
+    // none, and return how many there were.
+    pub fn finish(&mut self) -> Result<u64> {
+        let name = self.name();
+        self.file()?.flush().context(name)?;
+        Ok(self.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects() {
+        let path = std::env::temp_dir().join(format!("rcut-rejects-{}", std::process::id()));
+        let mut rejects = Rejects::new(path.clone().into_os_string());
+        assert!(!path.exists());
+        assert_eq!(rejects.finish().unwrap(), 0);
+        assert_eq!(std::fs::read(&path).unwrap(), b"");
+        rejects.write(b"a,\xff", "\n").unwrap();
+        rejects.write(b"b", "\n").unwrap();
+        assert_eq!(rejects.finish().unwrap(), 2);
+        assert_eq!(std::fs::read(&path).unwrap(), b"a,\xff\nb\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
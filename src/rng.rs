@@ -0,0 +1,55 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+// Every random choice rcut makes comes from here, so that one --seed
+// makes a whole run reproducible.  Without one, a seed is picked at
+// random and logged, so that a run can still be repeated afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seed(pub u64);
+
+impl Seed {
+    pub fn new(seed: Option<u64>) -> Self {
+        Seed(seed.unwrap_or_else(rand::random))
+    }
+
+    // A generator for one feature.  Each feature gets a stream of its
+    // own, so using one doesn't change the choices another makes.  The
+    // algorithm is named rather than StdRng, which rand is free to
+    // change, so a seed means the same thing in every build.
+    pub fn rng(&self, feature: &str) -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(self.0 ^ fnv1a(feature))
+    }
+}
+
+// A hash that, unlike std's, is the same on every platform and release.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn test_seed() {
+        let draw = |seed: Seed, feature| seed.rng(feature).gen::<u64>();
+        assert_eq!(draw(Seed(42), "sample"), draw(Seed(42), "sample"));
+        assert_ne!(draw(Seed(42), "sample"), draw(Seed(43), "sample"));
+        assert_ne!(draw(Seed(42), "sample"), draw(Seed(42), "shuffle"));
+        // A run saved with --seed has to make the same choices when it's
+        // repeated, so the stream itself mustn't change under us.
+        let mut rng = Seed(42).rng("sample");
+        assert_eq!(
+            [rng.gen::<u64>(), rng.gen::<u64>(), rng.gen::<u64>()],
+            [
+                6627797285555041550,
+                16043981038527498176,
+                1875881305712496465
+            ]
+        );
+    }
+}
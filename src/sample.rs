@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{aggregate::Aggregate, memory::row_size, resolve_index, rng::Seed};
+
+// What `rcut sample` asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleSpec {
+    pub count: usize,
+    pub stratify: Option<isize>,
+}
+
+// A uniform sample of up to `count` of the records offered to it, by
+// reservoir sampling (Algorithm R).  Each record is kept alongside its
+// position in the input so the sample can be printed in input order.
+#[derive(Debug, Default)]
+struct Reservoir {
+    seen: u64,
+    rows: Vec<(u64, Vec<String>)>,
+    bytes: usize,
+}
+
+impl Reservoir {
+    fn offer(&mut self, count: usize, record: u64, fields: &[&str], rng: &mut ChaCha8Rng) {
+        self.seen += 1;
+        let slot = if self.rows.len() < count {
+            None
+        } else {
+            match rng.gen_range(0..self.seen) as usize {
+                slot if slot < count => Some(slot),
+                _ => return,
+            }
+        };
+        let row = (
+            record,
+            fields.iter().map(|val| String::from(*val)).collect(),
+        );
+        self.bytes += row_size(fields);
+        match slot {
+            Some(slot) => {
+                self.bytes -= row_size(&self.rows[slot].1);
+                self.rows[slot] = row;
+            }
+            None => self.rows.push(row),
+        }
+    }
+}
+
+// Sample the output records, separately for each value of the
+// --stratify field if there is one, before handing the sample to
+// whatever aggregate is in use.  Where the first record is the header,
+// it's kept out of the sample and always comes first.
+#[derive(Debug)]
+pub struct Sample {
+    spec: SampleSpec,
+    rng: ChaCha8Rng,
+    records: u64,
+    strata: HashMap<String, Reservoir>,
+    // What the reservoirs hold between them.
+    bytes: usize,
+    named: bool,
+    header: Option<Vec<String>>,
+    inner: Option<Box<dyn Aggregate>>,
+}
+
+impl Sample {
+    pub fn new(spec: SampleSpec, seed: Seed, inner: Option<Box<dyn Aggregate>>) -> Self {
+        Sample {
+            spec,
+            rng: seed.rng("sample"),
+            records: 0,
+            strata: HashMap::new(),
+            bytes: 0,
+            named: false,
+            header: None,
+            inner,
+        }
+    }
+
+    pub fn with_header(mut self, named: bool) -> Self {
+        self.named = named;
+        self
+    }
+}
+
+impl Aggregate for Sample {
+    fn add(&mut self, fields: &[&str]) -> Result<()> {
+        if self.named && self.header.is_none() {
+            self.header = Some(fields.iter().map(|name| String::from(*name)).collect());
+            return Ok(());
+        }
+        self.records += 1;
+        let stratum = self
+            .spec
+            .stratify
+            .and_then(|field| resolve_index(field, fields.len()))
+            .map(|offset| String::from(fields[offset]))
+            .unwrap_or_default();
+        let reservoir = self.strata.entry(stratum).or_default();
+        let before = reservoir.bytes;
+        reservoir.offer(self.spec.count, self.records, fields, &mut self.rng);
+        self.bytes = self.bytes + reservoir.bytes - before;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<Vec<Vec<String>>> {
+        self.bytes = 0;
+        let mut rows: Vec<(u64, Vec<String>)> = self
+            .strata
+            .drain()
+            .flat_map(|(_, reservoir)| reservoir.rows)
+            .collect();
+        rows.sort_by_key(|(record, _)| *record);
+        let rows = self
+            .header
+            .take()
+            .into_iter()
+            .chain(rows.into_iter().map(|(_, row)| row));
+        match self.inner {
+            Some(ref mut inner) => {
+                for row in rows {
+                    inner.add(&row.iter().map(String::as_str).collect::<Vec<_>>())?;
+                }
+                inner.finish()
+            }
+            None => Ok(rows.collect()),
+        }
+    }
+
+    fn footprint(&self) -> usize {
+        self.bytes + self.inner.as_ref().map_or(0, |inner| inner.footprint())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(spec: SampleSpec, input: &[[&str; 2]]) -> Vec<Vec<String>> {
+        let mut sample = Sample::new(spec, Seed(42), None);
+        for row in input {
+            sample.add(row).unwrap();
+        }
+        sample.finish().unwrap()
+    }
+
+    #[test]
+    fn test_sample() {
+        let input: Vec<[&str; 2]> = (0..100)
+            .map(|i| {
+                if i % 10 == 0 {
+                    ["rare", "x"]
+                } else {
+                    ["common", "x"]
+                }
+            })
+            .collect();
+        let spec = SampleSpec {
+            count: 5,
+            stratify: None,
+        };
+        let rows = sample(spec, &input);
+        assert_eq!(rows.len(), 5);
+        assert_eq!(rows, sample(spec, &input));
+
+        let rows = sample(
+            SampleSpec {
+                stratify: Some(1),
+                ..spec
+            },
+            &input,
+        );
+        assert_eq!(rows.iter().filter(|row| row[0] == "rare").count(), 5);
+        assert_eq!(rows.iter().filter(|row| row[0] == "common").count(), 5);
+
+        // Asking for more than there is gets everything, in order.
+        let rows = sample(SampleSpec { count: 10, ..spec }, &input[..3]);
+        assert_eq!(
+            rows,
+            vec![vec!["rare", "x"], vec!["common", "x"], vec!["common", "x"]]
+        );
+    }
+
+    #[test]
+    fn test_sample_header() {
+        let input: Vec<[&str; 2]> = (0..50)
+            .map(|i| {
+                if i % 2 == 0 {
+                    ["even", "x"]
+                } else {
+                    ["odd", "x"]
+                }
+            })
+            .collect();
+        for stratify in [None, Some(1)] {
+            for seed in 0..20 {
+                let spec = SampleSpec { count: 3, stratify };
+                let mut sample = Sample::new(spec, Seed(seed), None).with_header(true);
+                sample.add(&["kind", "value"]).unwrap();
+                for row in &input {
+                    sample.add(row).unwrap();
+                }
+                let rows = sample.finish().unwrap();
+                assert_eq!(rows[0], vec!["kind", "value"]);
+                // Three records a stratum on top of the header, which
+                // isn't one of them.
+                let strata = if stratify.is_some() { 2 } else { 1 };
+                assert_eq!(rows.len(), 1 + 3 * strata);
+                assert!(rows[1..].iter().all(|row| row[0] != "kind"));
+            }
+        }
+    }
+}
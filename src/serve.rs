@@ -0,0 +1,242 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    thread,
+};
+
+use anyhow::{Context, Result};
+use tracing::{info, warn};
+
+use crate::{
+    cli::ServeArgs,
+    i18n::tr,
+    i18n::trf,
+    job_from_args,
+    metrics::{self, Metrics},
+    parse_cli, InputMode,
+};
+
+// Request bodies larger than this are turned away.
+const MAX_BODY: usize = 64 << 20;
+
+// Split a spec into words the way a POSIX shell would, minus
+// everything but quoting: 'single', "double" and backslash escapes.
+pub fn split_words(s: &str) -> Result<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '\'' | '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some(end) if end == c => break,
+                        Some('\\') if c == '"' => word.extend(chars.next()),
+                        Some(other) => word.push(other),
+                        None => bail!(trf("unterminated quote in '{}'", &[s])),
+                    }
+                }
+            }
+            '\\' => word.get_or_insert_with(String::new).extend(chars.next()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+// The rcut command line a spec stands for, checked once up front so a
+// bad spec fails at startup rather than on every request.  Requests
+// bring their own input, so the spec can't name any.
+pub fn spec_argv(spec: &str) -> Result<Vec<String>> {
+    let mut argv = vec![String::from("rcut")];
+    argv.extend(split_words(spec)?);
+    let (_, inputs, run_options) = job_from_args(parse_cli(Some(argv.clone()))?.into_args()?)?;
+    if !inputs.is_empty()
+        || run_options.join.is_some()
+        || run_options.tee.is_some()
+        || run_options.dry_run
+        || run_options.verify_against_cut
+        || run_options.input_mode != InputMode::Sequential
+    {
+        bail!(tr(
+            "a serve spec can only say how to cut, not what to read or where to write"
+        ));
+    }
+    Ok(argv)
+}
+
+// Cut the body of each POST with the spec and send back the result.
+// Every request gets a fresh job, so aggregates and record numbers
+// start over each time.
+pub fn serve(args: &ServeArgs) -> Result<()> {
+    let argv = spec_argv(&args.spec)?;
+    let metrics = Arc::new(Metrics::default());
+    if let Some(ref sink) = args.metrics {
+        metrics::export(sink.clone(), Arc::clone(&metrics), "serve")?;
+    }
+    let listener = TcpListener::bind(&args.bind).with_context(|| args.bind.clone())?;
+    info!(bind = %args.bind, spec = %args.spec, "listening");
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!(%err, "accept failed");
+                continue;
+            }
+        };
+        let argv = argv.clone();
+        let metrics = Arc::clone(&metrics);
+        thread::spawn(move || {
+            let peer = stream.peer_addr().ok();
+            if let Err(err) = handle(&argv, &stream, &metrics) {
+                warn!(?peer, %err, "request failed");
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle(argv: &[String], stream: &TcpStream, metrics: &Metrics) -> Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut writer = stream;
+    respond(argv, &mut reader, &mut writer, metrics)
+}
+
+// Read one request and write its response.
+fn respond(
+    argv: &[String],
+    reader: &mut impl BufRead,
+    writer: &mut impl Write,
+    metrics: &Metrics,
+) -> Result<()> {
+    let (status, body) = match read_request(reader)? {
+        Err(status) => (status, String::new()),
+        Ok(body) => match cut(argv, &body) {
+            Ok((output, records)) => {
+                metrics.read(records, body.len() as u64);
+                ("200 OK", output)
+            }
+            Err(err) => {
+                metrics.read(0, body.len() as u64);
+                metrics.error();
+                ("422 Unprocessable Entity", format!("{:#}\n", err))
+            }
+        },
+    };
+    info!(status, bytes = body.len(), "response");
+    write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    writer.flush()?;
+    Ok(())
+}
+
+// The body of a POST, or the status to refuse anything else with.
+fn read_request(reader: &mut impl BufRead) -> Result<Result<Vec<u8>, &'static str>> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut length = None;
+    let mut chunked = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let (name, value) = (name.trim().to_ascii_lowercase(), value.trim());
+            if name == "content-length" {
+                length = value.parse::<usize>().ok();
+            } else if name == "transfer-encoding" {
+                chunked = true;
+            }
+        }
+    }
+    let mut words = request_line.split_whitespace();
+    let (method, path) = (words.next(), words.next());
+    if method.is_none() || path.is_none() {
+        return Ok(Err("400 Bad Request"));
+    }
+    if method != Some("POST") {
+        return Ok(Err("405 Method Not Allowed"));
+    }
+    let length = match length {
+        Some(length) if !chunked => length,
+        _ => return Ok(Err("411 Length Required")),
+    };
+    if length > MAX_BODY {
+        return Ok(Err("413 Payload Too Large"));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok(body))
+}
+
+// The cut body, and how many records it had.
+fn cut(argv: &[String], body: &[u8]) -> Result<(String, u64)> {
+    let (job, _, _) = job_from_args(parse_cli(Some(argv.to_vec()))?.into_args()?)?;
+    let mut output = vec![];
+    let records = job.process_reader(body, &mut output)?;
+    job.finish(&mut output)?;
+    Ok((String::from_utf8(output)?, records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(spec: &str, request: &str) -> String {
+        let argv = spec_argv(spec).unwrap();
+        let mut response = vec![];
+        respond(
+            &argv,
+            &mut request.as_bytes(),
+            &mut response,
+            &Metrics::default(),
+        )
+        .unwrap();
+        String::from_utf8(response).unwrap()
+    }
+
+    #[test]
+    fn test_split_words() {
+        assert_eq!(
+            split_words(r#"-d ' ' -o "|" -f 1\ 2"#).unwrap(),
+            vec!["-d", " ", "-o", "|", "-f", "1 2"]
+        );
+        assert_eq!(split_words("''").unwrap(), vec![""]);
+        assert!(split_words("-d 'x").is_err());
+    }
+
+    #[test]
+    fn test_spec_argv() {
+        assert!(spec_argv("-d : -f 1").is_ok());
+        assert!(spec_argv("-d : -f 1 /etc/passwd").is_err());
+        assert!(spec_argv("--paste -f 1").is_err());
+        assert!(spec_argv("-f x").is_err());
+    }
+
+    #[test]
+    fn test_respond() {
+        let response = request(
+            "-d : -f 1,3",
+            "POST /cut HTTP/1.1\r\nHost: x\r\nContent-Length: 12\r\n\r\na:b:c\nd:e:f\n",
+        );
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\na:c\nd:f\n"));
+        assert!(request("-f 1", "GET / HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405"));
+        assert!(request("-f 1", "POST / HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 411"));
+        let response = request(
+            "--expr 'print 1 / $1'",
+            "POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\n0\n",
+        );
+        assert!(response.starts_with("HTTP/1.1 422"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    fmt,
+    io::{self, Write},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+
+use crate::i18n::{tr, trf};
+
+// Set from the signal handlers, and acted on between lines.
+static FLAGS: OnceLock<Flags> = OnceLock::new();
+
+// The --timeout, once its timer is running.
+static TIMER: OnceLock<Duration> = OnceLock::new();
+
+#[derive(Debug, Default)]
+struct Flags {
+    interrupted: Arc<AtomicBool>,
+    report: Arc<AtomicBool>,
+    timed_out: Arc<AtomicBool>,
+}
+
+// Exit status after SIGINT, as the shells report a process it killed.
+pub const INTERRUPTED_STATUS: i32 = 130;
+
+// The error a job stops with after SIGINT, once its output so far has
+// been flushed.
+#[derive(Debug)]
+pub struct Interrupted;
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", tr("interrupted"))
+    }
+}
+
+impl std::error::Error for Interrupted {}
+
+// Exit status after --timeout, as timeout(1) uses.
+pub const TIMED_OUT_STATUS: i32 = 124;
+
+// How long a job gets to stop by itself once its time is up, before
+// it's stopped outright, say when it's stuck reading a stalled pipe.
+const GRACE: Duration = Duration::from_secs(2);
+
+// The error a job stops with when --timeout runs out, once its output
+// so far has been flushed.
+#[derive(Debug)]
+pub struct TimedOut;
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", tr("timed out"))
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+// Parse a duration like `60s`, `500ms`, `5m` or `1h`; a bare number
+// is seconds.
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let invalid = || format_err!(trf("invalid duration '{}'", &[s]));
+    let split = s
+        .trim()
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.trim().len());
+    let (number, suffix) = s.trim().split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let scale = match suffix.trim() {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" | "min" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(invalid()),
+    };
+    Duration::try_from_secs_f64(number * scale)
+        .ok()
+        .filter(|limit| !limit.is_zero())
+        .ok_or_else(invalid)
+}
+
+// Time the job out after `limit`: it stops at the next line, like
+// after SIGINT, or a little later it's made to exit.
+pub fn start_timer(limit: Duration) {
+    let flags = FLAGS.get_or_init(Flags::default);
+    let timed_out = Arc::clone(&flags.timed_out);
+    let _ = TIMER.set(limit);
+    thread::spawn(move || {
+        thread::sleep(limit);
+        timed_out.store(true, Ordering::Relaxed);
+        thread::sleep(GRACE);
+        let _ = writeln!(io::stderr(), "rcut: {}", tr("timed out"));
+        process::exit(TIMED_OUT_STATUS);
+    });
+}
+
+pub fn timer_started() -> bool {
+    TIMER.get().is_some()
+}
+
+pub fn timed_out() -> bool {
+    FLAGS
+        .get()
+        .is_some_and(|flags| flags.timed_out.load(Ordering::Relaxed))
+}
+
+// Where a job is in its input, for SIGUSR1.
+#[derive(Debug, Default)]
+pub struct Progress {
+    pub input: String,
+    // Where this run started reading the input, when resuming.
+    pub start: u64,
+    pub offset: u64,
+}
+
+impl Progress {
+    pub fn report(&self, written: u64) {
+        let input = if self.input.is_empty() {
+            "<stdin>"
+        } else {
+            self.input.as_str()
+        };
+        // Nothing sensible to do if stderr itself is gone.
+        let _ = writeln!(
+            io::stderr(),
+            "rcut: {} {}, {} {}, {} {}",
+            tr("reading"),
+            input,
+            tr("offset"),
+            self.offset,
+            tr("records written"),
+            written
+        );
+    }
+}
+
+// Catch SIGINT, to stop cleanly between lines, and SIGUSR1, to report
+// progress.  A second SIGINT kills the process outright, for when
+// it's stuck waiting on input and never gets to the next line.
+pub fn install() -> Result<()> {
+    let flags = FLAGS.get_or_init(Flags::default);
+    signal_hook::flag::register_conditional_default(
+        signal_hook::consts::SIGINT,
+        Arc::clone(&flags.interrupted),
+    )?;
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&flags.interrupted))?;
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGUSR1, Arc::clone(&flags.report))?;
+    Ok(())
+}
+
+pub fn interrupted() -> bool {
+    FLAGS
+        .get()
+        .is_some_and(|flags| flags.interrupted.load(Ordering::Relaxed))
+}
+
+// Whether progress has been asked for since last time.
+pub fn take_report() -> bool {
+    FLAGS
+        .get()
+        .is_some_and(|flags| flags.report.swap(false, Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("60s").unwrap(), Duration::from_secs(60));
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1.5m").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        for bad in ["", "0", "-1s", "5 fortnights", "1e400"] {
+            assert!(parse_duration(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+
+use anyhow::Result;
+
+use crate::{field_parser, i18n::trf, resolve_index, FieldSelector};
+
+// What one --subfield asked for: output field N, numbered as -f numbers
+// fields, split on a delimiter of its own into pieces, of which those
+// selected are kept, joined back with the same delimiter.
+#[derive(Debug, Clone)]
+pub struct Subfield {
+    pub field: isize,
+    pub delim: String,
+    pub pieces: FieldSelector,
+}
+
+// Parse `N:DELIM:PIECES`, where PIECES is a selector like -f takes.
+// DELIM runs to the first colon after its first character, so it can be
+// a colon itself, as in `2:::1`.
+pub fn parse_subfield(s: &str) -> Result<Subfield> {
+    let invalid = || format_err!(trf("invalid subfield '{}': expected N:DELIM:PIECES", &[s]));
+    let (field, rest) = s.split_once(':').ok_or_else(invalid)?;
+    let split = rest
+        .char_indices()
+        .skip(1)
+        .find(|(_, c)| *c == ':')
+        .map(|(at, _)| at)
+        .ok_or_else(invalid)?;
+    let (delim, pieces) = (&rest[..split], &rest[split + 1..]);
+    let field = field
+        .trim()
+        .parse::<isize>()
+        .ok()
+        .filter(|idx| *idx != 0)
+        .ok_or_else(invalid)?;
+    if pieces.is_empty() {
+        return Err(invalid());
+    }
+    Ok(Subfield {
+        field,
+        delim: String::from(delim),
+        pieces: field_parser(pieces)?,
+    })
+}
+
+impl Subfield {
+    pub fn describe(&self) -> String {
+        format!(
+            "field {} on {:?}, pieces {}",
+            self.field,
+            self.delim,
+            self.pieces.describe()
+        )
+    }
+
+    fn pick<'a>(&self, field: &'a str) -> Vec<&'a str> {
+        let pieces: Vec<&str> = field.split(self.delim.as_str()).collect();
+        self.pieces.select(&pieces)
+    }
+}
+
+// The output fields with each subfield taken in turn, so that a field
+// can be split again after it's been split once.  A subfield of a field
+// the record doesn't have does nothing, and one with no pieces
+// selected is empty.
+pub fn apply_subfields<'a>(subfields: &[Subfield], fields: &[&'a str]) -> Vec<Cow<'a, str>> {
+    let mut split: Vec<Cow<'a, str>> = fields.iter().map(|field| Cow::Borrowed(*field)).collect();
+    for subfield in subfields {
+        let Some(offset) = resolve_index(subfield.field, split.len()) else {
+            continue;
+        };
+        // A single piece of a field straight from the line is still a
+        // slice of it.
+        let value = match split[offset] {
+            Cow::Borrowed(field) => match subfield.pick(field).as_slice() {
+                [piece] => Cow::Borrowed(*piece),
+                pieces => Cow::Owned(pieces.join(&subfield.delim)),
+            },
+            Cow::Owned(ref field) => Cow::Owned(subfield.pick(field).join(&subfield.delim)),
+        };
+        split[offset] = value;
+    }
+    split
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_subfield() {
+        let subfield = parse_subfield("3:/:1").unwrap();
+        assert_eq!((subfield.field, subfield.delim.as_str()), (3, "/"));
+        assert_eq!(subfield.pieces.describe(), "1");
+        let subfield = parse_subfield("-1:::2-").unwrap();
+        assert_eq!((subfield.field, subfield.delim.as_str()), (-1, ":"));
+        assert_eq!(parse_subfield("2:, :1-4:2").unwrap().delim, ", ");
+        for bad in ["0:/:1", "x:/:1", "3:/:", "3:/", "3::1", "3:/:x"] {
+            assert!(parse_subfield(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_apply_subfields() {
+        let subfields = [
+            parse_subfield("2:@:2").unwrap(),
+            parse_subfield("2:.:1").unwrap(),
+            parse_subfield("-1:/:-1").unwrap(),
+            parse_subfield("1:/:2,1").unwrap(),
+            parse_subfield("9:/:1").unwrap(),
+        ];
+        assert_eq!(
+            apply_subfields(&subfields, &["a/b", "ann@web1.example", "/var/log/x"]),
+            vec!["b/a", "web1", "x"]
+        );
+        assert_eq!(
+            apply_subfields(&subfields[..1], &["", "no-at-sign"]),
+            vec!["", ""]
+        );
+    }
+}
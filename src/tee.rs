@@ -0,0 +1,67 @@
+use std::io::{self, BufRead, Read, Write};
+
+// A reader that copies everything read through it, byte for byte, to a
+// second writer, so the raw input can be archived while it's being cut.
+// Errors writing the copy can't be reported from consume(), so they're
+// held until the next read.
+pub struct Tee<'a, R> {
+    inner: R,
+    copy: Option<&'a mut dyn Write>,
+    error: Option<io::Error>,
+}
+
+impl<'a, R: BufRead> Tee<'a, R> {
+    pub fn new(inner: R, copy: Option<&'a mut dyn Write>) -> Self {
+        Tee {
+            inner,
+            copy,
+            error: None,
+        }
+    }
+}
+
+impl<R: BufRead> Read for Tee<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for Tee<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        if let Some(ref mut copy) = self.copy {
+            if let Ok(buf) = self.inner.fill_buf() {
+                if let Err(err) = copy.write_all(&buf[..amt]) {
+                    self.error.get_or_insert(err);
+                }
+            }
+        }
+        self.inner.consume(amt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tee() {
+        let mut copy = vec![];
+        let lines: Vec<String> = Tee::new("a b\n\nc\r\n".as_bytes(), Some(&mut copy))
+            .lines()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(lines, vec!["a b", "", "c"]);
+        assert_eq!(copy, b"a b\n\nc\r\n");
+    }
+}
@@ -0,0 +1,106 @@
+// An output template for --format, like `{1}\t{3}:{2}`: literal text
+// with the fields of a line filled in wherever one is named in braces,
+// as often as it's named.  A field is named as -f names one, so `{-1}`
+// or `{last}` is the last; `{{` and `}}` are literal braces.
+
+use anyhow::Result;
+
+use crate::{cli::unescape, end_parser, i18n::tr, resolve_index};
+
+#[derive(Debug, Clone)]
+enum Piece {
+    Text(String),
+    Field(isize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+    pieces: Vec<Piece>,
+}
+
+// Parse a --format template, reading the escapes in it as --escapes
+// reads them in -o.
+pub fn parse_template(s: &str) -> Result<Template> {
+    let mut pieces = vec![];
+    let mut text = String::new();
+    let unescaped = unescape(s)?;
+    let mut chars = unescaped.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let (name, rest) = chars
+                    .as_str()
+                    .split_once('}')
+                    .ok_or_else(|| format_err!(tr("a { in the template is never closed")))?;
+                let index = end_parser(name.trim())?;
+                if index == 0 {
+                    bail!(tr("fields are numbered from 1"));
+                }
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Field(index));
+                chars = rest.chars();
+            }
+            '}' => bail!(tr("a } in the template has to be doubled, as }}")),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(Template {
+        source: String::from(s),
+        pieces,
+    })
+}
+
+impl Template {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    // Fill in a line's fields, where one the line doesn't have is
+    // left empty.
+    pub fn render(&self, fields: &[&str]) -> String {
+        let mut rendered = String::new();
+        for piece in &self.pieces {
+            match piece {
+                Piece::Text(text) => rendered.push_str(text),
+                Piece::Field(index) => {
+                    if let Some(offset) = resolve_index(*index, fields.len()) {
+                        rendered.push_str(fields[offset]);
+                    }
+                }
+            }
+        }
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template() {
+        let fields = ["a", "b", "c"];
+        let render = |s: &str| parse_template(s).unwrap().render(&fields);
+        assert_eq!(render(r"{1}\t{3}:{2}"), "a\tc:b");
+        assert_eq!(render("{1}{1} {-1} {last} { n-1 }"), "aa c c b");
+        assert_eq!(render("{{{2}}} {9}!"), "{b} !");
+        assert_eq!(render("plain"), "plain");
+        for bad in ["{1", "}", "{0}", "{x}", "{}", r"\q"] {
+            assert!(parse_template(bad).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    io::{self, Write},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+
+use crate::i18n::trf;
+
+// How far ahead of the rate output can get before it's held back, so
+// a slow rate doesn't mean a sleep after every line.
+const SLACK: Duration = Duration::from_millis(20);
+
+// What --throttle is limiting: bytes of output, or records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Bytes,
+    Records,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub per_second: f64,
+    pub unit: Unit,
+}
+
+// How many bytes a size suffix like `MB` or `KiB` stands for; the
+// suffix is lowercase.
+pub fn byte_scale(suffix: &str) -> Option<f64> {
+    match suffix {
+        "b" => Some(1.0),
+        "kb" | "k" => Some(1e3),
+        "mb" | "m" => Some(1e6),
+        "gb" | "g" => Some(1e9),
+        "kib" => Some(1024.0),
+        "mib" => Some(1024.0 * 1024.0),
+        "gib" => Some(1024.0 * 1024.0 * 1024.0),
+        _ => None,
+    }
+}
+
+// Parse a rate like `10MB/s`, `512KiB/s` or `1000/s`: a number of bytes
+// with a size suffix, or else of records, per second.
+pub fn parse_rate(s: &str) -> Result<Rate> {
+    let invalid = || format_err!(trf("invalid rate '{}'", &[s]));
+    let amount = s
+        .trim()
+        .strip_suffix("/s")
+        .or_else(|| s.trim().strip_suffix("/sec"))
+        .unwrap_or(s.trim());
+    let split = amount
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(amount.len());
+    let (number, suffix) = amount.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let (scale, unit) = match suffix.trim().to_ascii_lowercase().as_str() {
+        "" | "records" | "lines" => (1.0, Unit::Records),
+        suffix => (byte_scale(suffix).ok_or_else(invalid)?, Unit::Bytes),
+    };
+    if number <= 0.0 {
+        return Err(invalid());
+    }
+    Ok(Rate {
+        per_second: number * scale,
+        unit,
+    })
+}
+
+// A writer that paces what goes through it to no more than a given
+// rate, flushing what it has before each pause so that whatever reads
+// the output gets it steadily rather than in bursts.  Without a rate it
+// just passes everything on.
+pub struct Throttle<W> {
+    inner: W,
+    rate: Option<Rate>,
+    // What ends each record, for counting records.
+    terminator: Vec<u8>,
+    started: Option<Instant>,
+    sent: f64,
+}
+
+impl<W: Write> Throttle<W> {
+    pub fn new(inner: W, rate: Option<Rate>, terminator: &[u8]) -> Self {
+        Throttle {
+            inner,
+            rate,
+            terminator: terminator.to_vec(),
+            started: None,
+            sent: 0.0,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    // Wait until the output so far is within the rate.
+    fn pace(&mut self, rate: Rate) -> io::Result<()> {
+        let started = *self.started.get_or_insert_with(Instant::now);
+        let due = Duration::from_secs_f64(self.sent / rate.per_second);
+        let elapsed = started.elapsed();
+        if due > elapsed + SLACK {
+            self.inner.flush()?;
+            thread::sleep(due - elapsed);
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for Throttle<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let rate = match self.rate {
+            Some(rate) => rate,
+            None => return self.inner.write(buf),
+        };
+        let len = self.inner.write(buf)?;
+        self.sent += match rate.unit {
+            Unit::Bytes => len as f64,
+            Unit::Records => {
+                memchr::memmem::find_iter(&buf[..len], &self.terminator).count() as f64
+            }
+        };
+        self.pace(rate)?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate() {
+        let rate = |s| parse_rate(s).unwrap();
+        assert_eq!(rate("10MB/s").per_second, 1e7);
+        assert_eq!(rate("10MB/s").unit, Unit::Bytes);
+        assert_eq!(rate("2KiB/s").per_second, 2048.0);
+        assert_eq!(rate("1000/s").unit, Unit::Records);
+        assert_eq!(rate("0.5 records/sec").per_second, 0.5);
+        for bad in ["", "fast", "10XB/s", "0/s", "-5/s"] {
+            assert!(parse_rate(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_throttle() {
+        let started = Instant::now();
+        let mut output = Throttle::new(vec![], parse_rate("100/s").ok(), b"\n");
+        for _ in 0..10 {
+            output.write_all(b"x\n").unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(70));
+        assert_eq!(output.inner, b"x\n".repeat(10));
+    }
+}
@@ -0,0 +1,322 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, BufRead, IsTerminal, Write},
+    path::PathBuf,
+    sync::{mpsc, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, ClearType},
+};
+
+use crate::{
+    cli::TopArgs,
+    default_separator,
+    diagnostics::InputFile,
+    i18n::tr,
+    metrics::{self, Metrics},
+    open_input,
+    pick::with_screen,
+    Delimiter,
+};
+
+// Rates are worked out over this much of the recent past.
+const WINDOW: Duration = Duration::from_secs(10);
+// How often the screen is redrawn, and a followed file checked.
+const TICK: Duration = Duration::from_millis(250);
+
+// Running counts of each distinct value, overall and within the
+// rate window.
+#[derive(Debug)]
+struct Tally {
+    started: Instant,
+    total: u64,
+    counts: HashMap<String, u64>,
+    recent: VecDeque<(Instant, String)>,
+    recent_counts: HashMap<String, u64>,
+}
+
+// One line of the dashboard.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    value: String,
+    count: u64,
+    share: f64,
+    rate: f64,
+}
+
+impl Tally {
+    fn new(now: Instant) -> Self {
+        Tally {
+            started: now,
+            total: 0,
+            counts: HashMap::new(),
+            recent: VecDeque::new(),
+            recent_counts: HashMap::new(),
+        }
+    }
+
+    fn add(&mut self, value: String, now: Instant) {
+        self.total += 1;
+        *self.counts.entry(value.clone()).or_default() += 1;
+        *self.recent_counts.entry(value.clone()).or_default() += 1;
+        self.recent.push_back((now, value));
+    }
+
+    // Forget whatever has fallen out of the rate window.
+    fn expire(&mut self, now: Instant) {
+        while let Some((when, _)) = self.recent.front() {
+            if now.duration_since(*when) <= WINDOW {
+                break;
+            }
+            let (_, value) = self.recent.pop_front().expect("front exists");
+            if let Some(count) = self.recent_counts.get_mut(&value) {
+                *count -= 1;
+                if *count == 0 {
+                    self.recent_counts.remove(&value);
+                }
+            }
+        }
+    }
+
+    // Seconds of the rate window that have actually gone by.
+    fn window(&self, now: Instant) -> f64 {
+        now.duration_since(self.started)
+            .min(WINDOW)
+            .as_secs_f64()
+            .max(TICK.as_secs_f64())
+    }
+
+    fn rate(&self, now: Instant) -> f64 {
+        self.recent.len() as f64 / self.window(now)
+    }
+
+    // The `n` most common values, ties broken by value.
+    fn top(&mut self, n: usize, now: Instant) -> Vec<Row> {
+        self.expire(now);
+        let mut counts: Vec<(&String, &u64)> = self.counts.iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let window = self.window(now);
+        counts
+            .into_iter()
+            .take(n)
+            .map(|(value, count)| Row {
+                value: value.clone(),
+                count: *count,
+                share: *count as f64 * 100.0 / self.total as f64,
+                rate: self.recent_counts.get(value).copied().unwrap_or(0) as f64 / window,
+            })
+            .collect()
+    }
+}
+
+// Read lines on a thread of their own, so the screen keeps updating
+// while waiting on a quiet log.  When following, the end of the file
+// is only where it stops for now; a line is only sent once its
+// newline has arrived.
+fn spawn_reader(
+    mut input: Box<dyn BufRead + Send>,
+    name: String,
+    follow: bool,
+    metrics: Arc<Metrics>,
+) -> mpsc::Receiver<Result<String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        loop {
+            match input.read_line(&mut line) {
+                Ok(0) if follow => thread::sleep(TICK),
+                Ok(0) => return,
+                Ok(_) if !line.ends_with('\n') && follow => continue,
+                Ok(_) => {
+                    metrics.read(1, line.len() as u64);
+                    let complete = std::mem::take(&mut line);
+                    let complete = complete.strip_suffix('\n').unwrap_or(&complete);
+                    let complete = complete.strip_suffix('\r').unwrap_or(complete);
+                    if sender.send(Ok(String::from(complete))).is_err() {
+                        return;
+                    }
+                }
+                Err(err) => {
+                    metrics.error();
+                    let _ = sender.send(Err(anyhow::Error::new(err).context(InputFile(name))));
+                    return;
+                }
+            }
+        }
+    });
+    receiver
+}
+
+// A live dashboard of the most common values of the selected fields,
+// drawn on stderr.  When it's closed, the final counts are printed on
+// stdout, like `sort | uniq -c | sort -rn` would.
+pub fn top(args: &TopArgs) -> Result<()> {
+    if !io::stderr().is_terminal() {
+        bail!(tr("rcut top needs a terminal"));
+    }
+    let (input, name): (Box<dyn BufRead + Send>, String) = match args.file {
+        Some(ref file) => (
+            Box::new(open_input(file)?),
+            file.to_string_lossy().into_owned(),
+        ),
+        None if args.follow => bail!(tr("--follow needs a file to follow")),
+        None => (
+            Box::new(io::BufReader::new(io::stdin())),
+            String::from("<stdin>"),
+        ),
+    };
+    let followed = args
+        .file
+        .as_ref()
+        .filter(|_| args.follow)
+        .map(PathBuf::from);
+    let metrics = Arc::new(Metrics::new(followed));
+    if let Some(ref sink) = args.metrics {
+        metrics::export(sink.clone(), Arc::clone(&metrics), "top")?;
+    }
+    let lines = spawn_reader(input, name.clone(), args.follow, metrics);
+    let delimiter = args.delimiter.clone().unwrap_or(Delimiter::Whitespace);
+    let separator = default_separator(None, &args.delimiter);
+    let key = |line: &str| args.fields.select(&delimiter.split(line)).join(&separator);
+
+    let mut tally = Tally::new(Instant::now());
+    with_screen(|screen| {
+        let mut done = false;
+        loop {
+            while !done {
+                match lines.try_recv() {
+                    Ok(line) => tally.add(key(&line?), Instant::now()),
+                    Err(mpsc::TryRecvError::Empty) => break,
+                    Err(mpsc::TryRecvError::Disconnected) => done = true,
+                }
+            }
+            draw(screen, &mut tally, &name, done)?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    let ctrl_c = key.modifiers.contains(KeyModifiers::CONTROL)
+                        && key.code == KeyCode::Char('c');
+                    if key.kind != KeyEventKind::Release
+                        && (ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc))
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    })?;
+
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for row in tally.top(usize::MAX, Instant::now()) {
+        writeln!(stdout, "{} {}", row.count, row.value)?;
+    }
+    Ok(())
+}
+
+fn draw(screen: &mut impl Write, tally: &mut Tally, name: &str, done: bool) -> Result<()> {
+    let (width, height) = terminal::size()?;
+    let (width, height) = (width as usize, height as usize);
+    let now = Instant::now();
+    let state = if done { tr("finished") } else { tr("reading") };
+    let summary = format!(
+        "{}: {} {}, {:.1}/s ({})  —  {}",
+        name,
+        tally.total,
+        tr("lines"),
+        tally.rate(now),
+        state,
+        tr("q to quit")
+    );
+    queue!(
+        screen,
+        terminal::Clear(ClearType::All),
+        cursor::MoveTo(0, 0),
+        Print(clip(&summary, width)),
+        cursor::MoveTo(0, 2),
+        SetAttribute(Attribute::Reverse),
+        Print(clip(
+            &format!(
+                "{:>10} {:>6} {:>8}  {}",
+                tr("count"),
+                "%",
+                "/s",
+                tr("value")
+            ),
+            width
+        )),
+        SetAttribute(Attribute::Reset)
+    )?;
+    for (y, row) in tally
+        .top(height.saturating_sub(3), now)
+        .into_iter()
+        .enumerate()
+    {
+        let line = format!(
+            "{:>10} {:>6.1} {:>8.1}  {}",
+            row.count, row.share, row.rate, row.value
+        );
+        queue!(
+            screen,
+            cursor::MoveTo(0, y as u16 + 3),
+            Print(clip(&line, width))
+        )?;
+    }
+    screen.flush().context(tr("could not draw the screen"))?;
+    Ok(())
+}
+
+fn clip(val: &str, width: usize) -> String {
+    val.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .take(width)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tally() {
+        let start = Instant::now();
+        let mut tally = Tally::new(start);
+        for value in ["GET", "POST", "GET", "PUT", "GET", "POST"] {
+            tally.add(String::from(value), start);
+        }
+        let later = start + Duration::from_secs(5);
+        tally.add(String::from("PUT"), later);
+        let rows = tally.top(2, later);
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].value.as_str(), rows[0].count), ("GET", 3));
+        assert_eq!((rows[1].value.as_str(), rows[1].count), ("POST", 2));
+        assert!((rows[0].share - 3.0 * 100.0 / 7.0).abs() < 1e-9);
+        assert!((rows[0].rate - 3.0 / 5.0).abs() < 1e-9);
+
+        // Once the early burst leaves the window only the late PUT counts.
+        let much_later = start + Duration::from_secs(12);
+        let rows = tally.top(3, much_later);
+        assert_eq!(rows[2].value, "PUT");
+        assert_eq!(rows[0].rate, 0.0);
+        assert!((rows[2].rate - 0.1).abs() < 1e-9);
+        assert_eq!(tally.total, 7);
+    }
+
+    #[test]
+    fn test_spawn_reader() {
+        let input: Box<dyn BufRead + Send> = Box::new(io::Cursor::new("a 1\r\nb 2\nc"));
+        let metrics = Arc::new(Metrics::default());
+        let lines: Vec<String> = spawn_reader(input, String::from("x"), false, metrics)
+            .iter()
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(lines, vec!["a 1", "b 2", "c"]);
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+
+use crate::{end_parser, i18n::tr, i18n::trf, resolve_index};
+
+// A change --map makes to the text of a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transform {
+    Upper,
+    Lower,
+    Trim,
+    SqueezeSpace,
+}
+
+impl Transform {
+    fn name(self) -> &'static str {
+        match self {
+            Transform::Upper => "upper",
+            Transform::Lower => "lower",
+            Transform::Trim => "trim",
+            Transform::SqueezeSpace => "squeeze-space",
+        }
+    }
+
+    fn apply(self, value: &str) -> String {
+        match self {
+            Transform::Upper => value.to_uppercase(),
+            Transform::Lower => value.to_lowercase(),
+            Transform::Trim => String::from(value.trim()),
+            // Each run of whitespace becomes a single space.
+            Transform::SqueezeSpace => {
+                let mut squeezed = String::with_capacity(value.len());
+                let mut space = false;
+                for c in value.chars() {
+                    if c.is_whitespace() {
+                        space = true;
+                        continue;
+                    }
+                    if space {
+                        squeezed.push(' ');
+                        space = false;
+                    }
+                    squeezed.push(c);
+                }
+                if space {
+                    squeezed.push(' ');
+                }
+                squeezed
+            }
+        }
+    }
+}
+
+// What one --map asked for: a transform of one output field, numbered
+// as -f numbers fields, so `-1:trim` trims the last.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMap {
+    pub field: isize,
+    pub transform: Transform,
+}
+
+impl FieldMap {
+    pub fn describe(&self) -> String {
+        format!("field {} {}", self.field, self.transform.name())
+    }
+}
+
+// Parse `N:TRANSFORM`.
+pub fn parse_map(s: &str) -> Result<FieldMap> {
+    let (field, transform) = s
+        .rsplit_once(':')
+        .ok_or_else(|| format_err!(trf("invalid map '{}': expected N:TRANSFORM", &[s])))?;
+    let field = end_parser(field.trim())?;
+    if field == 0 {
+        bail!(tr("fields are numbered from 1"));
+    }
+    let transform = match transform.trim() {
+        "upper" => Transform::Upper,
+        "lower" => Transform::Lower,
+        "trim" => Transform::Trim,
+        "squeeze-space" => Transform::SqueezeSpace,
+        other => bail!(trf(
+            "unknown transform '{}': expected upper, lower, trim or squeeze-space",
+            &[other]
+        )),
+    };
+    Ok(FieldMap { field, transform })
+}
+
+// The output fields with each map applied in turn, so that two maps of
+// the same field both take effect.  A map of a field the record
+// doesn't have does nothing.
+pub fn apply_maps(maps: &[FieldMap], fields: &[&str]) -> Vec<String> {
+    let mut mapped: Vec<String> = fields.iter().map(|field| String::from(*field)).collect();
+    for map in maps {
+        if let Some(offset) = resolve_index(map.field, mapped.len()) {
+            mapped[offset] = map.transform.apply(&mapped[offset]);
+        }
+    }
+    mapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_map() {
+        assert_eq!(
+            parse_map("2:upper").unwrap(),
+            FieldMap {
+                field: 2,
+                transform: Transform::Upper
+            }
+        );
+        assert_eq!(parse_map("last:trim").unwrap().field, -1);
+        assert_eq!(
+            parse_map("-2:squeeze-space").unwrap().transform,
+            Transform::SqueezeSpace
+        );
+        for bad in ["0:upper", "2:shout", "upper", "x:lower", ":trim"] {
+            assert!(parse_map(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_apply_maps() {
+        let maps = [
+            parse_map("1:trim").unwrap(),
+            parse_map("1:upper").unwrap(),
+            parse_map("-1:squeeze-space").unwrap(),
+            parse_map("9:lower").unwrap(),
+        ];
+        assert_eq!(
+            apply_maps(&maps, &["  ab c ", "b", "x \t y  "]),
+            vec!["AB C", "b", "x y "]
+        );
+        assert_eq!(apply_maps(&maps, &[]), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    ffi::OsString,
+    io::{self, Read, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    diagnostics::InputFile, i18n::tr, i18n::trf, open_input, positions::Unit, CutJob, Delimiter,
+    Undelimited,
+};
+
+// The options --verify-against-cut can be given with: those cut has,
+// and those that change nothing about the output.  Anything else is
+// refused before a job is even built, so rcut options added later
+// can't slip past the comparison.
+const CUT_OPTIONS: &[&str] = &[
+    "fields",
+    "bytes",
+    "characters",
+    "positions",
+    "delimiter",
+    "escapes",
+    "output-separator",
+    "only-delimited",
+    "complement",
+    "zero-terminated",
+    "verify-against-cut",
+    "args",
+    "files",
+    "verbose",
+    "debug",
+    "error-format",
+    "help",
+    "version",
+];
+
+// Make --verify-against-cut, in a Command and its subcommands, conflict
+// with every option not in CUT_OPTIONS.
+pub fn cut_options_only(cmd: clap::Command<'static>) -> clap::Command<'static> {
+    let subcommands: Vec<String> = cmd
+        .get_subcommands()
+        .map(|sub| String::from(sub.get_name()))
+        .collect();
+    let cmd = subcommands.into_iter().fold(cmd, |cmd, name| {
+        cmd.mut_subcommand(name.as_str(), cut_options_only)
+    });
+    if !cmd
+        .get_arguments()
+        .any(|arg| arg.get_id() == "verify-against-cut")
+    {
+        return cmd;
+    }
+    let others: Vec<&'static str> = cmd
+        .get_arguments()
+        .map(|arg| arg.get_id())
+        .filter(|id| !CUT_OPTIONS.contains(id))
+        .collect();
+    cmd.mut_arg("verify-against-cut", |arg| arg.conflicts_with_all(&others))
+}
+
+// The arguments that make the system `cut` do what the job does, or an
+// error saying why it can't.  The options are all ones cut has, but not
+// every use of them is one it can follow.
+pub fn cut_args(job: &CutJob) -> Result<Vec<String>> {
+    let (flag, delim) = match job.unit {
+        Some(Unit::Bytes) if job.output_separator.is_empty() => ("-b", None),
+        // Most cuts count bytes even for -c, which comparing will show.
+        Some(Unit::Characters) if job.output_separator.is_empty() => ("-c", None),
+        Some(_) => bail!(tr(
+            "cut puts the pieces back together as they were, so drop -o"
+        )),
+        None => ("-f", Some(delimiter(job)?)),
+    };
+    if !job.selector.names.is_empty() {
+        bail!(tr("cut can't select columns by name"));
+    }
+    if !job.selector.excluded.is_empty() {
+        bail!(tr("cut can't knock fields out of a selection"));
+    }
+    let mut last = 0;
+    for range in job.selector.fields.iter() {
+        if range.start < 1 {
+            bail!(tr("cut can't count fields from the end"));
+        }
+        if range.step != 1 {
+            bail!(tr("cut can't step through a range"));
+        }
+        if range.start <= last || range.stop < range.start {
+            bail!(tr(
+                "cut prints fields in input order, so the selector must be ascending"
+            ));
+        }
+        last = range.stop;
+    }
+    let fields = if job.selector.fields.is_empty() {
+        String::from("1-")
+    } else {
+        job.selector.describe()
+    };
+    let mut args = vec![];
+    if let Some(delim) = delim {
+        args.extend([String::from("-d"), delim]);
+    }
+    args.extend([String::from(flag), fields]);
+    if job.complement {
+        args.push(String::from("--complement"));
+    }
+    if job.undelimited == Undelimited::Suppress {
+        args.push(String::from("-s"));
+    }
+    match job.terminator.as_str() {
+        "\n" => {}
+        "\0" => args.push(String::from("-z")),
+        _ => bail!(tr("cut only ends records with a newline, or NUL with -z")),
+    }
+    Ok(args)
+}
+
+// The single character cut splits fields on, which has to be the one
+// the job splits on and joins with.
+fn delimiter(job: &CutJob) -> Result<String> {
+    let delim = match job.input_delim {
+        Delimiter::String(ref delim) if delim.chars().count() == 1 => delim.clone(),
+        Delimiter::String(_) => bail!(tr("cut only splits on a single character")),
+        Delimiter::Regex(_) => bail!(tr("cut can't split on a regular expression")),
+        Delimiter::Csv(_) => bail!(tr("cut doesn't know CSV quoting")),
+        Delimiter::Logfmt(_) => bail!(tr("cut doesn't know logfmt")),
+        Delimiter::Fixed(_) => bail!(tr("cut can't cut columns by width")),
+        Delimiter::Limited { .. } => bail!(tr("cut can't stop splitting after so many fields")),
+        Delimiter::Extract(_) => bail!(tr("cut can't pick out capture groups")),
+        _ => bail!(tr("cut splits on tabs unless told otherwise, so give -d")),
+    };
+    if job.output_separator != delim {
+        bail!(tr(
+            "cut joins fields with the delimiter, so -o must match it"
+        ));
+    }
+    Ok(delim)
+}
+
+// Output split into the records it's compared by.
+fn records<'a>(output: &'a str, terminator: &str) -> Vec<&'a str> {
+    match terminator {
+        "\n" => output.lines().collect(),
+        terminator => output.split_terminator(terminator).collect(),
+    }
+}
+
+// Cut the inputs with both rcut and the system `cut`, print rcut's
+// output and fail if the two differ anywhere.
+pub fn verify(job: &CutJob, inputs: &[OsString], output: &mut impl Write) -> Result<()> {
+    let args = cut_args(job).context(tr("these options are not cut-compatible"))?;
+    let mut input = vec![];
+    if inputs.is_empty() {
+        io::stdin().lock().read_to_end(&mut input)?;
+    }
+    for filename in inputs {
+        open_input(filename)?
+            .read_to_end(&mut input)
+            .with_context(|| InputFile(filename.to_string_lossy().into_owned()))?;
+    }
+
+    let mut ours = vec![];
+    job.process_reader(input.as_slice(), &mut ours)?;
+    job.finish(&mut ours)?;
+    let theirs = run_cut(&args, input)?;
+    output.write_all(&ours)?;
+    output.flush()?;
+
+    let ours = String::from_utf8_lossy(&ours);
+    let theirs = String::from_utf8_lossy(&theirs);
+    let (ours, theirs) = (
+        records(&ours, &job.terminator),
+        records(&theirs, &job.terminator),
+    );
+    let lines = ours.len().max(theirs.len());
+    let differing: Vec<usize> = (0..lines)
+        .filter(|i| ours.get(*i) != theirs.get(*i))
+        .collect();
+    if let Some(first) = differing.first() {
+        let missing = tr("<no line>");
+        bail!(trf(
+            "rcut and cut disagree on {} of {} lines; the first is line {}:\n  rcut: {}\n  cut:  {}",
+            &[
+                &differing.len().to_string(),
+                &lines.to_string(),
+                &(first + 1).to_string(),
+                ours.get(*first).copied().unwrap_or(missing),
+                theirs.get(*first).copied().unwrap_or(missing),
+            ]
+        ));
+    }
+    Ok(())
+}
+
+// Feed the input to `cut` from another thread, so neither side blocks
+// on a full pipe, and collect what it prints.
+fn run_cut(args: &[String], input: Vec<u8>) -> Result<Vec<u8>> {
+    let mut child = Command::new("cut")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context(tr("could not run cut"))?;
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let result = child.wait_with_output().context(tr("could not run cut"))?;
+    // cut may exit without reading everything, which is its business.
+    let _ = writer.join();
+    if !result.status.success() {
+        bail!(trf(
+            "cut failed: {}",
+            &[String::from_utf8_lossy(&result.stderr).trim()]
+        ));
+    }
+    Ok(result.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_command_line;
+
+    fn args(argv: &[&str]) -> Result<Vec<String>> {
+        let (job, _, _) = parse_command_line(Some(argv.to_vec())).unwrap();
+        cut_args(&job)
+    }
+
+    #[test]
+    fn test_cut_args() {
+        assert_eq!(
+            args(&["rcut", "-d", ":", "-f", "1,3-4"]).unwrap(),
+            vec!["-d", ":", "-f", "1,3-4"]
+        );
+        assert_eq!(args(&["rcut", "-d", ","]).unwrap()[3], "1-");
+        assert!(args(&["rcut", "-f", "1"]).is_err());
+        assert!(args(&["rcut", "-d", "::", "-f", "1"]).is_err());
+        assert!(args(&["rcut", "-d", ":", "-o", " ", "-f", "1"]).is_err());
+        assert!(args(&["rcut", "-d", ":", "-f", "3,1"]).is_err());
+        assert!(args(&["rcut", "-d", ":", "-f", "-1"]).is_err());
+        assert_eq!(args(&["rcut", "-b", "1-3,5"]).unwrap(), vec!["-b", "1-3,5"]);
+        assert!(args(&["rcut", "-b", "1", "-o", ":"]).is_err());
+        assert_eq!(args(&["rcut", "-c", "2-4"]).unwrap(), vec!["-c", "2-4"]);
+        assert_eq!(
+            args(&["rcut", "-z", "-c", "2"]).unwrap(),
+            vec!["-c", "2", "-z"]
+        );
+    }
+
+    #[test]
+    fn test_cut_options_only() {
+        let verify = |extra: &[&str]| {
+            let argv = [
+                &["rcut", "--verify-against-cut", "-d", ",", "-f", "1"],
+                extra,
+            ]
+            .concat();
+            parse_command_line(Some(argv))
+        };
+        for ok in [
+            &[][..],
+            &["--complement"],
+            &["-s", "-z"],
+            &["-o", ","],
+            &["-v"],
+        ] {
+            assert!(verify(ok).is_ok(), "{:?}", ok);
+        }
+        for rcut_only in [
+            &["--pad", "x"][..],
+            &["--unique"],
+            &["--count"],
+            &["-H"],
+            &["--line-numbers"],
+            &["--output-format", "json"],
+            &["--strict"],
+            &["--csv"],
+            &["--header"],
+            &["--sort-by", "1"],
+            &["--where", "1==a"],
+        ] {
+            assert!(verify(rcut_only).is_err(), "{:?}", rcut_only);
+        }
+        assert!(
+            parse_command_line(Some(vec!["rcut", "bytes", "--verify-against-cut", "1-2"])).is_ok()
+        );
+        assert!(parse_command_line(Some(vec![
+            "rcut",
+            "bytes",
+            "--verify-against-cut",
+            "--unique",
+            "1-2"
+        ]))
+        .is_err());
+    }
+}
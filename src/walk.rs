@@ -0,0 +1,202 @@
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::{diagnostics::InputFile, i18n::trf};
+
+// A shell-style pattern for --include and --exclude, matched against
+// a file's name without its directory: `*` is any run of characters,
+// `?` any one, and `[...]` any one of those listed, or with `[!...]`
+// any one not listed.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    source: String,
+    re: Regex,
+}
+
+pub fn parse_glob(s: &str) -> Result<Glob> {
+    let mut pattern = String::from("^");
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            '[' => {
+                pattern.push('[');
+                let mut class = chars.clone().peekable();
+                if let Some('!' | '^') = class.peek() {
+                    pattern.push('^');
+                    chars.next();
+                }
+                let mut closed = false;
+                let mut empty = true;
+                for c in chars.by_ref() {
+                    match c {
+                        ']' if !empty => {
+                            closed = true;
+                            break;
+                        }
+                        '-' if !empty => pattern.push('-'),
+                        c => pattern.push_str(&regex::escape(&c.to_string())),
+                    }
+                    empty = false;
+                }
+                if !closed {
+                    bail!(trf("invalid glob '{}': a [ is never closed", &[s]));
+                }
+                pattern.push(']');
+            }
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Ok(Glob {
+        source: String::from(s),
+        re: Regex::new(&pattern)?,
+    })
+}
+
+impl Glob {
+    fn matches(&self, path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| self.re.is_match(&name.to_string_lossy()))
+    }
+}
+
+// Which of the files -r finds are cut: those matching an --include, if
+// there are any, and no --exclude.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+}
+
+impl Filter {
+    pub fn describe(&self) -> String {
+        let globs = |globs: &[Glob]| {
+            let sources: Vec<&str> = globs.iter().map(|glob| glob.source.as_str()).collect();
+            sources.join(" ")
+        };
+        let mut description = String::from("walked");
+        if !self.include.is_empty() {
+            description += &format!(", including {}", globs(&self.include));
+        }
+        if !self.exclude.is_empty() {
+            description += &format!(", excluding {}", globs(&self.exclude));
+        }
+        description
+    }
+
+    fn wants(&self, path: &Path) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|glob| glob.matches(path)))
+            && !self.exclude.iter().any(|glob| glob.matches(path))
+    }
+}
+
+// The files under a directory, found as they're asked for and in name
+// order, so that each run, and so a checkpoint of one, goes through
+// them in the same order.  Symbolic links to files are followed, but
+// those to directories aren't, which could otherwise go round forever.
+pub struct Walk<'a> {
+    filter: &'a Filter,
+    // Still to visit, the next last, each with whether it's a directory.
+    pending: Vec<(PathBuf, bool)>,
+}
+
+impl<'a> Walk<'a> {
+    pub fn new(root: &Path, filter: &'a Filter) -> Self {
+        Walk {
+            filter,
+            pending: vec![(root.to_path_buf(), true)],
+        }
+    }
+
+    fn read_dir(&mut self, dir: &Path) -> Result<()> {
+        let name = || InputFile(dir.to_string_lossy().into_owned());
+        let mut entries = fs::read_dir(dir)
+            .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+            .with_context(name)?;
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries.into_iter().rev() {
+            let path = entry.path();
+            let kind = entry.file_type().with_context(name)?;
+            if kind.is_dir() {
+                self.pending.push((path, true));
+            } else if kind.is_file() || (kind.is_symlink() && path.is_file()) {
+                self.pending.push((path, false));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Result<OsString>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((path, dir)) = self.pending.pop() {
+            if dir {
+                if let Err(err) = self.read_dir(&path) {
+                    return Some(Err(err));
+                }
+            } else if self.filter.wants(&path) {
+                return Some(Ok(path.into_os_string()));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        let matches = |glob: &str, name: &str| parse_glob(glob).unwrap().matches(Path::new(name));
+        assert!(matches("*.csv", "dir/a.csv"));
+        assert!(!matches("*.csv", "a.csv.gz"));
+        assert!(matches("a?c", "abc"));
+        assert!(matches("log[0-9].txt", "log7.txt"));
+        assert!(!matches("log[!0-9].txt", "log7.txt"));
+        assert!(matches("[]x]", "]"));
+        assert!(matches("a.b+(c)", "a.b+(c)"));
+        assert!(!matches("a.b", "axb"));
+        assert!(parse_glob("log[0-9").is_err());
+    }
+
+    #[test]
+    fn test_walk() {
+        let root = std::env::temp_dir().join(format!("rcut-walk-{}", std::process::id()));
+        for file in ["b.csv", "a/z.csv", "a/y.txt", "a/b/x.csv", "c.csv.bak"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, "").unwrap();
+        }
+        let walked = |filter: &Filter| -> Vec<String> {
+            Walk::new(&root, filter)
+                .map(|path| {
+                    let path = PathBuf::from(path.unwrap());
+                    let relative = path.strip_prefix(&root).unwrap();
+                    relative.to_string_lossy().replace('\\', "/")
+                })
+                .collect()
+        };
+        assert_eq!(
+            walked(&Filter::default()),
+            ["a/b/x.csv", "a/y.txt", "a/z.csv", "b.csv", "c.csv.bak"]
+        );
+        let filter = Filter {
+            include: vec![parse_glob("*.csv*").unwrap()],
+            exclude: vec![parse_glob("*.bak").unwrap(), parse_glob("z*").unwrap()],
+        };
+        assert_eq!(walked(&filter), ["a/b/x.csv", "b.csv"]);
+        fs::remove_dir_all(&root).unwrap();
+        assert!(Walk::new(&root, &filter).next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,209 @@
+// Golden tests against the system `cut`: generated inputs are cut by
+// both tools, across a matrix of flags in the subset of rcut that is
+// meant to behave like cut, and their output and exit status compared.
+// Skipped where `cut` isn't GNU cut.
+
+use std::{
+    env,
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+const DELIMITERS: [&str; 5] = [",", ":", ";", "\t", " "];
+const FIELDS: [&str; 11] = [
+    "1",
+    "2",
+    "3",
+    "1,3",
+    "2-3",
+    "1-2,4",
+    "1,2,3,4,5,6,7",
+    "2-10",
+    "5",
+    "2-",
+    "1,3-",
+];
+
+fn gnu_cut() -> bool {
+    Command::new("cut")
+        .arg("--version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("GNU"))
+        .unwrap_or(false)
+}
+
+// A small deterministic generator, so a failure can be reproduced.
+struct Lcg(u64);
+
+impl Lcg {
+    fn below(&mut self, n: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % n
+    }
+}
+
+// Lines of two to seven fields, some of them empty or not ASCII.  Every
+// line has at least one delimiter: a line with none is printed whole by
+// cut but is a single field to rcut.  Lines end in \n alone, since rcut
+// also takes \r\n as a line ending where cut keeps the \r.
+fn corpus(delim: &str, seed: u64) -> String {
+    let words = ["a", "bb", "", "ccc d", "é", "12", "x-y", "naïve", "  "];
+    let mut rng = Lcg(seed);
+    let mut text = String::new();
+    for _ in 0..50 {
+        let count = 2 + rng.below(6);
+        let fields: Vec<&str> = (0..count)
+            .map(|_| words[rng.below(words.len() as u64) as usize])
+            .filter(|word| !word.contains(delim))
+            .collect();
+        let mut line = fields.join(delim);
+        if !line.contains(delim) {
+            line.push_str(delim);
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
+
+// Run with stdin read from the file `stdin`, if any.
+fn run(program: &str, args: &[&str], stdin: Option<&str>) -> Output {
+    Command::new(program)
+        .args(args)
+        .stdin(stdin.map_or_else(Stdio::null, |path| File::open(path).unwrap().into()))
+        .env("LC_ALL", "C.UTF-8")
+        .output()
+        .unwrap_or_else(|err| panic!("running {}: {}", program, err))
+}
+
+fn assert_same(args: &[&str]) {
+    assert_same_as(args, args);
+}
+
+// Where rcut needs a flag to behave as cut does by default.
+fn assert_same_as(args: &[&str], cut_args: &[&str]) {
+    assert_same_reading(args, cut_args, None);
+}
+
+fn assert_same_reading(args: &[&str], cut_args: &[&str], stdin: Option<&str>) {
+    let ours = run(env!("CARGO_BIN_EXE_rcut"), args, stdin);
+    let theirs = run("cut", cut_args, stdin);
+    assert_eq!(
+        String::from_utf8_lossy(&ours.stdout),
+        String::from_utf8_lossy(&theirs.stdout),
+        "output of {:?}",
+        args
+    );
+    assert_eq!(
+        ours.status.code(),
+        theirs.status.code(),
+        "status of {:?}",
+        args
+    );
+}
+
+fn scratch_dir() -> PathBuf {
+    let dir = env::temp_dir().join(format!("rcut-gnu-cut-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write(dir: &Path, name: &str, text: &str) -> String {
+    let path = dir.join(name);
+    fs::write(&path, text).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_matches_gnu_cut() {
+    if !gnu_cut() {
+        eprintln!("skipping: no GNU cut");
+        return;
+    }
+    let dir = scratch_dir();
+    for (seed, delim) in DELIMITERS.iter().enumerate() {
+        let first = write(&dir, "first", &corpus(delim, seed as u64));
+        let second = write(&dir, "second", &corpus(delim, seed as u64 + 100));
+        for fields in FIELDS {
+            assert_same(&["-d", delim, "-f", fields, &first]);
+            assert_same(&["-d", delim, "-f", fields, &first, &second]);
+            assert_same(&["--complement", "-d", delim, "-f", fields, &first]);
+        }
+        let args = ["-d", delim, "-f", "2", &first, "-", &second];
+        assert_same_reading(&args, &args, Some(&second));
+    }
+    // Byte positions agree with cut's, even where they split a
+    // character.
+    let text = write(&dir, "text", &corpus(",", 7));
+    for bytes in ["1", "2-4", "1,3,5-7", "3-99", "40", "3,1", "1-3,2"] {
+        assert_same(&["-b", bytes, &text]);
+        assert_same(&["--complement", "-b", bytes, &text]);
+    }
+    let empty = write(&dir, "empty", "");
+    assert_same(&["-d", ",", "-f", "1", &empty]);
+    let unterminated = write(&dir, "unterminated", "a,b\nc,d");
+    assert_same(&["-d", ",", "-f", "2", &unterminated]);
+    let undelimited = write(&dir, "undelimited", "a,b\nnone\n\nc,d,e\n");
+    for fields in ["1", "2-"] {
+        assert_same(&["-s", "-d", ",", "-f", fields, &undelimited]);
+        assert_same_as(
+            &["--print-undelimited", "-d", ",", "-f", fields, &undelimited],
+            &["-d", ",", "-f", fields, &undelimited],
+        );
+    }
+    // With -z a newline is just another character of the record.
+    let nul = write(&dir, "nul", "a,b\nc,d\0none\0\0e,f\r\0g");
+    for fields in ["1", "2-"] {
+        assert_same(&["-z", "-s", "-d", ",", "-f", fields, &nul]);
+        assert_same_as(
+            &["-z", "--print-undelimited", "-d", ",", "-f", fields, &nul],
+            &["-z", "-d", ",", "-f", fields, &nul],
+        );
+    }
+    // Bytes that aren't UTF-8 are cut as they are.
+    let latin1 = dir.join("latin1");
+    fs::write(&latin1, b"caf\xe9,cr\xe8me,x\n\xff\xfe,\xe9t\xe9\n").unwrap();
+    let latin1 = latin1.to_string_lossy().into_owned();
+    for fields in ["2", "1,3-", "2-"] {
+        assert_same(&["-d", ",", "-f", fields, &latin1]);
+        assert_same(&["--complement", "-d", ",", "-f", fields, &latin1]);
+        assert_same(&["-b", fields, &latin1]);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_fails_like_gnu_cut() {
+    if !gnu_cut() {
+        eprintln!("skipping: no GNU cut");
+        return;
+    }
+    let missing = env::temp_dir()
+        .join(format!("rcut-gnu-cut-missing-{}", std::process::id()))
+        .to_string_lossy()
+        .into_owned();
+    let args = ["-d", ",", "-f", "1", missing.as_str()];
+    let ours = run(env!("CARGO_BIN_EXE_rcut"), &args, None);
+    let theirs = run("cut", &args, None);
+    assert_eq!(
+        ours.status.code(),
+        theirs.status.code(),
+        "status of {:?}",
+        args
+    );
+    assert_eq!(ours.stdout, theirs.stdout, "output of {:?}", args);
+    // A bad selector is a usage error, which rcut exits 2 for, the way
+    // clap programs do, where cut exits 1.
+    for fields in ["x", "1-x", ""] {
+        let args = ["-d", ",", "-f", fields, "/dev/null"];
+        let ours = run(env!("CARGO_BIN_EXE_rcut"), &args, None);
+        let theirs = run("cut", &args, None);
+        assert_eq!(ours.status.code(), Some(2), "status of {:?}", args);
+        assert_eq!(theirs.status.code(), Some(1), "status of {:?}", args);
+        assert!(ours.stdout.is_empty() && theirs.stdout.is_empty());
+    }
+}